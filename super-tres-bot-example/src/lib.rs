@@ -0,0 +1,61 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Bot de ejemplo que sigue la interfaz de `super_tres_engine::abi`.
+// Lee la posición del búfer con el propio motor y busca con alfa-beta poco profundo,
+// porque en el navegador se ejecuta interpretado.
+//
+//     cargo build --release -p super-tres-bot-example --target wasm32-unknown-unknown
+//
+// El módulo queda en `target/wasm32-unknown-unknown/release/super_tres_bot_example.wasm`
+// y se puede subir en la aplicación o usar en los torneos con `wasm:<fichero>`.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::ptr::addr_of_mut;
+use super_tres_engine::abi::{self, ABI_VERSION, BUFFER_BYTES};
+use super_tres_engine::search::{search, SearchLimits};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// EXPORTS
+
+
+/// Búsqueda corta: el intérprete limita las instrucciones de cada jugada
+const LIMITS: SearchLimits = SearchLimits { depth: 4, nodes: 5_000 };
+
+/// Búfer en el que el anfitrión escribe la posición
+static mut BUFFER: [u8; BUFFER_BYTES] = [0; BUFFER_BYTES];
+
+
+#[no_mangle]
+pub extern "C" fn st_abi_version() -> i32 {
+    ABI_VERSION
+}
+
+
+#[no_mangle]
+pub extern "C" fn st_buffer() -> *mut u8 {
+    addr_of_mut!(BUFFER) as *mut u8
+}
+
+
+#[no_mangle]
+pub extern "C" fn st_choose() -> i32 {
+    // SAFETY: el anfitrión solo escribe en el búfer entre llamadas, nunca durante una
+    let position = unsafe { &*addr_of_mut!(BUFFER) };
+
+    abi::decode_position(position)
+        .and_then(|game| search(&game, LIMITS).best_move())
+        .map_or(-1, abi::encode_move)
+}
@@ -0,0 +1,60 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Cuenta del servidor de partidas guardada en el navegador.
+// La clave la da el servidor al crear la cuenta y no se puede recuperar,
+// así que se guarda para volver a entrar solo al conectarse.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::session::storage;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ACCOUNT
+
+
+/// Clave del almacenamiento local en la que se guarda la cuenta
+const STORAGE_KEY: &str = "super-tres-account";
+
+
+/// Cuenta de un servidor
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Account {
+    pub server: String,
+    pub name: String,
+    pub key: String,
+}
+
+
+impl Account {
+
+    /// Cuenta guardada, si hay una
+    pub fn load() -> Option<Self> {
+        let value = storage()?.get_item(STORAGE_KEY).ok()??;
+        let mut fields = value.split_whitespace();
+        let account = Self {
+            server: fields.next()?.to_string(),
+            name: fields.next()?.to_string(),
+            key: fields.next()?.to_string(),
+        };
+        Some(account)
+    }
+
+
+    pub fn save(&self) {
+        if let Some(storage) = storage() {
+            let _ = storage.set_item(STORAGE_KEY, &format!("{} {} {}", self.server, self.name, self.key));
+        }
+    }
+}
@@ -1,27 +1,349 @@
 //-------------------------------------------------------------------//
 //  AUTHOR:    @sfmolina                                            //
-//  Version:   v2                                                  //
-//  Modified:  31dec24                                            //
+//  Version:   v3                                                  //
+//  Modified:  19oc26                                             //
 //---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Aplicación: la barra de navegación y la vista que se elige en ella, la
+// partida, el vestíbulo del servidor o su archivo de partidas. La conexión
+// con el servidor es de la aplicación y la comparten las vistas. La partida
+// sigue montada mientras se ven las otras.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
 
 
 
 use yew::prelude::*;
+use super_tres_engine::net::{ClientMessage, ServerMessage};
+use crate::account::Account;
 use crate::components::{
+    archive_view::ArchiveViewComponent,
+    lobby::LobbyComponent,
+    nav_bar::NavbarComponent,
+    server::ServerComponent,
     super_tres::SuperTresComponent,
-    nav_bar::NavbarComponent
 };
+use crate::connection::{Connection, Listener};
+use crate::session::Session;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// COMPONENT
+
+
+/// Vistas de la aplicación
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum View {
+
+    /// Partida en curso, local o en una sala del servidor
+    Game,
+
+    /// Conexión con el servidor y sus salas
+    Lobby,
+
+    /// Archivo de partidas terminadas del servidor
+    Archive,
+}
+
+
+/// Mensajes que puede recibir la aplicación
+pub enum AppMsg {
+    Show(View),
+    Connect(String),
+    Disconnect,
+    Disconnected(Option<String>),
+    Login(String),
+    Server(ServerMessage),
+    Left,
+}
+
 
+/// Información que guarda la aplicación
+pub struct App {
 
+    /// Vista que se muestra
+    view: View,
 
-#[function_component(App)]
-pub fn app() -> Html {
+    /// Conexión con el servidor de partidas en red
+    connection: Option<Connection>,
 
+    /// Escucha de los mensajes del servidor. Se deja de escuchar al descartarla.
+    _listener: Option<Listener>,
 
-    html! {
-        <>
-            <NavbarComponent />
-            <SuperTresComponent />
-        </>
+    /// Cuenta con la que se entró en el servidor
+    account: Option<String>,
+
+    /// Se está en una sala del servidor
+    in_room: bool,
+
+    /// Se pidió volver al asiento de la sesión guardada y no hay respuesta todavía
+    resuming: bool,
+
+    /// Sala del enlace de invitación con el que se abrió la aplicación.
+    /// Se entra en ella al conectarse.
+    invite: Option<String>,
+
+    /// Último error del servidor o de la conexión fuera de una sala
+    error: Option<String>,
+}
+
+
+impl Component for App {
+
+    type Message = AppMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+
+        let invite = invited_room();
+
+        // Si se recargó la página a media partida se vuelve a ella
+        let view = match Session::load() {
+            Some(session) => {
+                ctx.link().send_message(AppMsg::Connect(session.server));
+                View::Game
+            },
+            None if invite.is_some() => View::Lobby,
+            None => View::Game,
+        };
+
+        Self {
+            view,
+            connection: None,
+            _listener: None,
+            account: None,
+            in_room: false,
+            resuming: false,
+            invite,
+            error: None,
+        }
     }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+
+        match msg {
+            AppMsg::Show(view) => {
+
+                // El archivo es del servidor al que se está conectado
+                if view == View::Archive && self.connection.is_none() {
+                    return false;
+                }
+                self.view = view;
+
+                true
+            },
+            AppMsg::Connect(url) => {
+
+                match Connection::open(&url, ctx.link().callback(AppMsg::Disconnected)) {
+                    Ok(connection) => {
+                        self._listener = Some(connection.listen(ctx.link().callback(AppMsg::Server)));
+
+                        // Primero la cuenta, para que cuente en la sala en la que se entre
+                        if let Some(account) = Account::load().filter(|account| account.server == url) {
+                            connection.send(ClientMessage::Login { name: account.name, key: Some(account.key) });
+                        }
+
+                        match Session::load().filter(|session| session.server == url) {
+                            Some(session) => {
+                                connection.send(ClientMessage::Resume(session.token));
+                                self.resuming = true;
+                            },
+                            None => {
+                                if let Some(code) = self.invite.take() {
+                                    connection.send(ClientMessage::Join(code));
+                                }
+                                self.view = View::Lobby;
+                            },
+                        }
+
+                        self.connection = Some(connection);
+                        self.error = None;
+                    },
+                    Err(err) => self.error = Some(format!("Could not connect to {}: {}", url, err)),
+                }
+
+                true
+            },
+            AppMsg::Disconnect => {
+
+                // Al soltar la conexión se cierra
+                self.close();
+                self.error = None;
+
+                true
+            },
+            AppMsg::Disconnected(err) => {
+
+                self.close();
+                self.error = Some(match (err, Session::load()) {
+                    (_, Some(session)) => format!("Connection lost. Connect again to return to room {}.", session.room),
+                    (Some(err), None) => err,
+                    (None, None) => "Disconnected from the server".to_string(),
+                });
+                self.view = View::Lobby;
+
+                true
+            },
+            AppMsg::Login(name) => {
+
+                if let Some(connection) = &self.connection {
+                    // Con la clave guardada se vuelve a la cuenta; sin ella se crea una
+                    let key = Account::load()
+                        .filter(|account| account.server == connection.server() && account.name.eq_ignore_ascii_case(&name))
+                        .map(|account| account.key);
+                    connection.send(ClientMessage::Login { name, key });
+                }
+
+                false
+            },
+            AppMsg::Server(message) => {
+
+                let Some(connection) = &self.connection else {
+                    return false;
+                };
+
+                match message {
+                    ServerMessage::Account { name, key } => {
+                        Account { server: connection.server().to_string(), name: name.clone(), key }.save();
+                        self.account = Some(name);
+                        self.error = None;
+                    },
+                    // La partida de la sala la lleva la vista de la partida
+                    ServerMessage::Joined { .. } | ServerMessage::Watching { .. } => {
+                        self.in_room = true;
+                        self.resuming = false;
+                        self.error = None;
+                        self.view = View::Game;
+                    },
+                    ServerMessage::Session(_) => self.resuming = false,
+                    ServerMessage::Closed => {
+                        self.in_room = false;
+                        self.error = Some("The players left and the room was closed".to_string());
+                        self.view = View::Lobby;
+                    },
+                    ServerMessage::Error(err) if self.resuming => {
+                        // El asiento ya no está guardado: se sigue en el vestíbulo
+                        self.resuming = false;
+                        Session::clear();
+                        self.error = Some(format!("Could not return to the game: {}", err));
+                        self.view = View::Lobby;
+                    },
+                    // En la sala los errores se muestran en su panel
+                    ServerMessage::Error(err) if !self.in_room => self.error = Some(err),
+                    _ => return false,
+                }
+
+                true
+            },
+            AppMsg::Left => {
+
+                self.in_room = false;
+                self.view = View::Lobby;
+
+                true
+            },
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+
+        let account = self.account.clone().map(AttrValue::from);
+
+        // La partida no se desmonta: se oculta mientras se ve otra vista
+        let page = match (self.view, &self.connection) {
+            (View::Game, _) => None,
+            (View::Archive, Some(connection)) => Some(html! {
+                <ArchiveViewComponent
+                    server={AttrValue::from(connection.server().to_string())}
+                    account={account.clone()}
+                    on_close={ctx.link().callback(|_| AppMsg::Show(View::Lobby))}
+                />
+            }),
+            (View::Lobby | View::Archive, connection) => Some(html! {
+                <>
+                    <div class="block gb-online">
+                        <ServerComponent
+                            connected={connection.is_some()}
+                            invite={self.invite.clone().map(AttrValue::from)}
+                            account={account.clone()}
+                            error={self.error.clone().map(AttrValue::from)}
+                            on_connect={ctx.link().callback(AppMsg::Connect)}
+                            on_disconnect={ctx.link().callback(|_| AppMsg::Disconnect)}
+                            on_login={ctx.link().callback(AppMsg::Login)}
+                        />
+                    </div>
+                    {
+                        match connection {
+                            Some(connection) => html! {
+                                <div class="block gb-mid">
+                                    <LobbyComponent
+                                        connection={connection.clone()}
+                                        account={account.clone()}
+                                        on_archive={ctx.link().callback(|_| AppMsg::Show(View::Archive))}
+                                    />
+                                </div>
+                            },
+                            None => html! {},
+                        }
+                    }
+                </>
+            }),
+        };
+
+        html! {
+            <>
+                <NavbarComponent
+                    view={self.view}
+                    connected={self.connection.is_some()}
+                    on_view={ctx.link().callback(AppMsg::Show)}
+                />
+                <SuperTresComponent
+                    visible={self.view == View::Game}
+                    connection={self.connection.clone()}
+                    on_leave={ctx.link().callback(|_| AppMsg::Left)}
+                />
+                {
+                    for page.map(|page| html! {
+                        <div class="section game-section">
+                            <div class="container is-flex is-justify-content-center board-container">
+                                <div class="box game-board">
+                                    { page }
+                                </div>
+                            </div>
+                        </div>
+                    })
+                }
+            </>
+        }
+    }
+}
+
+
+impl App {
+
+    /// Suelta la conexión, que se cierra, y deja el archivo que se consultaba
+    fn close(&mut self) {
+        self.connection = None;
+        self._listener = None;
+        self.account = None;
+        self.in_room = false;
+        self.resuming = false;
+        if self.view == View::Archive {
+            self.view = View::Lobby;
+        }
+    }
+}
+
+
+
+/// Sala del parámetro `room` de la dirección con la que se abrió la aplicación
+fn invited_room() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let params = web_sys::UrlSearchParams::new_with_str(&search).ok()?;
+    params.get("room").map(|code| code.trim().to_uppercase()).filter(|code| !code.is_empty())
 }
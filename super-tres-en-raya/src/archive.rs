@@ -0,0 +1,113 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Consultas a la API HTTP del archivo de partidas del servidor. La API está
+// en el mismo puerto que el WebSocket, así que su dirección sale de la del
+// servidor. Las respuestas JSON se leen con el `JSON` del navegador.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use gloo_net::http::Request;
+use web_sys::js_sys::{Array, Reflect, JSON};
+use web_sys::wasm_bindgen::JsValue;
+use super_tres_engine::archive::{ArchiveQuery, ArchivedGame};
+use super_tres_engine::record::GameRecord;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ARCHIVE
+
+
+/// Página de partidas que cumplen los filtros y cuántas son en total
+pub async fn search(server: &str, query: &ArchiveQuery) -> Result<(u64, Vec<ArchivedGame>), String> {
+    let response = get(&api_url(server, &format!("games?{}", query))).await?;
+
+    let total = number(&response, "total").ok_or_else(invalid)? as u64;
+    let games = Reflect::get(&response, &"games".into()).map_err(|_| invalid())?;
+    let games = Array::from(&games)
+        .iter()
+        .map(|game| archived_game(&game).ok_or_else(invalid))
+        .collect::<Result<_, _>>()?;
+
+    Ok((total, games))
+}
+
+
+/// Partida del archivo con su registro completo
+pub async fn game(server: &str, id: u64) -> Result<(ArchivedGame, GameRecord), String> {
+    let response = get(&api_url(server, &format!("games/{}", id))).await?;
+
+    let game = archived_game(&response).ok_or_else(invalid)?;
+    let record = text(&response, "record").ok_or_else(invalid)?;
+    let record = record.parse().map_err(|err| format!("the archived game is damaged: {}", err))?;
+
+    Ok((game, record))
+}
+
+
+/// Dirección de la API en el servidor de ese WebSocket
+fn api_url(server: &str, path: &str) -> String {
+    let base = server.trim_end_matches('/');
+    let base = match base.split_once("://") {
+        Some(("wss", host)) => format!("https://{}", host),
+        Some((_, host)) => format!("http://{}", host),
+        None => format!("http://{}", base),
+    };
+    format!("{}/api/{}", base, path)
+}
+
+
+/// Pide la dirección y lee su respuesta. Si es un error, devuelve su texto.
+async fn get(url: &str) -> Result<JsValue, String> {
+    let response = Request::get(url).send().await.map_err(|err| format!("could not reach the archive: {}", err))?;
+    let body = response.text().await.map_err(|err| err.to_string())?;
+    let json = JSON::parse(&body).map_err(|_| invalid())?;
+
+    if response.ok() {
+        Ok(json)
+    } else {
+        Err(text(&json, "error").unwrap_or_else(|| format!("the archive answered {}", response.status())))
+    }
+}
+
+
+fn invalid() -> String {
+    "the archive sent an invalid answer".to_string()
+}
+
+
+fn text(value: &JsValue, name: &str) -> Option<String> {
+    Reflect::get(value, &name.into()).ok()?.as_string()
+}
+
+
+fn number(value: &JsValue, name: &str) -> Option<f64> {
+    Reflect::get(value, &name.into()).ok()?.as_f64()
+}
+
+
+/// Partida del archivo de un objeto de la API
+fn archived_game(value: &JsValue) -> Option<ArchivedGame> {
+    Some(ArchivedGame {
+        id: number(value, "id")? as u64,
+        players: [text(value, "x"), text(value, "o")],
+        ruleset: text(value, "ruleset")?.parse().ok()?,
+        time_control: text(value, "time_control")?.parse().ok()?,
+        result: text(value, "result")?.parse().ok()?,
+        termination: text(value, "termination")?,
+        plies: number(value, "plies")? as u32,
+        opening: text(value, "opening").and_then(|opening| opening.parse().ok()),
+        rated: Reflect::get(value, &"rated".into()).ok()?.as_bool()?,
+        ended: number(value, "ended")? as u64,
+    })
+}
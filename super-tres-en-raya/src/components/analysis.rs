@@ -0,0 +1,128 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Barra de evaluación, variante principal y valor exacto de los finales.
+
+
+
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+use super_tres_engine::game::Move;
+use super_tres_engine::search::{forced_win, format_score};
+use super_tres_engine::solver::Value;
+
+
+
+/// Escala de la barra: una ventaja de esta puntuación llena
+/// aproximadamente tres cuartos de la barra
+const BAR_SCALE: f64 = 250.0;
+
+
+#[derive(Properties, PartialEq)]
+pub struct EvalBarProps {
+
+    /// Puntuación desde el punto de vista de X
+    pub score: i32,
+}
+
+
+#[function_component(EvalBarComponent)]
+pub fn eval_bar(props: &EvalBarProps) -> Html {
+
+    // Porcentaje de la barra que ocupa X
+    let share = match forced_win(props.score) {
+        Some((Player::First, _)) => 100.0,
+        Some((Player::Second, _)) => 0.0,
+        None => 100.0 / (1.0 + (-(props.score as f64) / BAR_SCALE).exp()),
+    };
+
+    html! {
+        <div class="eval-bar" title={format_score(props.score)}>
+            <div class="eval-fill-sp" style={format!("height: {:.1}%", 100.0 - share)}></div>
+            <div class="eval-fill-fp" style={format!("height: {:.1}%", share)}></div>
+            <div class="eval-label">{ format_score(props.score) }</div>
+        </div>
+    }
+}
+
+
+#[derive(Properties, PartialEq)]
+pub struct PrincipalVariationProps {
+
+    /// Puntuación desde el punto de vista de X
+    pub score: i32,
+
+    /// Profundidad de la búsqueda
+    pub depth: u8,
+
+    /// Variante principal
+    pub pv: Vec<Move>,
+
+    /// Jugador que hace la primera jugada de la variante
+    pub turn: Player,
+}
+
+
+#[function_component(PrincipalVariationComponent)]
+pub fn principal_variation(props: &PrincipalVariationProps) -> Html {
+
+    let mut player = props.turn;
+
+    html! {
+        <div class="pv">
+            <span class="tag is-dark">{ format_score(props.score) }</span>
+            <span class="tag is-light">{ format!("depth {}", props.depth) }</span>
+            {
+                for props.pv.iter().map(|mv| {
+                    let class = match player {
+                        Player::First => "pv-move first-player",
+                        Player::Second => "pv-move second-player",
+                    };
+                    player = player.other();
+
+                    html! { <span {class}>{ mv.to_string() }</span> }
+                })
+            }
+        </div>
+    }
+}
+
+
+#[derive(Properties, PartialEq)]
+pub struct TablebaseProps {
+
+    /// Valor teórico para el jugador al que le toca
+    pub value: Value,
+
+    /// Jugada que consigue ese valor
+    pub best: Option<Move>,
+
+    /// Jugador al que le toca
+    pub turn: Player,
+}
+
+
+#[function_component(TablebaseComponent)]
+pub fn tablebase(props: &TablebaseProps) -> Html {
+
+    let verdict = match (props.value, props.value.winner(props.turn)) {
+        (Value::Win(plies) | Value::Loss(plies), Some(winner)) => format!("{} wins in {}", winner.symbol(), plies),
+        _ => "Draw".to_string(),
+    };
+
+    html! {
+        <div class="tablebase">
+            <span class="tag is-high">{ "Tablebase" }</span>
+            <span class="tablebase-verdict">{ verdict }</span>
+            {
+                match props.best {
+                    Some(best) => html! { <span class="pv-move">{ best.to_string() }</span> },
+                    None => html! {},
+                }
+            }
+        </div>
+    }
+}
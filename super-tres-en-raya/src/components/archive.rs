@@ -0,0 +1,296 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Archivo de partidas del servidor: búsqueda por jugador, reglas, resultado,
+// fechas, longitud y primera jugada, con los resultados por páginas. Cada
+// partida se abre en el tablero, donde se reproduce jugada a jugada.
+
+
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use super_tres_engine::archive::{ArchiveQuery, ArchivedGame, Date, ARCHIVE_PAGE};
+use super_tres_engine::ruleset::Ruleset;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct ArchiveProps {
+
+    /// Última búsqueda
+    pub query: ArchiveQuery,
+
+    /// Página de partidas de la última búsqueda y cuántas la cumplen en total
+    pub games: Vec<ArchivedGame>,
+    pub total: u64,
+
+    /// Hay una consulta en curso
+    pub loading: bool,
+    pub error: Option<AttrValue>,
+
+    /// Cuenta con la que se entró, cuyas partidas se resaltan
+    pub account: Option<AttrValue>,
+
+    pub on_search: Callback<ArchiveQuery>,
+
+    /// Abre la partida con ese identificador en el tablero
+    pub on_open: Callback<u64>,
+    pub on_close: Callback<()>,
+}
+
+
+/// Resultados por los que se puede buscar, con su nombre
+const RESULTS: [(&str, &str); 3] = [("1-0", "X wins"), ("0-1", "O wins"), ("1/2-1/2", "Draw")];
+
+
+/// Nombre de un jugador de la partida, que puede no tener cuenta
+fn player_name(player: &Option<String>) -> String {
+    player.clone().unwrap_or_else(|| "Guest".to_string())
+}
+
+
+#[function_component(ArchiveComponent)]
+pub fn archive(props: &ArchiveProps) -> Html {
+
+    // Filtros del formulario, que se buscan al pulsar Search
+    let draft = use_state(|| props.query.clone());
+
+    let on_input = |update: fn(&mut ArchiveQuery, &str)| {
+        let draft = draft.clone();
+        Callback::from(move |event: Event| {
+            let mut query = (*draft).clone();
+            update(&mut query, event.target_unchecked_into::<HtmlInputElement>().value().trim());
+            draft.set(query);
+        })
+    };
+    let on_select = |update: fn(&mut ArchiveQuery, &str)| {
+        let draft = draft.clone();
+        Callback::from(move |event: Event| {
+            let mut query = (*draft).clone();
+            update(&mut query, &event.target_unchecked_into::<HtmlSelectElement>().value());
+            draft.set(query);
+        })
+    };
+
+    let search = {
+        let (on_search, draft) = (props.on_search.clone(), draft.clone());
+        Callback::from(move |_: MouseEvent| on_search.emit(draft.at(0)))
+    };
+    let clear = {
+        let (on_search, draft) = (props.on_search.clone(), draft.clone());
+        Callback::from(move |_: MouseEvent| {
+            draft.set(ArchiveQuery::default());
+            on_search.emit(ArchiveQuery::default());
+        })
+    };
+    let page = |offset: u32| {
+        let (on_search, query) = (props.on_search.clone(), props.query.clone());
+        Callback::from(move |_: MouseEvent| on_search.emit(query.at(offset)))
+    };
+    let close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    let text = |value: Option<String>| value.unwrap_or_default();
+    let first = props.query.offset as u64;
+    let last = first + props.games.len() as u64;
+    let previous = props.query.offset.saturating_sub(ARCHIVE_PAGE as u32);
+    let next = props.query.offset + ARCHIVE_PAGE as u32;
+
+    html! {
+        <div class="lobby archive">
+
+            // Filtros
+            <div class="lobby-section">
+                <p class="lobby-title">
+                    {"Game archive"}
+                    <button class="button is-small is-text lobby-refresh" onclick={close}>{"Back to lobby"}</button>
+                </p>
+                <div class="archive-filters">
+                    <input class="input is-small" type="text" placeholder="Player" value={text(draft.player.clone())}
+                        onchange={on_input(|query, value| query.player = Some(value.to_string()).filter(|value| !value.is_empty()))} />
+                    <div class="select is-small">
+                        <select onchange={on_select(|query, value| query.ruleset = value.parse().ok())}>
+                            <option value="" selected={draft.ruleset.is_none()}>{"Any rules"}</option>
+                            {
+                                for Ruleset::ALL.iter().map(|option| html! {
+                                    <option value={option.to_string()} selected={draft.ruleset == Some(*option)}>
+                                        { option.to_string() }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                    <div class="select is-small">
+                        <select onchange={on_select(|query, value| query.result = value.parse().ok())}>
+                            <option value="" selected={draft.result.is_none()}>{"Any result"}</option>
+                            {
+                                for RESULTS.iter().map(|(value, name)| html! {
+                                    <option value={*value} selected={draft.result.is_some_and(|result| result.to_string() == *value)}>
+                                        { *name }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                    <input class="input is-small" type="text" placeholder="First move" maxlength="2" value={text(draft.opening.map(|opening| opening.to_string()))}
+                        onchange={on_input(|query, value| query.opening = value.to_lowercase().parse().ok())} />
+                    <label class="archive-range">
+                        {"From"}
+                        <input class="input is-small" type="date" value={text(draft.from.map(|date| date.to_string()))}
+                            onchange={on_input(|query, value| query.from = value.parse::<Date>().ok())} />
+                        {"to"}
+                        <input class="input is-small" type="date" value={text(draft.to.map(|date| date.to_string()))}
+                            onchange={on_input(|query, value| query.to = value.parse::<Date>().ok())} />
+                    </label>
+                    <label class="archive-range">
+                        {"Moves"}
+                        <input class="input is-small" type="number" min="0" max="81" placeholder="min" value={text(draft.min_plies.map(|plies| plies.to_string()))}
+                            onchange={on_input(|query, value| query.min_plies = value.parse().ok())} />
+                        {"to"}
+                        <input class="input is-small" type="number" min="0" max="81" placeholder="max" value={text(draft.max_plies.map(|plies| plies.to_string()))}
+                            onchange={on_input(|query, value| query.max_plies = value.parse().ok())} />
+                    </label>
+                    <button class="button is-small is-high" onclick={search} disabled={props.loading}>{"Search"}</button>
+                    <button class="button is-small" onclick={clear} disabled={props.loading}>{"Clear"}</button>
+                </div>
+                {
+                    match &props.error {
+                        Some(error) => html! { <p class="online-error has-text-centered">{ error.clone() }</p> },
+                        None => html! {},
+                    }
+                }
+            </div>
+
+            // Partidas
+            <div class="lobby-section">
+                {
+                    if props.games.is_empty() {
+                        html! {
+                            <p class="has-text-centered">
+                                { if props.loading { "Searching…" } else { "No finished games match these filters." } }
+                            </p>
+                        }
+                    } else {
+                        html! {
+                            <>
+                                <table class="table is-narrow is-fullwidth archive-games">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Date"}</th>
+                                            <th>{"X"}</th>
+                                            <th>{"O"}</th>
+                                            <th>{"Result"}</th>
+                                            <th>{"Moves"}</th>
+                                            <th>{"First"}</th>
+                                            <th>{"Rules"}</th>
+                                            <th></th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        {
+                                            for props.games.iter().map(|game| {
+                                                let own = props.account.as_deref().is_some_and(|account| {
+                                                    game.players.iter().flatten().any(|player| player.eq_ignore_ascii_case(account))
+                                                });
+                                                let (on_open, id) = (props.on_open.clone(), game.id);
+                                                html! {
+                                                    <tr class={classes!(own.then_some("is-selected"))} key={game.id}>
+                                                        <td>{ Date::from_secs(game.ended).to_string() }</td>
+                                                        <td>{ player_name(&game.players[0]) }</td>
+                                                        <td>{ player_name(&game.players[1]) }</td>
+                                                        <td title={game.termination.clone()}>
+                                                            { game.result.to_string() }
+                                                            { if game.rated { " ★" } else { "" } }
+                                                        </td>
+                                                        <td>{ game.plies }</td>
+                                                        <td>{ game.opening.map(|opening| opening.to_string()).unwrap_or_default() }</td>
+                                                        <td title={game.ruleset.description()}>{ game.ruleset.to_string() }</td>
+                                                        <td>
+                                                            <button class="button is-small" onclick={Callback::from(move |_: MouseEvent| on_open.emit(id))} disabled={props.loading}>
+                                                                {"Replay"}
+                                                            </button>
+                                                        </td>
+                                                    </tr>
+                                                }
+                                            })
+                                        }
+                                    </tbody>
+                                </table>
+                                <div class="is-flex is-align-items-center is-justify-content-center archive-pages">
+                                    <button class="button is-small" onclick={page(previous)} disabled={props.loading || first == 0}>{"Previous"}</button>
+                                    <span>{ format!("Games {}–{} of {}", first + 1, last, props.total) }</span>
+                                    <button class="button is-small" onclick={page(next)} disabled={props.loading || last >= props.total}>{"Next"}</button>
+                                </div>
+                            </>
+                        }
+                    }
+                }
+            </div>
+
+        </div>
+    }
+}
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct ReplayProps {
+
+    /// Partida del archivo que se reproduce
+    pub game: ArchivedGame,
+
+    /// Número de jugadas de la posición que se está viendo.
+    /// Si es None, se ve la posición final.
+    pub viewed: Option<usize>,
+
+    /// Salta a la posición tras ese número de jugadas, o a la final con None
+    pub on_review: Callback<Option<usize>>,
+    pub on_close: Callback<()>,
+}
+
+
+#[function_component(ReplayComponent)]
+pub fn replay(props: &ReplayProps) -> Html {
+
+    let total = props.game.plies as usize;
+    let viewed = props.viewed.unwrap_or(total);
+
+    let jump = |ply: usize| {
+        let on_review = props.on_review.clone();
+        Callback::from(move |_: MouseEvent| on_review.emit((ply < total).then_some(ply)))
+    };
+    let close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    let ending = match props.game.termination.as_str() {
+        "normal" => String::new(),
+        termination => format!(" by {}", termination),
+    };
+
+    html! {
+        <div class="online replay">
+            <div class="is-flex is-align-items-center online-actions">
+                <span>
+                    { format!("{} (X) vs {} (O)", player_name(&props.game.players[0]), player_name(&props.game.players[1])) }
+                </span>
+                <span>{ format!("{}{}", props.game.result, ending) }</span>
+                <span>{ format!("{} · {} · {}", Date::from_secs(props.game.ended), props.game.ruleset, props.game.time_control) }</span>
+                <button class="button is-small" onclick={close}>{"Back to archive"}</button>
+            </div>
+            <div class="is-flex is-align-items-center online-actions">
+                <button class="button is-small" onclick={jump(0)} disabled={viewed == 0} title="Start">{"⏮"}</button>
+                <button class="button is-small" onclick={jump(viewed.saturating_sub(1))} disabled={viewed == 0} title="Previous move">{"◀"}</button>
+                <span>{ format!("Move {} of {}", viewed, total) }</span>
+                <button class="button is-small" onclick={jump(viewed + 1)} disabled={viewed == total} title="Next move">{"▶"}</button>
+                <button class="button is-small" onclick={jump(total)} disabled={viewed == total} title="End">{"⏭"}</button>
+            </div>
+        </div>
+    }
+}
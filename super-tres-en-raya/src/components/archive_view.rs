@@ -0,0 +1,235 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Vista del archivo de partidas del servidor: la búsqueda con sus resultados
+// y la reproducción jugada a jugada de la partida que se abre, en un
+// tablero de solo lectura. Las consultas van a la API HTTP del servidor.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use yew::prelude::*;
+use super_tres_engine::archive::{replay, ArchiveQuery, ArchivedGame};
+use super_tres_engine::game::{Game, Move};
+use super_tres_engine::record::GameRecord;
+use crate::archive;
+use crate::components::archive::{ArchiveComponent, ReplayComponent};
+use crate::components::board::BoardComponent;
+use crate::components::export::ExportComponent;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// COMPONENT
+
+
+/// Mensajes que puede recibir la vista
+pub enum ArchiveViewMsg {
+    Search(ArchiveQuery),
+    Results(ArchiveQuery, Result<(u64, Vec<ArchivedGame>), String>),
+    Open(u64),
+    Loaded(Result<(ArchivedGame, GameRecord), String>),
+    Review(Option<usize>),
+    CloseReplay,
+}
+
+
+#[derive(Properties, PartialEq)]
+pub struct ArchiveViewProps {
+
+    /// Dirección del servidor cuyo archivo se consulta
+    pub server: AttrValue,
+
+    /// Cuenta con la que se entró, cuyas partidas se resaltan
+    pub account: Option<AttrValue>,
+
+    /// Vuelve al vestíbulo
+    pub on_close: Callback<()>,
+}
+
+
+/// Partida del archivo que se reproduce
+struct Replay {
+    game: ArchivedGame,
+
+    /// Partida completa
+    replayed: Game,
+
+    /// Número de jugadas de la posición que se ve. Si es None, se ve la final.
+    viewed: Option<usize>,
+}
+
+
+/// Información que guarda la vista
+pub struct ArchiveViewComponent {
+
+    /// Última búsqueda y la página de partidas que la cumplen
+    query: ArchiveQuery,
+    games: Vec<ArchivedGame>,
+
+    /// Partidas que cumplen la búsqueda en total
+    total: u64,
+
+    /// Hay una consulta en curso
+    loading: bool,
+    error: Option<String>,
+
+    /// Partida que se reproduce en el tablero
+    replay: Option<Replay>,
+}
+
+
+impl Component for ArchiveViewComponent {
+
+    type Message = ArchiveViewMsg;
+    type Properties = ArchiveViewProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+
+        ctx.link().send_message(ArchiveViewMsg::Search(ArchiveQuery::default()));
+
+        Self {
+            query: ArchiveQuery::default(),
+            games: Vec::new(),
+            total: 0,
+            loading: false,
+            error: None,
+            replay: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+
+        match msg {
+            ArchiveViewMsg::Search(query) => {
+
+                self.query = query.clone();
+                self.loading = true;
+
+                let server = ctx.props().server.to_string();
+                ctx.link().send_future(async move {
+                    let results = archive::search(&server, &query).await;
+                    ArchiveViewMsg::Results(query, results)
+                });
+
+                true
+            },
+            ArchiveViewMsg::Results(query, results) => {
+
+                // Las respuestas a búsquedas anteriores ya no se muestran
+                if query != self.query {
+                    return false;
+                }
+
+                self.loading = false;
+                match results {
+                    Ok((total, games)) => {
+                        self.total = total;
+                        self.games = games;
+                        self.error = None;
+                    },
+                    Err(err) => self.error = Some(err),
+                }
+
+                true
+            },
+            ArchiveViewMsg::Open(id) => {
+
+                self.loading = true;
+
+                let server = ctx.props().server.to_string();
+                ctx.link().send_future(async move { ArchiveViewMsg::Loaded(archive::game(&server, id).await) });
+
+                true
+            },
+            ArchiveViewMsg::Loaded(result) => {
+
+                self.loading = false;
+                let loaded = result.and_then(|(game, record)| {
+                    replay(&record).map(|replayed| (game, replayed)).map_err(|err| format!("Could not replay the game: {}", err))
+                });
+
+                match loaded {
+                    // Se reproduce desde el principio
+                    Ok((game, replayed)) => {
+                        let viewed = (replayed.ply() > 0).then_some(0);
+                        self.replay = Some(Replay { game, replayed, viewed });
+                        self.error = None;
+                    },
+                    Err(err) => self.error = Some(err),
+                }
+
+                true
+            },
+            ArchiveViewMsg::Review(ply) => {
+
+                let Some(replay) = &mut self.replay else {
+                    return false;
+                };
+                replay.viewed = ply.filter(|ply| *ply < replay.replayed.ply());
+
+                true
+            },
+            ArchiveViewMsg::CloseReplay => {
+
+                self.replay = None;
+
+                true
+            },
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+
+        let Some(replay) = &self.replay else {
+            return html! {
+                <div class="block gb-mid">
+                    <ArchiveComponent
+                        query={self.query.clone()}
+                        games={self.games.clone()}
+                        total={self.total}
+                        loading={self.loading}
+                        error={self.error.clone().map(AttrValue::from)}
+                        account={ctx.props().account.clone()}
+                        on_search={ctx.link().callback(ArchiveViewMsg::Search)}
+                        on_open={ctx.link().callback(ArchiveViewMsg::Open)}
+                        on_close={ctx.props().on_close.clone()}
+                    />
+                </div>
+            };
+        };
+
+        let moves: Vec<Move> = replay.replayed.moves().collect();
+        let shown = match replay.viewed {
+            Some(ply) => Game::from_moves(&moves[..ply]).unwrap_or_default(),
+            None => replay.replayed.clone(),
+        };
+
+        html! {
+            <>
+                <div class="block gb-online">
+                    <ReplayComponent
+                        game={replay.game.clone()}
+                        viewed={replay.viewed}
+                        on_review={ctx.link().callback(ArchiveViewMsg::Review)}
+                        on_close={ctx.link().callback(|_| ArchiveViewMsg::CloseReplay)}
+                    />
+                </div>
+                <div class="block gb-mid">
+                    <BoardComponent game={shown.clone()} read_only=true />
+                </div>
+                <div class="block gb-export">
+                    <ExportComponent {moves} ply={shown.ply()} />
+                </div>
+            </>
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Supertablero de una posición: los subtableros ganados, el activo, la
+// última jugada, la jugada marcada y el cursor del teclado. Lo usan la
+// partida en curso y la reproducción de las partidas del archivo.
+
+
+
+use yew::prelude::*;
+use super_tres_engine::board::{Player, Position, TableroTres};
+use super_tres_engine::game::{Game, Move};
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct BoardProps {
+
+    /// Posición que se muestra
+    pub game: Game,
+
+    /// Jugada marcada en este turno y todavía no confirmada
+    #[prop_or_default]
+    pub pending: Option<Move>,
+
+    /// Casilla del cursor del teclado, si se muestra
+    #[prop_or_default]
+    pub cursor: Option<Move>,
+
+    /// Se puede jugar en la posición: las casillas del subtablero activo se pueden pulsar
+    #[prop_or_default]
+    pub playable: bool,
+
+    /// Tablero de solo lectura, sea cual sea el subtablero activo
+    #[prop_or_default]
+    pub read_only: bool,
+
+    /// Marca o desmarca la casilla
+    #[prop_or_default]
+    pub on_mark: Callback<Move>,
+}
+
+
+#[function_component(BoardComponent)]
+pub fn board(props: &BoardProps) -> Html {
+    html! {
+        <div class="container is-flex is-justify-content-center">
+            <div class={classes!("super-grid", "columns", "is-centered", props.read_only.then_some("read-only"))}>
+                <div class="column is-narrow">
+                    { for (0..3).map(|i| render_super_row(props, i)) }
+                </div>
+            </div>
+        </div>
+    }
+}
+
+
+fn render_super_row(props: &BoardProps, i: u8) -> Html {
+    html! {
+        <div class="columns super-row" key={i}>
+            { for (0..3).map(|j| render_super_cell(props, i, j)) }
+        </div>
+    }
+}
+
+
+fn render_super_cell(props: &BoardProps, i: u8, j: u8) -> Html {
+    let position = Position { x: i, y: j };
+    let is_active = props.game.board_enabled(position);
+
+    let cell_classes = classes!(
+        "super-cell",
+        "column",
+        "is-narrow",
+        if is_active { "active-board" } else { "inactive-board" }
+    );
+
+    html! {
+        <div class={cell_classes} key={format!("{}-{}", i, j)}>
+            {
+                match props.game.tablero().get(position) {
+                    Ok(Player::First) => html! {
+                        <div class="won-cell first-player is-flex is-justify-content-center is-align-items-center">{"X"}</div>
+                    },
+                    Ok(Player::Second) => html! {
+                        <div class="won-cell second-player is-flex is-justify-content-center is-align-items-center">{"O"}</div>
+                    },
+                    Err(tab) => render_sub_board(props, tab, position),
+                }
+            }
+        </div>
+    }
+}
+
+
+fn render_sub_board(props: &BoardProps, tab: &TableroTres, position: Position) -> Html {
+    html! {
+        <div class="sub-grid">
+            { for (0..3).map(|k| render_sub_row(props, tab, position, k)) }
+        </div>
+    }
+}
+
+
+fn render_sub_row(props: &BoardProps, tab: &TableroTres, pos1: Position, k: u8) -> Html {
+    html! {
+        <div class="columns is-gapless sub-row" key={k}>
+            { for (0..3).map(|l| render_sub_cell(props, tab, pos1, k, l)) }
+        </div>
+    }
+}
+
+
+fn render_sub_cell(props: &BoardProps, tab: &TableroTres, pos1: Position, k: u8, l: u8) -> Html {
+    let pos2 = Position { x: k, y: l };
+    let mv = Move::new(pos1, pos2);
+
+    // En un tablero de solo lectura no se pulsa nada, sea cual sea el subtablero activo.
+    // Si no se puede jugar, todas las casillas están inactivas.
+    let is_disabled = props.read_only || !props.playable || !props.game.board_enabled(pos1);
+    let is_last = props.game.last_move() == Some(mv);
+    let is_cursor = props.cursor == Some(mv);
+
+    // Las casillas jugadas en turnos anteriores no se pueden volver a jugar;
+    // la marcada en este turno todavía se puede desmarcar
+    let is_played = tab.get(pos2).is_some();
+    let value = if props.pending == Some(mv) {
+        props.game.turn()
+    } else {
+        tab.get(pos2)
+    };
+
+    let cell_classes = classes!(
+        "button",
+        match value {
+            Some(Player::First) => "btn-fp",
+            Some(Player::Second) => "btn-sp",
+            None => "btn-none"
+        },
+        if is_played { "played" } else { "unplayed" },
+        is_last.then_some("last-move"),
+        is_cursor.then_some("cursor")
+    );
+
+    let on_mark = props.on_mark.clone();
+
+    html! {
+        <div class="column is-narrow sub-cell">
+            <button
+                class={cell_classes}
+                disabled={is_disabled}
+                onclick={(!props.read_only).then(|| Callback::from(move |_: MouseEvent| on_mark.emit(mv)))}
+            >
+                {
+                    match value {
+                        Some(player) => player.symbol(),
+                        None => " "
+                    }
+                }
+            </button>
+        </div>
+    }
+}
@@ -0,0 +1,128 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Chat de la sala de una partida en red: mensajes de jugadores y espectadores,
+// reacciones rápidas y avisos del servidor sobre la partida.
+
+
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+use super_tres_engine::net::{MAX_CHAT_LENGTH, REACTIONS};
+
+
+
+/// Línea del chat
+#[derive(Clone, PartialEq)]
+pub enum ChatLine {
+
+    /// Mensaje de un jugador, o de un espectador si `from` es None
+    Message { from: Option<Player>, text: AttrValue },
+    Reaction { from: Option<Player>, emoji: AttrValue },
+
+    /// Aviso del servidor
+    Notice(AttrValue),
+}
+
+
+#[derive(Properties, PartialEq)]
+pub struct ChatProps {
+
+    /// Líneas del chat, de la más antigua a la más reciente
+    pub lines: Vec<ChatLine>,
+
+    /// Fichas con las que se juega, para marcar los mensajes propios
+    pub seat: Option<Player>,
+
+    pub on_say: Callback<String>,
+    pub on_react: Callback<String>,
+}
+
+
+/// Nombre de quien habla
+fn speaker(from: Option<Player>, seat: Option<Player>) -> String {
+    match from {
+        Some(player) if Some(player) == seat => format!("{} (you)", player.symbol()),
+        Some(player) => player.symbol().to_string(),
+        None => "Spectator".to_string(),
+    }
+}
+
+
+#[function_component(ChatComponent)]
+pub fn chat(props: &ChatProps) -> Html {
+
+    let text = use_state(String::new);
+
+    let on_input = {
+        let text = text.clone();
+        Callback::from(move |event: InputEvent| text.set(event.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let on_submit = {
+        let (on_say, text) = (props.on_say.clone(), text.clone());
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            if !text.trim().is_empty() {
+                on_say.emit(text.trim().to_string());
+                text.set(String::new());
+            }
+        })
+    };
+
+    let line = |line: &ChatLine| match line {
+        ChatLine::Message { from, text } => html! {
+            <p class="chat-line">
+                <strong class={classes!(from.map(|player| if player == Player::First { "first-player" } else { "second-player" }))}>
+                    { speaker(*from, props.seat) }
+                </strong>
+                { format!(": {}", text) }
+            </p>
+        },
+        ChatLine::Reaction { from, emoji } => html! {
+            <p class="chat-line">
+                <strong>{ speaker(*from, props.seat) }</strong>
+                <span class="chat-reaction">{ emoji.clone() }</span>
+            </p>
+        },
+        ChatLine::Notice(text) => html! {
+            <p class="chat-line chat-notice">{ text.clone() }</p>
+        },
+    };
+
+    html! {
+        <div class="chat">
+
+            // En orden inverso: con `column-reverse` el final queda a la vista
+            <div class="chat-lines">
+                { for props.lines.iter().rev().map(line) }
+            </div>
+
+            <div class="is-flex chat-reactions">
+                {
+                    for REACTIONS.iter().map(|emoji| {
+                        let on_react = props.on_react.clone();
+                        html! {
+                            <button class="button is-small is-white" onclick={Callback::from(move |_: MouseEvent| on_react.emit(emoji.to_string()))}>
+                                { *emoji }
+                            </button>
+                        }
+                    })
+                }
+            </div>
+
+            <form class="field has-addons" onsubmit={on_submit}>
+                <div class="control is-expanded">
+                    <input class="input is-small" type="text" placeholder="Message" maxlength={MAX_CHAT_LENGTH.to_string()} value={(*text).clone()} oninput={on_input} />
+                </div>
+                <div class="control">
+                    <button class="button is-small is-high" type="submit" disabled={text.trim().is_empty()}>{"Send"}</button>
+                </div>
+            </form>
+
+        </div>
+    }
+}
@@ -0,0 +1,65 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Relojes de los dos jugadores en las partidas con control de tiempo.
+// Se marca el reloj que corre y el del jugador al que se le acabó el tiempo.
+
+
+
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct ClockProps {
+
+    /// Milisegundos que le quedan a X y a O
+    pub left: [u64; 2],
+
+    /// Jugador cuyo reloj corre
+    pub running: Option<Player>,
+}
+
+
+/// Tiempo en minutos y segundos, con décimas en los últimos diez segundos
+fn format_time(ms: u64) -> String {
+    let seconds = ms / 1000;
+    if ms < 10_000 {
+        format!("0:{:02}.{}", seconds, ms % 1000 / 100)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+
+#[function_component(ClockComponent)]
+pub fn clock(props: &ClockProps) -> Html {
+    html! {
+        <div class="clocks">
+            {
+                for [Player::First, Player::Second].into_iter().map(|player| {
+                    let left = props.left[player.index()];
+                    let player_class = match player {
+                        Player::First => "first-player",
+                        Player::Second => "second-player",
+                    };
+
+                    html! {
+                        <div class={classes!(
+                            "clock",
+                            (props.running == Some(player)).then_some("is-running"),
+                            (left == 0).then_some("is-flagged"),
+                        )}>
+                            <span class={classes!("player-symbol", player_class)}>{ player.symbol() }</span>
+                            <span class="clock-time">{ format_time(left) }</span>
+                        </div>
+                    }
+                })
+            }
+        </div>
+    }
+}
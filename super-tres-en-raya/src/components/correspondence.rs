@@ -0,0 +1,141 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Panel de las partidas por correspondencia: empezar una partida o unirse
+// a ella con el código de la primera jugada, el código de la última jugada
+// propia para mandarlo al rival y la entrada de los códigos del rival.
+
+
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct CorrespondenceProps {
+
+    /// Identificador de la partida en curso
+    pub game: Option<AttrValue>,
+
+    /// Fichas con las que se juega
+    pub seat: Option<Player>,
+
+    /// Turno de la partida. None si terminó.
+    pub turn: Option<Player>,
+
+    /// Código de la última jugada propia, para mandarlo al rival
+    pub code: Option<AttrValue>,
+
+    /// Último código que no se pudo aplicar y por qué
+    pub error: Option<AttrValue>,
+
+    pub on_new: Callback<()>,
+
+    /// Aplica un código del rival. Fuera de una partida, el de la primera jugada
+    /// empieza una partida con O.
+    pub on_apply: Callback<String>,
+    pub on_abandon: Callback<()>,
+}
+
+
+#[function_component(CorrespondenceComponent)]
+pub fn correspondence(props: &CorrespondenceProps) -> Html {
+
+    let code = use_state(String::new);
+
+    let on_code_input = {
+        let code = code.clone();
+        Callback::from(move |event: InputEvent| code.set(event.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let apply = {
+        let (on_apply, code) = (props.on_apply.clone(), code.clone());
+        Callback::from(move |_: MouseEvent| {
+            on_apply.emit(code.trim().to_string());
+            code.set(String::new());
+        })
+    };
+    let on_new = {
+        let on_new = props.on_new.clone();
+        Callback::from(move |_: MouseEvent| on_new.emit(()))
+    };
+    let on_abandon = {
+        let on_abandon = props.on_abandon.clone();
+        Callback::from(move |_: MouseEvent| on_abandon.emit(()))
+    };
+    let select_all = Callback::from(|event: FocusEvent| event.target_unchecked_into::<HtmlInputElement>().select());
+
+    let code_input = |placeholder: &'static str, label: &'static str| html! {
+        <div class="field has-addons">
+            <div class="control is-expanded">
+                <input class="input is-small correspondence-code" type="text" placeholder={placeholder} value={(*code).clone()} oninput={on_code_input.clone()} />
+            </div>
+            <div class="control">
+                <button class="button is-small is-high" onclick={apply.clone()} disabled={code.trim().is_empty()}>{ label }</button>
+            </div>
+        </div>
+    };
+
+    let body = match (&props.game, props.seat) {
+        (Some(game), Some(seat)) => html! {
+            <>
+                <div class="is-flex is-align-items-center online-actions">
+                    <span>{"Game "}<strong class="online-room">{ game.clone() }</strong></span>
+                    <span>{ format!("You play {}", seat.symbol()) }</span>
+                    <span>
+                        {
+                            match props.turn {
+                                None => "Game over",
+                                Some(turn) if turn == seat => "Your move: play it and press NEXT",
+                                Some(_) => "Waiting for your opponent's move",
+                            }
+                        }
+                    </span>
+                    <button class="button is-small" onclick={on_abandon}>{"Abandon"}</button>
+                </div>
+                {
+                    match &props.code {
+                        Some(own) => html! {
+                            <div class="field online-invite">
+                                <label class="label is-small">{"Send this code to your opponent"}</label>
+                                <div class="control">
+                                    <input class="input is-small correspondence-code" type="text" readonly=true value={own.clone()} onfocus={select_all} />
+                                </div>
+                            </div>
+                        },
+                        None => html! {},
+                    }
+                }
+                {
+                    match props.turn {
+                        Some(turn) if turn != seat => code_input("Opponent's move code", "Apply"),
+                        _ => html! {},
+                    }
+                }
+            </>
+        },
+        _ => html! {
+            <div class="is-flex is-align-items-center online-actions">
+                <span>{"Correspondence"}</span>
+                <button class="button is-small" onclick={on_new}>{"New game as X"}</button>
+                { code_input("First move code", "Join as O") }
+            </div>
+        },
+    };
+
+    html! {
+        <div class="online correspondence">
+            { body }
+            {
+                match &props.error {
+                    Some(error) => html! { <p class="online-error has-text-centered">{ error.clone() }</p> },
+                    None => html! {},
+                }
+            }
+        </div>
+    }
+}
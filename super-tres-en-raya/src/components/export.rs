@@ -0,0 +1,86 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Descarga de la posición mostrada como SVG y de la partida como SVG animado.
+
+
+
+use gloo_file::{Blob, ObjectUrl};
+use gloo_timers::callback::Timeout;
+use web_sys::HtmlAnchorElement;
+use web_sys::wasm_bindgen::JsCast;
+use yew::prelude::*;
+use super_tres_engine::game::{Game, Move};
+use super_tres_engine::svg::{position_svg, replay_svg};
+
+
+
+/// Segundos que se muestra cada posición en la repetición
+const FRAME_SECONDS: f64 = 1.0;
+
+/// Tiempo que se conserva el enlace de la descarga antes de liberarlo
+const REVOKE_DELAY_MS: u32 = 1000;
+
+
+#[derive(Properties, PartialEq)]
+pub struct ExportProps {
+
+    /// Jugadas de la partida
+    pub moves: Vec<Move>,
+
+    /// Número de jugadas de la posición mostrada
+    pub ply: usize,
+}
+
+
+#[function_component(ExportComponent)]
+pub fn export(props: &ExportProps) -> Html {
+
+    let download_position = {
+        let moves = props.moves[..props.ply.min(props.moves.len())].to_vec();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(game) = Game::from_moves(&moves) {
+                download(&format!("super-tres-{}.svg", moves.len()), &position_svg(&game));
+            }
+        })
+    };
+
+    let download_replay = {
+        let moves = props.moves.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(svg) = replay_svg(&moves, FRAME_SECONDS) {
+                download("super-tres-replay.svg", &svg);
+            }
+        })
+    };
+
+    html! {
+        <div class="buttons is-centered export">
+            <button class="button is-small" onclick={download_position}>{"Download SVG"}</button>
+            <button class="button is-small" onclick={download_replay} disabled={props.moves.is_empty()}>{"Download replay"}</button>
+        </div>
+    }
+}
+
+
+/// Descarga el texto como un fichero SVG con ese nombre
+fn download(name: &str, svg: &str) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+    let Ok(anchor) = document.create_element("a") else {
+        return;
+    };
+    let anchor: HtmlAnchorElement = anchor.unchecked_into();
+
+    let url = ObjectUrl::from(Blob::new_with_options(svg, Some("image/svg+xml")));
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+
+    // El navegador lee el enlace después del clic
+    Timeout::new(REVOKE_DELAY_MS, move || drop(url)).forget();
+}
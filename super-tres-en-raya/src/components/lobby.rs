@@ -0,0 +1,296 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v5                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Vestíbulo del servidor de partidas en red: salas abiertas, creación de
+// una sala con sus reglas, control de tiempo y fichas, y entrada por código
+// para jugar o para mirar. Debajo, la clasificación de los jugadores con
+// cuenta en el servidor y la entrada al archivo de partidas terminadas.
+// Las salas y la clasificación se piden al abrir el vestíbulo; al entrar en
+// una sala la partida pasa al tablero.
+
+
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+use super_tres_engine::clock::TimeControl;
+use super_tres_engine::net::{ClientMessage, RatingInfo, RoomInfo, RoomSettings, ServerMessage};
+use super_tres_engine::ruleset::Ruleset;
+use crate::connection::Connection;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct LobbyProps {
+
+    /// Conexión con el servidor
+    pub connection: Connection,
+
+    /// Cuenta con la que se entró, que se resalta en la clasificación
+    pub account: Option<AttrValue>,
+
+    /// Abre el archivo de partidas terminadas
+    pub on_archive: Callback<()>,
+}
+
+
+/// Nombre de las fichas que se eligen al crear una sala
+fn side_name(side: Option<Player>) -> &'static str {
+    side.map_or("Random", |side| side.symbol())
+}
+
+
+#[function_component(LobbyComponent)]
+pub fn lobby(props: &LobbyProps) -> Html {
+
+    let ruleset = use_state(Ruleset::default);
+    let time_control = use_state(TimeControl::default);
+    let side = use_state(|| None::<Player>);
+    let code = use_state(String::new);
+
+    // Salas abiertas y clasificación del servidor, de mayor a menor puntuación
+    let rooms = use_state(Vec::<RoomInfo>::new);
+    let ratings = use_state(Vec::<RatingInfo>::new);
+
+    {
+        let (rooms, ratings) = (rooms.clone(), ratings.clone());
+        use_effect_with(props.connection.clone(), move |connection| {
+            let listener = connection.listen(Callback::from(move |message| match message {
+                ServerMessage::Rooms(list) => rooms.set(list),
+                ServerMessage::Ratings(list) => ratings.set(list),
+                _ => {},
+            }));
+            connection.send(ClientMessage::List);
+            connection.send(ClientMessage::Ratings);
+            move || drop(listener)
+        });
+    }
+
+    let send = |message: fn(String) -> ClientMessage, code: String| {
+        let connection = props.connection.clone();
+        Callback::from(move |_: MouseEvent| connection.send(message(code.clone())))
+    };
+
+    let on_ruleset = {
+        let ruleset = ruleset.clone();
+        Callback::from(move |event: Event| {
+            if let Ok(value) = event.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                ruleset.set(value);
+            }
+        })
+    };
+    let on_time_control = {
+        let time_control = time_control.clone();
+        Callback::from(move |event: Event| {
+            if let Ok(value) = event.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                time_control.set(value);
+            }
+        })
+    };
+    let on_side = {
+        let side = side.clone();
+        Callback::from(move |event: Event| {
+            side.set(match event.target_unchecked_into::<HtmlSelectElement>().value().as_str() {
+                "X" => Some(Player::First),
+                "O" => Some(Player::Second),
+                _ => None,
+            })
+        })
+    };
+    let on_code_input = {
+        let code = code.clone();
+        Callback::from(move |event: InputEvent| code.set(event.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+
+    // Las fichas se eligen al crear la sala; None para sortearlas
+    let create = {
+        let (connection, ruleset, time_control, side) = (props.connection.clone(), ruleset.clone(), time_control.clone(), side.clone());
+        Callback::from(move |_: MouseEvent| {
+            connection.send(ClientMessage::Create { settings: RoomSettings { ruleset: *ruleset, time_control: *time_control }, side: *side })
+        })
+    };
+    let join = send(ClientMessage::Join, code.trim().to_uppercase());
+    let watch = send(ClientMessage::Watch, code.trim().to_uppercase());
+    let refresh = {
+        let connection = props.connection.clone();
+        Callback::from(move |_: MouseEvent| connection.send(ClientMessage::List))
+    };
+    let refresh_ratings = {
+        let connection = props.connection.clone();
+        Callback::from(move |_: MouseEvent| connection.send(ClientMessage::Ratings))
+    };
+    let archive = {
+        let on_archive = props.on_archive.clone();
+        Callback::from(move |_: MouseEvent| on_archive.emit(()))
+    };
+
+    html! {
+        <div class="lobby">
+
+            // Nueva sala
+            <div class="lobby-section">
+                <p class="lobby-title">{"New room"}</p>
+                <div class="is-flex is-align-items-center lobby-create">
+                    <div class="select is-small">
+                        <select onchange={on_ruleset} title={ruleset.description()}>
+                            {
+                                for Ruleset::ALL.iter().map(|option| html! {
+                                    <option value={option.to_string()} selected={*option == *ruleset}>
+                                        { option.to_string() }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                    <div class="select is-small">
+                        <select onchange={on_time_control}>
+                            {
+                                for TimeControl::PRESETS.iter().map(|option| html! {
+                                    <option value={option.to_string()} selected={*option == *time_control}>
+                                        { option.to_string() }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                    <div class="select is-small">
+                        <select onchange={on_side}>
+                            {
+                                for [None, Some(Player::First), Some(Player::Second)].into_iter().map(|option| html! {
+                                    <option value={side_name(option)} selected={option == *side}>
+                                        { side_name(option) }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                    <button class="button is-small is-high" onclick={create}>{"Create room"}</button>
+                </div>
+            </div>
+
+            // Entrada por código
+            <div class="lobby-section">
+                <p class="lobby-title">{"Join by code"}</p>
+                <div class="field has-addons is-justify-content-center">
+                    <div class="control">
+                        <input class="input is-small online-code" type="text" placeholder="Code" maxlength="8" value={(*code).clone()} oninput={on_code_input} />
+                    </div>
+                    <div class="control">
+                        <button class="button is-small" onclick={join} disabled={code.trim().is_empty()}>{"Join"}</button>
+                    </div>
+                    <div class="control">
+                        <button class="button is-small" onclick={watch} disabled={code.trim().is_empty()}>{"Watch"}</button>
+                    </div>
+                </div>
+            </div>
+
+            // Salas abiertas
+            <div class="lobby-section">
+                <p class="lobby-title">
+                    {"Rooms"}
+                    <button class="button is-small is-text lobby-refresh" onclick={refresh}>{"Refresh"}</button>
+                </p>
+                {
+                    if rooms.is_empty() {
+                        html! { <p class="has-text-centered">{"No games in progress. Create a room and share its code."}</p> }
+                    } else {
+                        html! {
+                            <table class="table is-narrow is-fullwidth lobby-rooms">
+                                <thead>
+                                    <tr>
+                                        <th>{"Room"}</th>
+                                        <th>{"Rules"}</th>
+                                        <th>{"Time"}</th>
+                                        <th>{"You play"}</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {
+                                        for rooms.iter().map(|room| {
+                                            html! {
+                                                <tr>
+                                                    <td class="online-room">{ room.code.clone() }</td>
+                                                    <td title={room.settings.ruleset.description()}>{ room.settings.ruleset.to_string() }</td>
+                                                    <td>{ room.settings.time_control.to_string() }</td>
+                                                    <td>{ room.seat.map_or("Full", |seat| seat.symbol()) }</td>
+                                                    <td class="lobby-room-actions">
+                                                        {
+                                                            match room.seat {
+                                                                Some(_) => html! {
+                                                                    <button class="button is-small" onclick={send(ClientMessage::Join, room.code.clone())}>
+                                                                        {"Join"}
+                                                                    </button>
+                                                                },
+                                                                None => html! {},
+                                                            }
+                                                        }
+                                                        <button class="button is-small" onclick={send(ClientMessage::Watch, room.code.clone())}>
+                                                            {"Watch"}
+                                                        </button>
+                                                    </td>
+                                                </tr>
+                                            }
+                                        })
+                                    }
+                                </tbody>
+                            </table>
+                        }
+                    }
+                }
+            </div>
+
+            // Clasificación
+            <div class="lobby-section">
+                <p class="lobby-title">
+                    {"Ratings"}
+                    <button class="button is-small is-text lobby-refresh" onclick={refresh_ratings}>{"Refresh"}</button>
+                </p>
+                {
+                    if ratings.is_empty() {
+                        html! { <p class="has-text-centered">{"No rated games yet. Sign in and play someone with an account."}</p> }
+                    } else {
+                        html! {
+                            <table class="table is-narrow is-fullwidth lobby-ratings">
+                                <thead>
+                                    <tr>
+                                        <th>{"#"}</th>
+                                        <th>{"Player"}</th>
+                                        <th title="Glicko-2 rating and its deviation">{"Rating"}</th>
+                                        <th>{"Games"}</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {
+                                        for ratings.iter().enumerate().map(|(rank, rating)| {
+                                            let own = props.account.as_deref().is_some_and(|account| account.eq_ignore_ascii_case(&rating.name));
+                                            html! {
+                                                <tr class={classes!(own.then_some("is-selected"))}>
+                                                    <td>{ rank + 1 }</td>
+                                                    <td>{ rating.name.clone() }</td>
+                                                    <td>{ format!("{} ± {}", rating.rating, rating.deviation) }</td>
+                                                    <td>{ rating.games }</td>
+                                                </tr>
+                                            }
+                                        })
+                                    }
+                                </tbody>
+                            </table>
+                        }
+                    }
+                }
+            </div>
+
+            // Archivo
+            <div class="lobby-section has-text-centered">
+                <p class="lobby-title">{"Archive"}</p>
+                <p>{"Search the finished games of this server and replay them."}</p>
+                <button class="button is-small" onclick={archive}>{"Open archive"}</button>
+            </div>
+
+        </div>
+    }
+}
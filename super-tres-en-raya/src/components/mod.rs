@@ -1,2 +1,17 @@
 pub mod super_tres;
-pub mod nav_bar;
\ No newline at end of file
+pub mod nav_bar;
+pub mod analysis;
+pub mod review;
+pub mod players;
+pub mod export;
+pub mod online;
+pub mod lobby;
+pub mod chat;
+pub mod clock;
+pub mod correspondence;
+pub mod peer;
+pub mod archive;
+pub mod shortcuts;
+pub mod board;
+pub mod server;
+pub mod archive_view;
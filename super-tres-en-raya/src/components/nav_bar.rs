@@ -1,17 +1,43 @@
 //-------------------------------------------------------------------//
 //  AUTHOR:    @sfmolina                                            //
-//  Version:   v2                                                  //
-//  Modified:  31dec24                                            //
+//  Version:   v3                                                  //
+//  Modified:  19oc26                                             //
 //---------------------------------------------------------------//
 
 
 
 use yew::prelude::*;
+use crate::app::View;
 
 
 
+#[derive(Properties, PartialEq)]
+pub struct NavbarProps {
+
+    /// Vista que se muestra
+    pub view: View,
+
+    /// Hay conexión con el servidor. Sin ella no hay archivo.
+    pub connected: bool,
+
+    pub on_view: Callback<View>,
+}
+
+
 #[function_component(NavbarComponent)]
-pub fn navbar() -> Html {
+pub fn navbar(props: &NavbarProps) -> Html {
+
+    let tab = |view: View, label: &'static str, enabled: bool| {
+        let on_view = props.on_view.clone();
+        html! {
+            <a
+                class={classes!("navbar-item", "nav-tab", (props.view == view).then_some("is-active"), (!enabled).then_some("is-disabled"))}
+                onclick={enabled.then(|| Callback::from(move |_: MouseEvent| on_view.emit(view)))}
+            >
+                { label }
+            </a>
+        }
+    };
 
     html! {
         <div class="nav-style">
@@ -20,6 +46,9 @@ pub fn navbar() -> Html {
                     <a class="navbar-item username" href="https://sfmolina.github.io/">
                         {"@sfmolina"}
                     </a>
+                    { tab(View::Game, "Play", true) }
+                    { tab(View::Lobby, "Lobby", true) }
+                    { tab(View::Archive, "Archive", props.connected) }
                 </div>
             </nav>
         </div>
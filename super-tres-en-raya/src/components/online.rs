@@ -0,0 +1,176 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v7                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Panel de la sala de una partida en red: su estado, con el enlace para
+// invitar al rival. Las salas se crean y se eligen en el vestíbulo. Los
+// espectadores ven a quién le toca en lugar de sus fichas. Durante la
+// partida los jugadores pueden proponer tablas, pedir deshacer su última
+// jugada, responder a las propuestas del rival o abandonar.
+
+
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+use super_tres_engine::net::{Offer, RoomSettings};
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct OnlineProps {
+
+    /// Código de la sala en la que se está
+    pub room: AttrValue,
+
+    /// Fichas con las que se juega en la sala. None si se está mirando.
+    pub seat: Option<Player>,
+
+    /// Turno de la partida de la sala
+    pub turn: Option<Player>,
+
+    /// Reglas y control de tiempo de la sala
+    pub settings: RoomSettings,
+
+    /// El rival está sentado en la sala
+    pub opponent: bool,
+
+    /// Segundos que se guarda el asiento del rival desconectado
+    pub away: Option<u32>,
+
+    /// Propuesta pendiente de respuesta y quién la hizo
+    pub offer: Option<(Offer, Player)>,
+
+    /// Último error del servidor en la sala
+    pub error: Option<AttrValue>,
+
+    pub on_leave: Callback<()>,
+    pub on_offer: Callback<Offer>,
+
+    /// Acepta (true) o rechaza la propuesta del rival
+    pub on_answer: Callback<(Offer, bool)>,
+    pub on_resign: Callback<()>,
+}
+
+
+/// Texto de una propuesta
+fn offer_text(offer: Offer, from: Player) -> String {
+    match offer {
+        Offer::Draw => format!("{} offers a draw", from.symbol()),
+        Offer::Takeback => format!("{} asks to take back their last move", from.symbol()),
+    }
+}
+
+
+/// Enlace a la aplicación que entra en la sala al conectarse
+fn invite_link(room: &str) -> Option<String> {
+    let location = web_sys::window()?.location();
+    Some(format!("{}{}?room={}", location.origin().ok()?, location.pathname().ok()?, room))
+}
+
+
+#[function_component(OnlineComponent)]
+pub fn online(props: &OnlineProps) -> Html {
+
+    let emit = |callback: &Callback<()>| {
+        let callback = callback.clone();
+        Callback::from(move |_: MouseEvent| callback.emit(()))
+    };
+    let select_all = Callback::from(|event: FocusEvent| event.target_unchecked_into::<HtmlInputElement>().select());
+    let offer = |offer: Offer| {
+        let on_offer = props.on_offer.clone();
+        Callback::from(move |_: MouseEvent| on_offer.emit(offer))
+    };
+    let answer = |offer: Offer, accept: bool| {
+        let on_answer = props.on_answer.clone();
+        Callback::from(move |_: MouseEvent| on_answer.emit((offer, accept)))
+    };
+
+    // Propuestas y abandono, solo para los jugadores de una partida en juego con rival
+    let game_actions = match props.seat {
+        Some(seat) if props.turn.is_some() && (props.opponent || props.away.is_some()) => html! {
+            <div class="is-flex is-align-items-center online-actions online-game-actions">
+                {
+                    match props.offer {
+                        Some((pending, from)) if from == seat => html! {
+                            <span>{ format!("You offered a {}, waiting for an answer", pending) }</span>
+                        },
+                        Some((pending, from)) => html! {
+                            <>
+                                <span>{ offer_text(pending, from) }</span>
+                                <button class="button is-small is-high" onclick={answer(pending, true)}>{"Accept"}</button>
+                                <button class="button is-small" onclick={answer(pending, false)}>{"Decline"}</button>
+                            </>
+                        },
+                        None => html! {
+                            <>
+                                <button class="button is-small" onclick={offer(Offer::Draw)}>{"Offer draw"}</button>
+                                <button class="button is-small" onclick={offer(Offer::Takeback)}>{"Takeback"}</button>
+                            </>
+                        },
+                    }
+                }
+                <button class="button is-small" onclick={emit(&props.on_resign)}>{"Resign"}</button>
+            </div>
+        },
+        _ => html! {},
+    };
+
+    let room = &props.room;
+    let body = html! {
+        <>
+            <div class="is-flex is-align-items-center online-actions">
+                <span>{"Room "}<strong class="online-room">{ room.clone() }</strong></span>
+                {
+                    match (props.seat, props.turn) {
+                        (Some(seat), _) => html! { <span>{ format!("You play {}", seat.symbol()) }</span> },
+                        (None, Some(turn)) => html! { <span>{ format!("Watching, {} to move", turn.symbol()) }</span> },
+                        (None, None) => html! { <span>{"Watching"}</span> },
+                    }
+                }
+                <span title={props.settings.ruleset.description()}>
+                    { format!("{}, {}", props.settings.ruleset, props.settings.time_control) }
+                </span>
+                {
+                    match (props.seat, props.opponent, props.away) {
+                        (None, _, _) => html! {},
+                        (Some(_), true, _) => html! { <span>{"Opponent connected"}</span> },
+                        (Some(_), false, Some(seconds)) => html! {
+                            <span>{ format!("Opponent disconnected, their seat is held for {} s", seconds) }</span>
+                        },
+                        (Some(_), false, None) => html! { <span>{"Waiting for an opponent…"}</span> },
+                    }
+                }
+                <button class="button is-small" onclick={emit(&props.on_leave)}>{"Leave"}</button>
+            </div>
+            { game_actions }
+            {
+                match invite_link(room).filter(|_| props.seat.is_some() && !props.opponent && props.away.is_none()) {
+                    Some(link) => html! {
+                        <div class="field online-invite">
+                            <label class="label is-small">{"Invite link"}</label>
+                            <div class="control">
+                                <input class="input is-small" type="text" readonly=true value={link} onfocus={select_all} />
+                            </div>
+                        </div>
+                    },
+                    None => html! {},
+                }
+            }
+        </>
+    };
+
+    html! {
+        <div class="online">
+            { body }
+            {
+                match &props.error {
+                    Some(error) => html! { <p class="online-error has-text-centered">{ error.clone() }</p> },
+                    None => html! {},
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,153 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Panel de las partidas directas entre dos navegadores: abrir una partida
+// y pegar la respuesta del rival, o unirse con el código de quien la abrió
+// y devolverle la respuesta. Con el canal abierto, el estado de la partida.
+
+
+
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct PeerProps {
+
+    /// Fichas con las que se juega. None si no hay partida directa: quien la abre juega con X.
+    pub seat: Option<Player>,
+
+    /// Código de conexión local para mandarlo al rival, cuando ya está listo
+    pub signal: Option<AttrValue>,
+
+    /// El canal con el rival está abierto
+    pub open: bool,
+
+    /// Turno de la partida. None si terminó.
+    pub turn: Option<Player>,
+    pub error: Option<AttrValue>,
+
+    pub on_host: Callback<()>,
+
+    /// Se une a la partida con el código de quien la abrió
+    pub on_join: Callback<String>,
+
+    /// Conecta con la respuesta del rival
+    pub on_answer: Callback<String>,
+    pub on_leave: Callback<()>,
+}
+
+
+#[function_component(PeerComponent)]
+pub fn peer(props: &PeerProps) -> Html {
+
+    let code = use_state(String::new);
+
+    let on_code_input = {
+        let code = code.clone();
+        Callback::from(move |event: InputEvent| code.set(event.target_unchecked_into::<HtmlTextAreaElement>().value()))
+    };
+    let submit = |callback: &Callback<String>| {
+        let (callback, code) = (callback.clone(), code.clone());
+        Callback::from(move |_: MouseEvent| {
+            callback.emit(code.trim().to_string());
+            code.set(String::new());
+        })
+    };
+    let on_host = {
+        let on_host = props.on_host.clone();
+        Callback::from(move |_: MouseEvent| on_host.emit(()))
+    };
+    let on_leave = {
+        let on_leave = props.on_leave.clone();
+        Callback::from(move |_: MouseEvent| on_leave.emit(()))
+    };
+    let select_all = Callback::from(|event: FocusEvent| event.target_unchecked_into::<HtmlTextAreaElement>().select());
+
+    let code_input = |placeholder: &'static str, label: &'static str, callback: &Callback<String>| html! {
+        <div class="field">
+            <div class="control">
+                <textarea class="textarea is-small peer-code" rows="3" placeholder={placeholder} value={(*code).clone()} oninput={on_code_input.clone()} />
+            </div>
+            <div class="control">
+                <button class="button is-small is-high" onclick={submit(callback)} disabled={code.trim().is_empty()}>{ label }</button>
+            </div>
+        </div>
+    };
+
+    let signal = match &props.signal {
+        Some(signal) => html! {
+            <div class="field online-invite">
+                <label class="label is-small">{"Send this code to your opponent"}</label>
+                <div class="control">
+                    <textarea class="textarea is-small peer-code" rows="3" readonly=true value={signal.clone()} onfocus={select_all} />
+                </div>
+            </div>
+        },
+        None => html! { <p>{"Preparing the connection code…"}</p> },
+    };
+
+    let body = match props.seat {
+        Some(seat) if props.open => html! {
+            <div class="is-flex is-align-items-center online-actions">
+                <span>{"Connected directly"}</span>
+                <span>{ format!("You play {}", seat.symbol()) }</span>
+                <span>
+                    {
+                        match props.turn {
+                            None => "Game over",
+                            Some(turn) if turn == seat => "Your move",
+                            Some(_) => "Opponent's move",
+                        }
+                    }
+                </span>
+                <button class="button is-small" onclick={on_leave}>{"Leave"}</button>
+            </div>
+        },
+        Some(Player::First) => html! {
+            <>
+                <div class="is-flex is-align-items-center online-actions">
+                    <span>{"Direct game: you play X"}</span>
+                    <button class="button is-small" onclick={on_leave}>{"Cancel"}</button>
+                </div>
+                { signal }
+                { code_input("Paste your opponent's answer", "Connect", &props.on_answer) }
+            </>
+        },
+        Some(Player::Second) => html! {
+            <>
+                <div class="is-flex is-align-items-center online-actions">
+                    <span>{"Direct game: you play O. Waiting for the host to connect…"}</span>
+                    <button class="button is-small" onclick={on_leave}>{"Cancel"}</button>
+                </div>
+                { signal }
+            </>
+        },
+        None => html! {
+            <>
+                <div class="is-flex is-align-items-center online-actions">
+                    <span>{"Direct game"}</span>
+                    <button class="button is-small" onclick={on_host}>{"Host as X"}</button>
+                </div>
+                { code_input("Paste the host's code", "Join as O", &props.on_join) }
+            </>
+        },
+    };
+
+    html! {
+        <div class="online peer">
+            { body }
+            {
+                match &props.error {
+                    Some(error) => html! { <p class="online-error has-text-centered">{ error.clone() }</p> },
+                    None => html! {},
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,130 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Panel de jugadores: cada lado lo juega una persona o un bot subido
+// como módulo WebAssembly (ver `super_tres_engine::abi`), y el control de
+// tiempo de la partida.
+
+
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use gloo_file::File;
+use super_tres_engine::board::Player;
+use super_tres_engine::clock::TimeControl;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct PlayersProps {
+
+    /// Nombre del bot de X y de O. None si juega una persona.
+    pub bots: [Option<AttrValue>; 2],
+
+    /// Último error al cargar o ejecutar un bot
+    pub error: Option<AttrValue>,
+
+    /// Se eligió un fichero para el bot de ese jugador
+    pub on_upload: Callback<(Player, File)>,
+
+    /// Ese jugador vuelve a ser una persona
+    pub on_human: Callback<Player>,
+
+    /// Control de tiempo de la partida
+    pub time_control: TimeControl,
+
+    /// Se eligió otro control de tiempo. Los relojes vuelven a empezar.
+    pub on_time_control: Callback<TimeControl>,
+}
+
+
+#[function_component(PlayersComponent)]
+pub fn players(props: &PlayersProps) -> Html {
+    html! {
+        <div class="players">
+            { for [Player::First, Player::Second].into_iter().map(|player| render_player(props, player)) }
+            { render_time_control(props) }
+            {
+                match &props.error {
+                    Some(error) => html! { <p class="players-error has-text-centered">{ error.clone() }</p> },
+                    None => html! {},
+                }
+            }
+        </div>
+    }
+}
+
+
+fn render_player(props: &PlayersProps, player: Player) -> Html {
+
+    let bot = &props.bots[player.index()];
+
+    let on_change = {
+        let on_upload = props.on_upload.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                on_upload.emit((player, File::from(file)));
+            }
+            // Para poder volver a subir el mismo fichero
+            input.set_value("");
+        })
+    };
+
+    let on_human = {
+        let on_human = props.on_human.clone();
+        Callback::from(move |_: MouseEvent| on_human.emit(player))
+    };
+
+    let player_class = match player {
+        Player::First => "first-player",
+        Player::Second => "second-player",
+    };
+
+    html! {
+        <div class="is-flex is-align-items-center player">
+            <span class={classes!("player-symbol", player_class)}>{ player.symbol() }</span>
+            <span class="player-name">
+                { bot.clone().unwrap_or_else(|| AttrValue::from("Human")) }
+            </span>
+            <label class="button is-small">
+                { "Upload bot" }
+                <input class="is-hidden" type="file" accept=".wasm" onchange={on_change} />
+            </label>
+            <button class="button is-small" onclick={on_human} disabled={bot.is_none()}>{ "Human" }</button>
+        </div>
+    }
+}
+
+
+fn render_time_control(props: &PlayersProps) -> Html {
+
+    let on_change = {
+        let on_time_control = props.on_time_control.clone();
+        Callback::from(move |event: Event| {
+            if let Ok(value) = event.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                on_time_control.emit(value);
+            }
+        })
+    };
+
+    html! {
+        <div class="is-flex is-align-items-center player">
+            <span class="player-name">{ "Clock" }</span>
+            <div class="select is-small">
+                <select onchange={on_change}>
+                    {
+                        for TimeControl::PRESETS.iter().map(|option| html! {
+                            <option value={option.to_string()} selected={*option == props.time_control}>
+                                { option.to_string() }
+                            </option>
+                        })
+                    }
+                </select>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,147 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Panel de análisis de una partida terminada: resumen por jugador,
+// jugadas señaladas y navegación por la partida.
+
+
+
+use yew::prelude::*;
+use super_tres_engine::board::Player;
+use super_tres_engine::review::{AnnotatedMove, GameAnalysis, MoveClass};
+use super_tres_engine::search::format_score;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct GameReviewProps {
+
+    /// Análisis de la partida
+    pub analysis: GameAnalysis,
+
+    /// Número de jugadas de la posición que se está viendo.
+    /// Si es None, se ve la posición final.
+    pub viewed: Option<usize>,
+
+    /// Salta a la posición tras ese número de jugadas, o vuelve a la partida con None
+    pub on_review: Callback<Option<usize>>,
+}
+
+
+#[function_component(GameReviewComponent)]
+pub fn game_review(props: &GameReviewProps) -> Html {
+
+    let total = props.analysis.moves.len();
+    let viewed = props.viewed.unwrap_or(total);
+
+    let jump = |ply: usize| {
+        let on_review = props.on_review.clone();
+        Callback::from(move |_: MouseEvent| on_review.emit(Some(ply)))
+    };
+
+    let back = {
+        let on_review = props.on_review.clone();
+        Callback::from(move |_: MouseEvent| on_review.emit(None))
+    };
+
+    html! {
+        <div class="review">
+
+            // Resumen por jugador
+            <table class="table is-narrow is-fullwidth review-summary">
+                <thead>
+                    <tr>
+                        <th></th>
+                        <th>{"Inaccuracies"}</th>
+                        <th>{"Mistakes"}</th>
+                        <th>{"Blunders"}</th>
+                        <th>{"Avg. loss"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for [Player::First, Player::Second].into_iter().map(|player| {
+                        let summary = props.analysis.summary(player);
+                        html! {
+                            <tr>
+                                <th>{ player.symbol() }</th>
+                                <td>{ summary.inaccuracies }</td>
+                                <td>{ summary.mistakes }</td>
+                                <td>{ summary.blunders }</td>
+                                <td>{ format!("{:.2}", summary.average_loss as f64 / 100.0) }</td>
+                            </tr>
+                        }
+                    }) }
+                </tbody>
+            </table>
+
+            // Navegación
+            <div class="buttons has-addons is-centered review-nav">
+                <button class="button is-small" onclick={jump(0)} disabled={viewed == 0}>{"⏮"}</button>
+                <button class="button is-small" onclick={jump(viewed.saturating_sub(1))} disabled={viewed == 0}>{"◀"}</button>
+                <span class="button is-small is-static">{ format!("{} / {}", viewed, total) }</span>
+                <button class="button is-small" onclick={jump((viewed + 1).min(total))} disabled={viewed == total}>{"▶"}</button>
+                <button class="button is-small" onclick={jump(total)} disabled={viewed == total}>{"⏭"}</button>
+                <button class="button is-small is-high" onclick={back} disabled={props.viewed.is_none()}>{"Back to game"}</button>
+            </div>
+
+            // Comentario de la jugada que se está viendo
+            {
+                match viewed.checked_sub(1).and_then(|ply| props.analysis.moves.get(ply)) {
+                    Some(annotated) => render_comment(annotated),
+                    None => html! {},
+                }
+            }
+
+            // Jugadas señaladas
+            <div class="tags review-flagged">
+                { for props.analysis.flagged().map(|annotated| {
+                    html! {
+                        <span
+                            class={classes!("tag", "is-clickable", class_tag(annotated.class), (viewed == annotated.ply + 1).then_some("is-selected"))}
+                            onclick={jump(annotated.ply + 1)}
+                        >
+                            { format!("{}. {} {}{}", annotated.ply + 1, annotated.player.symbol(), annotated.played, annotated.class.symbol()) }
+                        </span>
+                    }
+                }) }
+            </div>
+        </div>
+    }
+}
+
+
+fn render_comment(annotated: &AnnotatedMove) -> Html {
+
+    let best = match annotated.best {
+        Some(best) if best != annotated.played => format!(" Best was {}.", best),
+        _ => String::new(),
+    };
+
+    html! {
+        <p class="review-comment has-text-centered">
+            { format!(
+                "{}. {} {}{} ({} → {}).{}",
+                annotated.ply + 1,
+                annotated.player.symbol(),
+                annotated.played,
+                annotated.class.symbol(),
+                format_score(annotated.score_before),
+                format_score(annotated.score_after),
+                best
+            ) }
+        </p>
+    }
+}
+
+
+fn class_tag(class: MoveClass) -> &'static str {
+    match class {
+        MoveClass::Best => "is-success",
+        MoveClass::Inaccuracy => "is-info",
+        MoveClass::Mistake => "is-warning",
+        MoveClass::Blunder => "is-danger",
+    }
+}
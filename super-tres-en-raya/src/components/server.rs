@@ -0,0 +1,129 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Conexión con el servidor de partidas en red, encima del vestíbulo: la
+// dirección del servidor, o la entrada con una cuenta para que las partidas
+// puntúen y la desconexión. Con un enlace de invitación se entra en la sala
+// al conectarse.
+
+
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use super_tres_engine::net::{is_valid_name, MAX_NAME_LENGTH};
+
+
+
+/// Servidor que se propone al abrir el panel
+const DEFAULT_SERVER: &str = "ws://localhost:9000";
+
+
+#[derive(Properties, PartialEq)]
+pub struct ServerProps {
+
+    /// Hay conexión con el servidor
+    pub connected: bool,
+
+    /// Código de la sala a la que se invitó con el enlace
+    pub invite: Option<AttrValue>,
+
+    /// Cuenta con la que se entró en el servidor
+    pub account: Option<AttrValue>,
+
+    /// Último error del servidor o de la conexión
+    pub error: Option<AttrValue>,
+
+    pub on_connect: Callback<String>,
+    pub on_disconnect: Callback<()>,
+
+    /// Entra con la cuenta de ese nombre, o la crea si está libre
+    pub on_login: Callback<String>,
+}
+
+
+#[function_component(ServerComponent)]
+pub fn server(props: &ServerProps) -> Html {
+
+    let server = use_state(|| DEFAULT_SERVER.to_string());
+    let name = use_state(String::new);
+
+    let on_server_input = {
+        let server = server.clone();
+        Callback::from(move |event: InputEvent| server.set(event.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let connect = {
+        let (on_connect, server) = (props.on_connect.clone(), server.clone());
+        Callback::from(move |_: MouseEvent| on_connect.emit((*server).clone()))
+    };
+    let disconnect = {
+        let on_disconnect = props.on_disconnect.clone();
+        Callback::from(move |_: MouseEvent| on_disconnect.emit(()))
+    };
+    let on_name_input = {
+        let name = name.clone();
+        Callback::from(move |event: InputEvent| name.set(event.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let login = {
+        let (on_login, name) = (props.on_login.clone(), name.clone());
+        Callback::from(move |_: MouseEvent| on_login.emit(name.trim().to_string()))
+    };
+
+    let body = if props.connected {
+        html! {
+            <div class="is-flex is-align-items-center online-actions">
+                <span>{"Connected to the lobby"}</span>
+                {
+                    match &props.account {
+                        Some(account) => html! { <span>{"Signed in as "}<strong>{ account.clone() }</strong></span> },
+                        None => html! {
+                            <div class="field has-addons online-login">
+                                <div class="control">
+                                    <input class="input is-small" type="text" placeholder="Name" maxlength={MAX_NAME_LENGTH.to_string()} value={(*name).clone()} oninput={on_name_input} />
+                                </div>
+                                <div class="control">
+                                    <button class="button is-small" title="Rated games need an account; it is created if the name is free" onclick={login} disabled={!is_valid_name(name.trim())}>
+                                        {"Sign in"}
+                                    </button>
+                                </div>
+                            </div>
+                        },
+                    }
+                }
+                <button class="button is-small" onclick={disconnect}>{"Disconnect"}</button>
+            </div>
+        }
+    } else {
+        html! {
+            <div class="field has-addons">
+                <div class="control is-expanded">
+                    <input class="input is-small" type="text" value={(*server).clone()} oninput={on_server_input} />
+                </div>
+                <div class="control">
+                    <button class="button is-small is-high" onclick={connect}>
+                        {
+                            match &props.invite {
+                                Some(room) => format!("Connect and join {}", room),
+                                None => "Connect".to_string(),
+                            }
+                        }
+                    </button>
+                </div>
+            </div>
+        }
+    };
+
+    html! {
+        <div class="online">
+            { body }
+            {
+                match &props.error {
+                    Some(error) => html! { <p class="online-error has-text-centered">{ error.clone() }</p> },
+                    None => html! {},
+                }
+            }
+        </div>
+    }
+}
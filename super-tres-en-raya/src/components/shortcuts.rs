@@ -0,0 +1,53 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Ayuda con los atajos de teclado, encima del resto de la página.
+
+
+
+use yew::prelude::*;
+use crate::keyboard::SHORTCUTS;
+
+
+
+#[derive(Properties, PartialEq)]
+pub struct ShortcutsProps {
+    pub on_close: Callback<()>,
+}
+
+
+#[function_component(ShortcutsComponent)]
+pub fn shortcuts(props: &ShortcutsProps) -> Html {
+
+    let close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    html! {
+        <div class="modal is-active shortcuts">
+            <div class="modal-background" onclick={close.clone()}></div>
+            <div class="modal-content">
+                <div class="box">
+                    <p class="title is-5">{"Keyboard shortcuts"}</p>
+                    <table class="table is-narrow is-fullwidth">
+                        <tbody>
+                            {
+                                for SHORTCUTS.iter().map(|(keys, action)| html! {
+                                    <tr>
+                                        <td class="shortcut-keys"><kbd>{ *keys }</kbd></td>
+                                        <td>{ *action }</td>
+                                    </tr>
+                                })
+                            }
+                        </tbody>
+                    </table>
+                </div>
+            </div>
+            <button class="modal-close is-large" aria-label="close" onclick={close}></button>
+        </div>
+    }
+}
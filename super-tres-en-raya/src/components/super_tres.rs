@@ -1,10 +1,12 @@
 //-------------------------------------------------------------------//
 //  AUTHOR:    @sfmolina                                            //
-//  Version:   v2                                                  //
-//  Modified:  31dec24                                            //
+//  Version:   v22                                                 //
+//  Modified:  19oc26                                             //
 //---------------------------------------------------------------//
-// DESCRIPCIÓN:                                                
-// Componente de un juego de super tres en raya.
+// DESCRIPCIÓN:
+// Componente de un juego de super tres en raya: partidas locales entre
+// personas y bots, en red, por correspondencia y directas entre navegadores.
+// Sigue montado, oculto, mientras se ven el vestíbulo o el archivo.
 
 
 
@@ -13,461 +15,924 @@
 
 
 
+use std::cell::Cell;
+use std::rc::Rc;
+use gloo_events::EventListener;
+use gloo_file::callbacks::{read_as_bytes, FileReader};
+use gloo_file::File;
+use gloo_timers::callback::{Interval, Timeout};
+use web_sys::js_sys::Date;
 use yew::prelude::*;
-use std::collections::HashSet;
+use super_tres_engine::board::{Player, Position};
+use super_tres_engine::bot::Bot;
+use super_tres_engine::clock::{Clock, TimeControl};
+use super_tres_engine::correspondence::{new_game_id, MoveCode};
+use super_tres_engine::game::{Game, GameResult, Move};
+use super_tres_engine::net::{ClientMessage, Offer, RoomSettings, ServerMessage};
+use super_tres_engine::review::{GameAnalysis, GameReview};
+use super_tres_engine::rng::Rng;
+use super_tres_engine::ruleset::Ruleset;
+use super_tres_engine::search::{search_observed, SearchLimits, SearchObserver, SearchResult};
+use super_tres_engine::solver::{Solution, Solver};
+use super_tres_engine::tt::TranspositionTable;
+use super_tres_engine::wasm_bot::WasmBot;
+use crate::components::analysis::{EvalBarComponent, PrincipalVariationComponent, TablebaseComponent};
+use crate::components::board::BoardComponent;
+use crate::components::chat::{ChatComponent, ChatLine};
+use crate::components::clock::ClockComponent;
+use crate::components::correspondence::CorrespondenceComponent;
+use crate::components::export::ExportComponent;
+use crate::components::online::OnlineComponent;
+use crate::components::peer::PeerComponent;
+use crate::components::players::PlayersComponent;
+use crate::components::review::GameReviewComponent;
+use crate::components::shortcuts::ShortcutsComponent;
+use crate::connection::{Connection, Listener};
+use crate::correspondence::Correspondence;
+use crate::keyboard::{self, Shortcut};
+use crate::peer::{Peer, PeerCallbacks};
+use crate::session::Session;
 
 
 
 //-------------------------------------------------------------------
 //-------------------------------------------------------------------
-// DATA STRUCTURES
+// COMPONENT
+
+
+/// Límites de la búsqueda que alimenta el análisis.
+/// La búsqueda se hace en el hilo principal, así que tiene que ser corta.
+const ANALYSIS_LIMITS: SearchLimits = SearchLimits { depth: 6, nodes: 60_000 };
+
+/// Milisegundos máximos de la búsqueda del análisis y, aparte, de la
+/// resolución exacta. Cada una se hace después de pintar la página.
+const ANALYSIS_MS: f64 = 100.0;
+
+/// Límites de cada búsqueda del análisis de la partida terminada.
+/// Se analiza una jugada en cada paso, y entre paso y paso se pinta la página.
+const REVIEW_LIMITS: SearchLimits = SearchLimits { depth: 5, nodes: 20_000 };
+
+/// Casillas libres por debajo de las cuales se resuelve la posición de forma exacta
+const TABLEBASE_EMPTY: usize = 18;
+
+/// Nodos máximos de cada resolución exacta
+const TABLEBASE_NODES: u64 = 200_000;
+
+/// Tamaño de las tablas de transposición del análisis y del solucionador
+const TABLE_MEGABYTES: usize = 4;
 
+/// Espera antes de cada jugada de un bot, para que se puedan seguir las partidas entre bots
+const BOT_DELAY_MS: u32 = 400;
 
-/// Enumeración que representa a los jugadores
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub enum Player {
-    First,
-    Second,
+/// Líneas del chat que se conservan
+const CHAT_LINES: usize = 100;
+
+/// Cada cuánto se refresca el reloj que corre
+const CLOCK_TICK_MS: u32 = 100;
+
+
+/// Mensajes que puede recibir el componente
+pub enum SuperTresMsg {
+    Mark(Position, Position),
+    Check,
+    ToggleAnalysis,
+    Analyse,
+    AnalyseStep,
+    SearchPosition,
+    SolvePosition,
+    Review(Option<usize>),
+    UploadBot(Player, File),
+    BotLoaded(Player, String, Result<Vec<u8>, String>),
+    Human(Player),
+    BotMove,
+    SetTimeControl(TimeControl),
+    Tick,
+    NewCorrespondence,
+    ApplyCode(String),
+    AbandonCorrespondence,
+    HostPeer,
+    JoinPeer(String),
+    PeerAnswer(String),
+    PeerSignal(String),
+    PeerOpen,
+    PeerMessage(ClientMessage),
+    PeerClosed(Option<String>),
+    LeavePeer,
+    LeaveRoom,
+    Say(String),
+    React(String),
+    Offer(Offer),
+    Answer(Offer, bool),
+    Resign,
+    Server(ServerMessage),
+    Shortcut(Shortcut),
+    ToggleShortcuts,
 }
 
-/// Función que cambia de jugador
-fn switch(op: Option<Player>) -> Option<Player> {
-    match op {
-        Some(Player::First) => Some(Player::Second),
-        Some(Player::Second) => Some(Player::First),
-        None => None,
+
+/// Partida en red: sala del servidor en la que se está
+struct Online {
+    connection: Connection,
+    room: String,
+
+    /// Fichas con las que se juega en la sala. None si se está mirando.
+    seat: Option<Player>,
+
+    /// Reglas y control de tiempo de la sala
+    settings: RoomSettings,
+
+    /// El rival está sentado en la sala
+    opponent: bool,
+
+    /// Segundos que el servidor guarda el asiento del rival desconectado
+    away: Option<u32>,
+
+    /// Propuesta pendiente de respuesta y quién la hizo
+    offer: Option<(Offer, Player)>,
+
+    /// Chat de la sala
+    chat: Vec<ChatLine>,
+}
+
+
+/// Corta la búsqueda al llegar el momento, en milisegundos desde la época
+struct Deadline(f64);
+
+impl SearchObserver for Deadline {
+    fn should_stop(&self) -> bool {
+        Date::now() >= self.0
     }
 }
 
-/// Estructura que representa una posición en el tablero
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Position {
-    pub x: u8,
-    pub y: u8,
+
+/// Análisis de la partida terminada
+enum Reviewing {
+
+    /// En curso, con el paso siguiente programado. Se cancela al descartarlo.
+    Running { review: Box<GameReview>, _step: Timeout },
+    Done(GameAnalysis),
 }
 
-impl Position {
 
-    /// Proporciona la posición total en el supertablero a partir de la posición en un subtablero
-    fn total_pos(pos1: Position, pos2: Position) -> Position {
-        Position { x: pos1.x * 3 + pos2.x, y: pos1.y * 3 + pos2.y }
-    }
+/// Partida directa con otro navegador, sin servidor
+struct PeerGame {
+    peer: Peer,
 
-    fn partial_pos(total_pos: Position) -> (Position, Position) {
-        (Position { x: total_pos.x / 3, y: total_pos.y / 3 }, Position { x: total_pos.x % 3, y: total_pos.y % 3 })
-    }
+    /// Fichas con las que se juega: X quien abre la partida, O quien se une
+    seat: Player,
+
+    /// Código de conexión local para mandarlo al rival, cuando ya está listo
+    signal: Option<String>,
+
+    /// El canal con el rival está abierto
+    open: bool,
 }
 
-/// Posición inalcanzable.
-/// 
-/// X = 3, Y = 3
-/// 
-const UNREACHABLE: Position = Position { x: 9, y: 9 };
 
-/// Estructura que representa un tablero de 3x3
-#[derive(Clone, PartialEq, Debug)]
-struct TableroTres {
-    data: [[Option<Player>; 3]; 3],
+#[derive(Properties, PartialEq)]
+pub struct SuperTresProps {
+
+    /// Se ve la partida. Si no, sigue oculta mientras se ve otra vista.
+    pub visible: bool,
+
+    /// Conexión con el servidor de partidas en red, si la hay
+    pub connection: Option<Connection>,
+
+    /// Se dejó la sala de la partida en red
+    pub on_leave: Callback<()>,
 }
 
-impl TableroTres {
 
-    fn new() -> Self {
-        Self {
-            data: [[None; 3]; 3],
-        }
-    }
+/// Información que guarda el componente
+pub struct SuperTresComponent {
 
-    fn put(&mut self, pos: Position, value: Player) {
-        self.data[pos.x as usize][pos.y as usize] = Some(value);
-    }
+    /// Se ve la partida
+    visible: bool,
 
-    fn reset(&mut self, pos: Position) {
-        self.data[pos.x as usize][pos.y as usize] = None;
-    }
+    /// Partida en curso con las jugadas ya confirmadas
+    game: Game,
 
-    fn get(&self, pos: Position) -> Option<Player> {
-        self.data[pos.x as usize][pos.y as usize]
-    }
+    /// Jugada marcada en este turno y todavía no confirmada con NEXT.
+    /// Se puede desmarcar o cambiar por otra mientras no se confirme.
+    pending: Option<Move>,
 
-    fn check(&self) -> Option<Player> {
+    /// Indica si se muestra el análisis del motor
+    show_analysis: bool,
 
-        let check_line = |line: [Option<Player>; 3]| -> Option<Player> {
-            if line.iter().all(|x| x.is_some()) && line[0] == line[1] && line[1] == line[2] {
-                // Si todos los elementos son iguales y no son None, devuelvo el valor, el tablero se completó
-                line[0]
-            } else {
-                None
-            }
+    /// La posición mostrada cambió y se analiza después de pintarla
+    analysis_stale: bool,
+
+    /// Paso del análisis programado. Se cancela al descartarlo.
+    analysis_timer: Option<Timeout>,
+
+    /// Resultado de la última búsqueda sobre la posición mostrada
+    analysis: Option<SearchResult>,
+
+    /// Tabla de transposición del análisis. Se conserva entre posiciones.
+    tt: TranspositionTable,
+
+    /// Solucionador exacto de finales. Conserva su tabla entre posiciones.
+    solver: Solver,
+
+    /// Valor teórico de la posición mostrada, si se pudo resolver
+    solution: Option<Solution>,
+
+    /// Análisis de la partida terminada, hecho o en curso
+    game_analysis: Option<Reviewing>,
+
+    /// Posición de la partida que se está revisando y número de jugadas hasta ella.
+    /// Si es None, se muestra la partida en curso.
+    review: Option<(usize, Game)>,
+
+    /// Bot de X y de O. None si juega una persona.
+    bots: [Option<WasmBot>; 2],
+
+    /// Lecturas de ficheros de bots en curso. Se cancelan al descartarlas.
+    readers: [Option<FileReader>; 2],
+
+    /// Jugada del bot programada. Se cancela al descartarla.
+    bot_timer: Option<Timeout>,
+
+    /// Último error al cargar o ejecutar un bot
+    bot_error: Option<String>,
+
+    /// Control de tiempo de las partidas locales
+    time_control: TimeControl,
+
+    /// Reloj de la partida en curso. None si se juega sin tiempo.
+    clock: Option<Clock>,
+
+    /// Momento, en milisegundos desde la época, en que empezó a correr el
+    /// reloj del jugador al que le toca. None si el reloj está parado.
+    turn_started: Option<f64>,
+
+    /// Refresco del reloj mientras corre. Se cancela al descartarlo.
+    ticker: Option<Interval>,
+
+    /// Partida por correspondencia en curso. Sin conexión con el servidor.
+    correspondence: Option<Correspondence>,
+
+    /// Último código de jugada que no se pudo aplicar
+    correspondence_error: Option<String>,
+
+    /// Partida directa con otro navegador en curso
+    peer: Option<PeerGame>,
+
+    /// Último error de la partida directa
+    peer_error: Option<String>,
+
+    /// Sala de la partida en red en la que se está
+    online: Option<Online>,
+
+    /// Último error del servidor en la sala
+    online_error: Option<String>,
+
+    /// Escucha de los mensajes del servidor. Se deja de escuchar al descartarla.
+    _server: Option<Listener>,
+
+    /// Casilla del cursor del teclado. None hasta que se usa.
+    cursor: Option<Move>,
+
+    /// Indica si se muestra la ayuda de los atajos de teclado
+    show_shortcuts: bool,
+
+    /// Atajos de teclado a los que se responde ahora, compartidos con su escucha
+    shortcuts: Rc<Cell<keyboard::Enabled>>,
+
+    /// Escucha de los atajos de teclado. Se deja de escuchar al descartarla.
+    _keyboard: Option<EventListener>,
+}
+
+
+/// Implementación del componente
+impl Component for SuperTresComponent {
+
+
+    type Message = SuperTresMsg;
+    type Properties = SuperTresProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+
+        let mut component = Self {
+            visible: ctx.props().visible,
+            game: Game::new(),
+            pending: None,
+            show_analysis: true,
+            analysis_stale: false,
+            analysis_timer: None,
+            analysis: None,
+            tt: TranspositionTable::new(TABLE_MEGABYTES),
+            solver: Solver::new(TABLEBASE_EMPTY, TABLEBASE_NODES, TranspositionTable::new(TABLE_MEGABYTES)),
+            solution: None,
+            game_analysis: None,
+            review: None,
+            bots: [None, None],
+            readers: [None, None],
+            bot_timer: None,
+            bot_error: None,
+            time_control: TimeControl::default(),
+            clock: None,
+            turn_started: None,
+            ticker: None,
+            correspondence: None,
+            correspondence_error: None,
+            peer: None,
+            peer_error: None,
+            online: None,
+            online_error: None,
+            _server: Self::listen(ctx),
+            cursor: None,
+            show_shortcuts: false,
+            shortcuts: Rc::default(),
+            _keyboard: None,
         };
-    
-        // Check rows
-        for i in 0..3 {
-            if let Some(r) = check_line(self.data[i]) {
-                return Some(r);
-            }
-        }
-    
-        // Check columns
-        for i in 0..3 {
-            if let Some(r) = check_line([self.data[0][i], self.data[1][i], self.data[2][i]]) {
-                return Some(r);
+        component._keyboard = keyboard::listen(component.shortcuts.clone(), ctx.link().callback(SuperTresMsg::Shortcut));
+
+        component.load_correspondence();
+        component.analyse();
+        component
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+
+        self.visible = ctx.props().visible;
+
+        // La sala era de la conexión anterior: su asiento sigue en la sesión guardada
+        if ctx.props().connection != old_props.connection {
+            self._server = Self::listen(ctx);
+            if self.online.is_some() {
+                self.leave_room(ctx);
             }
         }
-    
-        // Check diagonals
-        if let Some(r) = check_line([self.data[0][0], self.data[1][1], self.data[2][2]]) {
-            return Some(r);
-        }
-        if let Some(r) = check_line([self.data[0][2], self.data[1][1], self.data[2][0]]) {
-            return Some(r);
-        }
-    
-        None
-    }
 
-    fn playable(&self) -> bool {
-        self.data.iter().any(|x| x.iter().any(|y| y.is_none()))
+        true
     }
 
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
 
-}
+        match msg {
+            SuperTresMsg::Mark(position1, position2) => {
 
+                let mv = Move::new(position1, position2);
 
-/// Estructura que representa un supertablero de 3x3
-pub struct TableroSuperTres {
-    tablero: [[Result<Player, TableroTres>; 3]; 3],
-}
+                if !self.can_play() {
+                    // Revisando la partida, en el turno de un bot o en el del rival en red no se puede jugar
+                    false
+                } else if self.pending == Some(mv) {
+                    // Si ya está marcado en este turno, se desmarca
+                    // y se vuelve a jugar en el mismo tablero
+                    self.pending = None;
+                    true
+                } else if self.game.is_legal(mv) {
+                    // Si no está marcado, se marca.
+                    // Si ya se había marcado otra casilla, se sustituye
+                    // para que no puedas marcar varias casillas en un solo turno
+                    self.pending = Some(mv);
+                    self.cursor = Some(mv);
+                    true
+                } else {
+                    // Casilla ya jugada o juego terminado, no se hace nada
+                    false
+                }
 
-impl TableroSuperTres {
+            },
+            SuperTresMsg::Check => {
 
-    fn new() -> Self {
+                // Si no se jugó, no se hace nada
+                if let Some(mv) = self.pending.take() {
+                    match (self.room(), &self.peer) {
+                        // En red la jugada la valida y la reparte el servidor
+                        (Some(online), _) => online.connection.send(ClientMessage::Move(mv)),
+                        // En una partida directa la valida también el rival al recibirla
+                        (None, Some(peer)) => {
+                            peer.peer.send(ClientMessage::Move(mv));
+                            self.commit(ctx, mv);
+                        },
+                        (None, None) => self.commit(ctx, mv),
+                    }
+                }
 
-        let tablero = [
-            [Err(TableroTres::new()), Err(TableroTres::new()), Err(TableroTres::new())],
-            [Err(TableroTres::new()), Err(TableroTres::new()), Err(TableroTres::new())],
-            [Err(TableroTres::new()), Err(TableroTres::new()), Err(TableroTres::new())],
-        ];
+                true
+            },
+            SuperTresMsg::ToggleAnalysis => {
 
-        Self {
-            tablero,
-        }
-    }
+                self.show_analysis = !self.show_analysis;
+                self.analyse();
 
-    fn get(&self, pos1: Position) -> Result<&Player, &TableroTres> {
-        self.tablero[pos1.x as usize][pos1.y as usize].as_ref()
-    }
+                true
+            },
+            SuperTresMsg::Analyse => {
 
-    fn get_mut(&mut self, pos1: Position) -> Result<&mut Player, &mut TableroTres> {
-        self.tablero[pos1.x as usize][pos1.y as usize].as_mut()
-    }
+                // Solo se analizan partidas terminadas, una vez
+                if self.game.result().is_none() || self.game_analysis.is_some() {
+                    return false;
+                }
 
-    fn put(&mut self, pos1: Position, pos2: Position, value: Player) {
-        let tab = self.get_mut(pos1);
+                let moves: Vec<Move> = self.game.moves().collect();
+                self.game_analysis = Some(Reviewing::Running { review: Box::new(GameReview::new(&moves, REVIEW_LIMITS)), _step: Self::review_step(ctx) });
 
-        match tab {
-            Err(tab3x3) => {
-                // Si tengo un tablero, lo modifico
-                tab3x3.put(pos2, value);
+                true
             },
-            Ok(_) => {
-                // Si tengo un valor, el tablero ya se completó, no hago nada
+            SuperTresMsg::AnalyseStep => {
+
+                let mut review = match self.game_analysis.take() {
+                    Some(Reviewing::Running { review, .. }) => review,
+                    other => {
+                        self.game_analysis = other;
+                        return false;
+                    },
+                };
+
+                // Una jugada por paso, para no bloquear la página
+                self.game_analysis = Some(if review.step() {
+                    Reviewing::Running { review, _step: Self::review_step(ctx) }
+                } else {
+                    Reviewing::Done(review.finish())
+                });
+
+                true
             },
-        }
-        
-    }
+            SuperTresMsg::Review(ply) => {
+
+                let moves: Vec<Move> = self.game.moves().collect();
 
-    fn reset(&mut self, pos1: Position, pos2: Position) {
-        let tab = self.get_mut(pos1);
+                self.review = ply
+                    .filter(|ply| *ply < moves.len())
+                    .and_then(|ply| Game::from_moves(&moves[..ply]).map(|game| (ply, game)));
+                self.analyse();
 
-        match tab {
-            Err(tab3x3) => {
-                // Si tengo un tablero, lo modifico
-                tab3x3.reset(pos2);
+                true
             },
-            Ok(_) => {
-                // Si tengo un valor, el tablero ya se completó, no hago nada
+            SuperTresMsg::UploadBot(player, file) => {
+
+                let name = file.name();
+                let link = ctx.link().clone();
+
+                self.readers[player.index()] = Some(read_as_bytes(&file, move |bytes| {
+                    let bytes = bytes.map_err(|err| err.to_string());
+                    link.send_message(SuperTresMsg::BotLoaded(player, name, bytes));
+                }));
+
+                false
             },
-        }
-    }
+            SuperTresMsg::BotLoaded(player, name, bytes) => {
 
-    /// Chequea si el supertablero está completo.
-    /// Si el supertablero está completo, devuelve el valor.
-    /// 
-    /// Los subtableros se chequean y si están completos, se cambian por el valor
-    fn check(&mut self) -> Option<Player> {
+                self.readers[player.index()] = None;
 
-        // Primero chequeo cada subtablero por si está completo y sustituyo dicho subtablero del supertablero
-        // por el valor ganador
-        for i in 0..3 {
-            for j in 0..3 {
-                if let Err(tab) = &self.tablero[i][j] {
-                    if let Some(value) = tab.check() {
-                        self.tablero[i][j] = Ok(value);
-                    }
+                match bytes.and_then(|bytes| WasmBot::new(name.clone(), &bytes).map_err(|err| err.to_string())) {
+                    Ok(bot) => {
+                        self.bots[player.index()] = Some(bot);
+                        self.bot_error = None;
+
+                        // La jugada marcada por la persona ya no vale
+                        if self.game.turn() == Some(player) {
+                            self.pending = None;
+                        }
+                    },
+                    Err(err) => self.bot_error = Some(format!("Could not load {}: {}", name, err)),
                 }
-            }
-        }
 
-        // Después, chequeo si el supertablero está completo y devuelvo el valor ganador
+                self.schedule_bot(ctx);
+                true
+            },
+            SuperTresMsg::Human(player) => {
+
+                self.bots[player.index()] = None;
 
-        let check_line = |line: &[Result<Player, TableroTres>; 3]| -> Option<Player> {
-            if line.iter().all(|x| x.is_ok()) {
-                let first_value = line[0].as_ref().ok()?;
-                if line.iter().all(|x| x.as_ref().ok() == Some(first_value)) {
-                    // Si todos los elementos son iguales y no son None, devuelvo el valor, el tablero se completó
-                    return Some(*first_value);
+                if self.game.turn() == Some(player) {
+                    self.bot_timer = None;
                 }
-            }
-            None
-        };
 
-        // Check rows
-        for i in 0..3 {
-            if let Some(r) = check_line(&self.tablero[i]) {
-                return Some(r);
-            }
-        }
+                true
+            },
+            SuperTresMsg::SetTimeControl(time_control) => {
 
-        // Check columns
-        for i in 0..3 {
-            if let Some(r) = check_line(&[self.tablero[0][i].clone(), self.tablero[1][i].clone(), self.tablero[2][i].clone()]) {
-                return Some(r);
-            }
-        }
+                self.time_control = time_control;
 
-        // Check diagonals
-        if let Some(r) = check_line(&[self.tablero[0][0].clone(), self.tablero[1][1].clone(), self.tablero[2][2].clone()]) {
-            return Some(r);
-        }
-        if let Some(r) = check_line(&[self.tablero[0][2].clone(), self.tablero[1][1].clone(), self.tablero[2][0].clone()]) {
-            return Some(r);
-        }
+                // Los relojes vuelven a empezar; en red manda el de la sala
+                if self.room().is_none() {
+                    self.reset_clock();
+                    self.start_clock(ctx);
+                }
 
-        None
+                true
+            },
+            SuperTresMsg::Tick => {
 
-    }
+                // En red es el servidor el que decide cuándo se acaba el tiempo
+                if let (None, Some(player), Some(left)) = (self.room(), self.running_clock(), self.clock_left()) {
+                    if left[player.index()] == 0 {
+                        self.flag(player);
+                    }
+                }
 
-}
+                true
+            },
+            SuperTresMsg::NewCorrespondence => {
 
+                let mut rng = Rng::new(Date::now() as u64);
+                self.start_correspondence(Correspondence { id: new_game_id(&mut rng), seat: Player::First }, Game::new());
 
-//-------------------------------------------------------------------
-//-------------------------------------------------------------------
-// COMPONENT
+                true
+            },
+            SuperTresMsg::ApplyCode(text) => {
+
+                let moves: Vec<Move> = self.game.moves().collect();
+                let result = text.parse::<MoveCode>().and_then(|code| match &self.correspondence {
+                    Some(correspondence) => code.verify(&correspondence.id, &moves).map(|_| code),
+                    // Fuera de una partida solo vale el código de la primera jugada, que la empieza
+                    None => code.verify(&code.game, &[]).map(|_| code),
+                });
+
+                match result {
+                    // Las jugadas propias se hacen en el tablero
+                    Ok(_) if self.correspondence.as_ref().is_some_and(|correspondence| self.game.turn() == Some(correspondence.seat)) => {
+                        self.correspondence_error = Some("It is your move: play it on the board".to_string());
+                    },
+                    Ok(code) if self.correspondence.is_some() => {
+                        self.commit(ctx, code.mv);
+                        self.correspondence_error = None;
+                    },
+                    Ok(code) => {
+                        let mut game = Game::new();
+                        game.apply(code.mv);
+                        self.start_correspondence(Correspondence { id: code.game, seat: Player::Second }, game);
+                    },
+                    Err(err) => self.correspondence_error = Some(err.to_string()),
+                }
 
+                true
+            },
+            SuperTresMsg::AbandonCorrespondence => {
 
-/// Mensajes que puede recibir el componente
-pub enum SuperTresMsg {
-    Mark(Position, Position),
-    Check,
-}
+                Correspondence::clear();
+                self.correspondence = None;
+                self.correspondence_error = None;
+                self.reset_clock();
+                self.schedule_bot(ctx);
 
+                true
+            },
+            SuperTresMsg::HostPeer => {
 
-/// Información que guarda el componente
-pub struct SuperTresComponent {
+                match Peer::host(self.peer_callbacks(ctx)) {
+                    Ok(peer) => self.start_peer(peer, Player::First),
+                    Err(err) => self.peer_error = Some(format!("Could not start a direct game: {}", err)),
+                }
 
-    /// Supertablero de juego
-    tablero: TableroSuperTres,
+                true
+            },
+            SuperTresMsg::JoinPeer(code) => {
 
-    /// Jugador al que le toca jugar.
-    /// Si es None, el juego terminó.
-    turn: Option<Player>,
+                match Peer::join(&code, self.peer_callbacks(ctx)) {
+                    Ok(peer) => self.start_peer(peer, Player::Second),
+                    Err(err) => self.peer_error = Some(format!("Could not join the direct game: {}", err)),
+                }
+
+                true
+            },
+            SuperTresMsg::PeerAnswer(code) => {
 
-    /// Indica si se jugó en el último turno.
-    turn_played: bool,
+                if let Some(peer) = &self.peer {
+                    self.peer_error = peer.peer.answer(&code).err();
+                }
+
+                true
+            },
+            SuperTresMsg::PeerSignal(code) => {
 
-    /// Última casilla jugada
-    last_played: Option<Position>,
+                if let Some(peer) = &mut self.peer {
+                    peer.signal = Some(code);
+                }
 
-    /// Conjunto de posiciones jugadas.
-    /// Las posiciones jugadas no se pueden volver a jugar.
-    /// 
-    /// Son posiciones totales en el supertablero, NO se refieren a subtableros.
-    played_total_positions: HashSet<Position>,
+                true
+            },
+            SuperTresMsg::PeerOpen => {
+
+                let Some(peer) = &mut self.peer else {
+                    return false;
+                };
+
+                // Con el canal abierto empieza una partida nueva
+                peer.open = true;
+                self.peer_error = None;
+                self.game = Game::new();
+                self.pending = None;
+                self.review = None;
+                self.game_analysis = None;
+                self.reset_clock();
+                self.analyse();
 
-    /// Última posición total jugada.
-    last_total_played: Option<Position>,
+                true
+            },
+            SuperTresMsg::PeerMessage(message) => {
 
-    /// Subtablero activo.
-    /// Si es None, cualquier subtablero está activo.
-    active_table: Option<Position>,
+                let Some(peer) = self.peer.as_ref().filter(|peer| peer.open) else {
+                    return false;
+                };
 
-    /// Ganador
-    winner: Option<Player>,
-}
+                match message {
+                    // El rival también es un cliente: su jugada se comprueba antes de aplicarla
+                    ClientMessage::Move(mv) if self.game.turn() == Some(peer.seat.other()) && self.game.is_legal(mv) => {
+                        self.commit(ctx, mv);
+                    },
+                    ClientMessage::Move(mv) => {
+                        self.peer_error = Some(format!("Your opponent sent an invalid move {}", mv));
+                    },
+                    ClientMessage::Leave => self.close_peer(Some("Your opponent left the game".to_string())),
+                    _ => return false,
+                }
 
+                true
+            },
+            SuperTresMsg::PeerClosed(err) => {
 
-/// Implementación del componente
-impl Component for SuperTresComponent {
+                if self.peer.is_none() {
+                    return false;
+                }
+                self.close_peer(Some(err.unwrap_or_else(|| "The direct connection was closed".to_string())));
 
+                true
+            },
+            SuperTresMsg::LeavePeer => {
 
-    type Message = SuperTresMsg;
-    type Properties = ();
-
-    fn create(_ctx: &Context<Self>) -> Self {
-
-        
-        Self { 
-            tablero: TableroSuperTres::new(),
-            turn: Some(Player::First),
-            turn_played: false,
-            last_played: None,
-            played_total_positions: HashSet::new(),
-            last_total_played: None,
-            active_table: Some(Position { x: 1, y: 1 }),
-            winner: None,
-        }
-    }
+                if let Some(peer) = self.peer.as_ref().filter(|peer| peer.open) {
+                    peer.peer.send(ClientMessage::Leave);
+                }
+                self.close_peer(None);
+                self.schedule_bot(ctx);
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+                true
+            },
+            SuperTresMsg::LeaveRoom => {
 
-        match msg {
-            SuperTresMsg::Mark(position1, position2) => {
+                // Dejar la sala a propósito renuncia al asiento
+                Session::clear();
 
-                // Compruebo si el juego continúa
-                if let Err(tab) = self.tablero.get(position1) {
+                if let Some(online) = &self.online {
+                    online.connection.send(ClientMessage::Leave);
+                }
+                self.leave_room(ctx);
+                ctx.props().on_leave.emit(());
 
-                    // El juego continúa, marco la casilla
+                true
+            },
+            SuperTresMsg::Say(text) => {
 
-                    match self.turn {
+                if let Some(online) = self.room() {
+                    online.connection.send(ClientMessage::Say(text));
+                }
 
-                        // Turno del jugador 1
-                        Some(Player::First) => {
-                            
-                            // Compruebo la casilla que se quiere marcar
-                            match tab.get(position2) {
-                                None => {
+                false
+            },
+            SuperTresMsg::React(emoji) => {
 
-                                    marcar_casilla_libre(self, position1, position2, Player::First)
+                if let Some(online) = self.room() {
+                    online.connection.send(ClientMessage::React(emoji));
+                }
 
-                                },
-                                Some(Player::First) => {
+                false
+            },
+            SuperTresMsg::Offer(offer) => {
 
-                                    marcar_casilla_ocupada(self, position1, position2)
-                                    
-                                },
-                                Some(Player::Second) => {
-                                    // Si ya está marcado por el otro jugador, no se hace nada
-                                    false
-                                }
-                            }
+                if let Some(online) = self.room() {
+                    online.connection.send(ClientMessage::Offer(offer));
+                }
 
-                        },
-                        // Turno del jugador 2
-                        Some(Player::Second) => {
-                            
-                            // Compruebo la casilla que se quiere marcar
-                            match tab.get(position2) {
-                                None => {
+                false
+            },
+            SuperTresMsg::Answer(offer, accept) => {
 
-                                    marcar_casilla_libre(self, position1, position2, Player::Second)
+                if let Some(online) = self.room() {
+                    online.connection.send(if accept { ClientMessage::Accept(offer) } else { ClientMessage::Decline(offer) });
+                }
 
-                                },
-                                Some(Player::Second) => {
+                false
+            },
+            SuperTresMsg::Resign => {
 
-                                    marcar_casilla_ocupada(self, position1, position2)
+                if let Some(online) = self.room() {
+                    online.connection.send(ClientMessage::Resign);
+                }
 
-                                },
-                                Some(Player::First) => {
-                                    // Si ya está marcado por el otro jugador, no se hace nada
-                                    false
-                                }
-                            }
-                        },
-                        // Ningún jugador
-                        None => {
-                            // El juego ya terminó, no se hace nada
-                            false
-                        },
-                    }
+                false
+            },
+            SuperTresMsg::Server(message) => {
 
-                } else {
+                // Al entrar en una sala la partida pasa a ser la suya
+                match message {
+                    ServerMessage::Joined { room, seat, settings } => {
+                        self.enter_room(ctx, room, Some(seat), settings);
+                        return true;
+                    },
+                    ServerMessage::Watching { room, settings } => {
+                        self.enter_room(ctx, room, None, settings);
+                        return true;
+                    },
+                    _ => {},
+                }
 
-                    // El juego ya terminó, no se hace nada
+                // Los demás mensajes son de la sala; los del vestíbulo los atiende su vista
+                let Some(online) = &mut self.online else {
+                    return false;
+                };
 
-                    false
+                match message {
+                    ServerMessage::Session(token) => {
+                        // Se guarda en el navegador para volver al asiento si se recarga
+                        // la página o se corta la conexión
+                        Session { server: online.connection.server().to_string(), room: online.room.clone(), token }.save();
+                    },
+                    ServerMessage::Closed => self.leave_room(ctx),
+                    ServerMessage::State(moves) => match Game::from_moves(&moves) {
+                        Some(game) => {
+                            // Cada jugada retira la propuesta pendiente
+                            online.offer = None;
+                            self.game = game;
+                            self.pending = None;
+                            self.review = None;
+                            self.game_analysis = None;
+                            self.analyse();
+                        },
+                        None => self.online_error = Some("The server sent an illegal game".to_string()),
+                    },
+                    ServerMessage::Clock { remaining, running } => {
+                        self.clock = Clock::new(online.settings.time_control).map(|clock| Clock { remaining, ..clock });
+                        self.turn_started = running.map(|(_, elapsed)| Date::now() - elapsed as f64);
+                        self.ticker = running.map(|_| Self::ticker(ctx));
+                    },
+                    // Llega después de la partida, que no sabe que terminó
+                    ServerMessage::Ended(result) => {
+                        self.game.end(result);
+                        self.analyse();
+                    },
+                    ServerMessage::Offered { offer, from } => online.offer = Some((offer, from)),
+                    ServerMessage::Declined { .. } => online.offer = None,
+                    ServerMessage::Opponent(present) => {
+                        online.opponent = present;
+                        online.away = None;
+                    },
+                    ServerMessage::OpponentAway(seconds) => {
+                        online.opponent = false;
+                        online.away = Some(seconds);
+                    },
+                    ServerMessage::Chat { from, text } => online.push_chat(ChatLine::Message { from, text: text.into() }),
+                    ServerMessage::Reaction { from, emoji } => online.push_chat(ChatLine::Reaction { from, emoji: emoji.into() }),
+                    ServerMessage::Notice(text) => online.push_chat(ChatLine::Notice(text.into())),
+                    ServerMessage::Error(err) => self.online_error = Some(err),
+                    ServerMessage::Rooms(_)
+                    | ServerMessage::Ratings(_)
+                    | ServerMessage::Account { .. }
+                    | ServerMessage::Joined { .. }
+                    | ServerMessage::Watching { .. } => return false,
                 }
 
+                true
             },
-            SuperTresMsg::Check => {
+            SuperTresMsg::Shortcut(shortcut) => {
+
+                // Con la ayuda abierta solo se puede cerrar
+                match shortcut {
+                    // Oculta, la partida no responde al teclado
+                    _ if !self.visible => return false,
+                    Shortcut::Help => {
+                        self.show_shortcuts = !self.show_shortcuts;
+                        return true;
+                    },
+                    Shortcut::Close => {
+                        let shown = self.show_shortcuts;
+                        self.show_shortcuts = false;
+                        return shown;
+                    },
+                    _ if self.show_shortcuts => return false,
+                    // El cursor se mueve por el tablero aunque no se pueda jugar en él
+                    Shortcut::Cursor(..) if !self.shows_cursor() => return false,
+                    Shortcut::Mark | Shortcut::Next | Shortcut::Undo if !self.board_playable() => return false,
+                    _ => {},
+                }
 
-                let final_result = self.tablero.check();
-
-                match final_result {
-                    None => {
-                        
-                        // El juego continúa
-
-                        // Si no se jugó, no se hace nada
-                        if self.turn_played {
-                
-                            // El subtablero en el que quiero jugar es el que corresponde a la última casilla jugada
-                            let future_table = self.tablero.get(self.last_played.unwrap());
-                            
-                            if let Err(future_table) = future_table {
-                                // Si el tablero no está completo, el tablero activo es el subtablero correspondiente
-                                if future_table.playable() {
-                                    self.active_table = self.last_played;
-                                } else {
-                                    // Si el tablero no tiene casillas libres y no es completo,
-                                    // es un empate y el tablero activo es el super tablero completo
-                                    self.active_table = None;
-                                }
-                            } else {
-                                // Si el tablero está completo, el tablero activo es el supertablero completo
-                                self.active_table = None;
-                            }
+                match shortcut {
+                    Shortcut::Cursor(rows, columns) => {
+                        // Si el cursor no está en un subtablero en el que se pueda jugar, aparece en uno
+                        let cursor = match self.cursor.filter(|mv| keyboard::focusable(&self.game, mv.board)) {
+                            Some(cursor) => keyboard::step(&self.game, cursor, (rows, columns)).unwrap_or(cursor),
+                            None => match keyboard::start(&self.game) {
+                                Some(cursor) => cursor,
+                                None => return false,
+                            },
+                        };
+                        self.cursor = Some(cursor);
+                        true
+                    },
+                    Shortcut::Mark => {
+                        if let Some(mv) = self.cursor.filter(|mv| keyboard::focusable(&self.game, mv.board)) {
+                            ctx.link().send_message(SuperTresMsg::Mark(mv.board, mv.cell));
+                        }
+                        false
+                    },
+                    Shortcut::Next => {
+                        if self.pending.is_some() {
+                            ctx.link().send_message(SuperTresMsg::Check);
+                        }
+                        false
+                    },
+                    Shortcut::Undo => self.pending.take().is_some(),
+                    Shortcut::Help | Shortcut::Close => false,
+                }
+            },
+            SuperTresMsg::SearchPosition => {
 
-                            // Cambio de turno
-                            self.turn = switch(self.turn);
-                            self.turn_played = false;
+                let game = self.displayed().clone();
+                self.analysis = Some(search_observed(&game, ANALYSIS_LIMITS, &mut self.tt, &mut Deadline(Date::now() + ANALYSIS_MS)));
 
-                            // Se almacena la posición total jugada y se limpia la última posición total jugada
-                            self.played_total_positions.insert(self.last_total_played.unwrap());
-                            self.last_total_played = None;
+                // La resolución exacta, después de pintar la búsqueda
+                self.analysis_timer = Some(Self::analysis_step(ctx, SuperTresMsg::SolvePosition));
 
-                        }
+                true
+            },
+            SuperTresMsg::SolvePosition => {
 
-                    },
-                    Some(value) => {
+                self.analysis_timer = None;
+                let game = self.displayed().clone();
+                self.solution = self.solver.solve_observed(&game, &Deadline(Date::now() + ANALYSIS_MS));
 
-                        // El juego terminó
+                self.solution.is_some()
+            },
+            SuperTresMsg::ToggleShortcuts => {
 
-                        // Si el supertablero está completo, el tablero activo es inalcanzable.
-                        // El turno es None.
-                        // El ganador es el valor del supertablero.
+                self.show_shortcuts = !self.show_shortcuts;
 
-                        self.active_table = Some(UNREACHABLE);
-                        self.turn = None;
-                        self.winner = Some(value);
-                        self.turn_played = false;
+                true
+            },
+            SuperTresMsg::BotMove => {
+
+                self.bot_timer = None;
+
+                let Some(player) = self.game.turn() else {
+                    return false;
+                };
+                let Some(bot) = self.bots[player.index()].as_mut() else {
+                    return false;
+                };
+
+                match bot.try_choose(&self.game) {
+                    Ok(mv) => self.commit(ctx, mv),
+                    Err(err) => {
+                        // El lado del bot que falla pasa a jugarlo una persona
+                        self.bot_error = Some(format!("{} ({}): {}", bot.name(), player.symbol(), err));
+                        self.bots[player.index()] = None;
                     },
                 }
 
                 true
             },
-            
+
+        }
+    }
+
+    /// Tras cada cambio, la escucha del teclado sabe qué atajos hacen algo
+    /// y, si la posición mostrada cambió, se programa su análisis
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        self.shortcuts.set(self.enabled_shortcuts());
+
+        // Oculta, la partida se analiza cuando se vuelva a ver
+        if self.analysis_stale && self.visible {
+            self.analysis_stale = false;
+            self.analysis_timer = Some(Self::analysis_step(ctx, SuperTresMsg::SearchPosition));
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
 
-        let winner_class = match self.winner {
+        let result = self.result();
+        let winner = result.and_then(GameResult::winner);
+
+        let winner_class = match winner {
             Some(Player::First) => "bg-first-player text-white",
             Some(Player::Second) => "bg-second-player text-white",
             None => ""
         };
 
         html! {
-            <div class={classes!("section", "game-section", winner_class)}>
-                
+            <div class={classes!("section", "game-section", winner_class, (!self.visible).then_some("is-hidden"))}>
+
                 <div class={"container is-flex is-justify-content-center board-container"}>
 
+                    // Barra de evaluación
+                    {
+                        match (&self.analysis, self.show_analysis) {
+                            (Some(analysis), true) => html! {
+                                <EvalBarComponent score={self.analysis_score(analysis)} />
+                            },
+                            _ => html! {},
+                        }
+                    }
+
                     <div class={classes!(
-                        "box", 
+                        "box",
                         "game-board",
                         {
-                            if let Some(winner) = self.winner {
+                            if let Some(winner) = winner {
                                 match winner {
                                     Player::First => "back-fp",
                                     Player::Second => "back-sp",
@@ -480,203 +945,661 @@ impl Component for SuperTresComponent {
 
                         // Botón de siguiente turno
                         <div class="block gb-up">
-                            <button 
+                            <button
                                 class={classes!("button", "is-high", "is-fullwidth")}
                                 onclick={ctx.link().callback(move |_| SuperTresMsg::Check)}
-                                disabled={!self.turn_played}
+                                disabled={self.pending.is_none()}
                             >
                                 {"NEXT"}
                             </button>
                         </div>
 
+                        // Jugadores
+                        <div class="block gb-players">
+                            { self.render_players(ctx) }
+                        </div>
+
+                        // Partidas en red, directas y por correspondencia
+                        <div class="block gb-online">
+                            { self.render_online(ctx) }
+                            { self.render_peer(ctx) }
+                            { self.render_correspondence(ctx) }
+                            { self.render_chat(ctx) }
+                        </div>
+
                         // Tablero de juego
                         <div class="block gb-mid">
-                            { self.render_super_board(ctx) }
+                            <BoardComponent
+                                game={self.displayed().clone()}
+                                pending={self.pending.filter(|_| self.review.is_none())}
+                                cursor={self.cursor.filter(|mv| self.shows_cursor() && keyboard::focusable(&self.game, mv.board))}
+                                playable={self.can_play()}
+                                read_only={self.spectating()}
+                                on_mark={ctx.link().callback(|mv: Move| SuperTresMsg::Mark(mv.board, mv.cell))}
+                            />
+                        </div>
+
+                        // Análisis del motor
+                        <div class="block gb-analysis">
+                            { self.render_analysis(ctx) }
+                        </div>
+
+                        // Análisis de la partida terminada
+                        <div class="block gb-review">
+                            { self.render_review(ctx) }
                         </div>
 
-                        // Anuncio de ganador
+                        // Descarga de la posición y de la partida
+                        <div class="block gb-export">
+                            <ExportComponent
+                                moves={self.game.moves().collect::<Vec<Move>>()}
+                                ply={self.displayed().ply()}
+                            />
+                        </div>
+
+                        // Relojes y anuncio de ganador
                         <div class="block gb-bot">
+                            { self.render_clock() }
                             {
-                                if let Some(winner) = self.winner {
-                                    html! {
+                                match result {
+                                    Some(GameResult::Win(winner)) => html! {
                                         <div class="title is-4 has-text-centered">
                                             <div>
-                                                {format!("Player {} wins!", 
-                                                    match winner {
-                                                        Player::First => "X",
-                                                        Player::Second => "O"
-                                                    }
-                                                )}
+                                                {format!("Player {} wins!", winner.symbol())}
                                             </div>
                                         </div>
-                                    }
-                                } else {
-                                    html! {}
+                                    },
+                                    Some(GameResult::Resigned(player)) => html! {
+                                        <div class="title is-4 has-text-centered">
+                                            <div>
+                                                {format!("Player {} resigns, {} wins!", player.symbol(), player.other().symbol())}
+                                            </div>
+                                        </div>
+                                    },
+                                    Some(GameResult::OutOfTime(player)) => html! {
+                                        <div class="title is-4 has-text-centered">
+                                            <div>
+                                                {format!("Player {} ran out of time, {} wins!", player.symbol(), player.other().symbol())}
+                                            </div>
+                                        </div>
+                                    },
+                                    Some(GameResult::Draw) => html! {
+                                        <div class="title is-4 has-text-centered">
+                                            <div>{"Draw!"}</div>
+                                        </div>
+                                    },
+                                    Some(GameResult::AgreedDraw) => html! {
+                                        <div class="title is-4 has-text-centered">
+                                            <div>{"Draw agreed!"}</div>
+                                        </div>
+                                    },
+                                    None => html! {},
                                 }
                             }
                         </div>
-                        
+
                     </div>
                 </div>
+
+                // Ayuda de los atajos de teclado
+                {
+                    if self.show_shortcuts {
+                        html! { <ShortcutsComponent on_close={ctx.link().callback(|_| SuperTresMsg::ToggleShortcuts)} /> }
+                    } else {
+                        html! {}
+                    }
+                }
             </div>
         }
     }
 }
 
+impl Online {
+
+    /// Añade la línea al chat, olvidando las más antiguas
+    fn push_chat(&mut self, line: ChatLine) {
+        if self.chat.len() == CHAT_LINES {
+            self.chat.remove(0);
+        }
+        self.chat.push(line);
+    }
+}
+
+
 impl SuperTresComponent {
 
-    fn render_super_board(&self, ctx: &Context<Self>) -> Html {
-        html! {
-            <div class="container is-flex is-justify-content-center">
-            <div class="super-grid columns is-centered">
-                <div class="column is-narrow">
-                    { for (0..3).map(|i| self.render_super_row(ctx, i)) }
-                </div>
-            </div>
-        </div>
+    /// Posición que se muestra en el tablero: la revisada o la partida en curso
+    fn displayed(&self) -> &Game {
+        match &self.review {
+            Some((_, game)) => game,
+            None => &self.game,
         }
     }
 
 
-    fn render_super_row(&self, ctx: &Context<Self>, i: u8) -> Html {
-        html! {
-            <div class="columns super-row" key={i}>
-                { for (0..3).map(|j| self.render_super_cell(ctx, i, j)) }
-            </div>
+    /// Sala de la partida en red, si se está en una
+    fn room(&self) -> Option<&Online> {
+        self.online.as_ref()
+    }
+
+
+    /// Escucha los mensajes del servidor de la conexión, si la hay
+    fn listen(ctx: &Context<Self>) -> Option<Listener> {
+        let connection = ctx.props().connection.as_ref()?;
+        Some(connection.listen(ctx.link().callback(SuperTresMsg::Server)))
+    }
+
+
+    /// Reglas de la partida en curso: las de la sala en red o las normales
+    fn ruleset(&self) -> Ruleset {
+        self.room().map_or(Ruleset::Standard, |online| online.settings.ruleset)
+    }
+
+
+    /// Resultado de la partida en curso con sus reglas
+    fn result(&self) -> Option<GameResult> {
+        self.ruleset().result(&self.game)
+    }
+
+
+    /// Indica si le toca jugar a un bot en la partida en curso.
+    /// En las partidas en red, directas y por correspondencia no juegan los bots.
+    fn bot_to_move(&self) -> bool {
+        self.room().is_none() && self.correspondence.is_none() && self.peer.is_none()
+            && self.game.turn().is_some_and(|turn| self.bots[turn.index()].is_some())
+    }
+
+
+    /// Indica si se está mirando una partida en red sin jugar en ella.
+    /// Los espectadores ven el tablero en modo de solo lectura.
+    fn spectating(&self) -> bool {
+        self.room().is_some_and(|online| online.seat.is_none())
+    }
+
+
+    /// Indica si la persona que usa la aplicación puede jugar en la posición mostrada
+    fn can_play(&self) -> bool {
+        if self.review.is_some() {
+            return false;
+        }
+
+        match self.room() {
+            Some(online) => online.opponent && self.game.turn().is_some() && self.game.turn() == online.seat,
+            None => match (&self.peer, &self.correspondence) {
+                (Some(peer), _) => peer.open && self.game.turn() == Some(peer.seat),
+                (None, Some(correspondence)) => self.game.turn() == Some(correspondence.seat),
+                (None, None) => !self.bot_to_move(),
+            },
         }
     }
 
 
-    fn render_super_cell(&self, ctx: &Context<Self>, i: u8, j: u8) -> Html {
-        let position = Position { x: i, y: j };
-        let is_active = !disabled_table(self.active_table, position);
-        
-        let cell_classes = classes!(
-            "super-cell",
-            "column",
-            "is-narrow",
-            if is_active { "active-board" } else { "inactive-board" }
-        );
+    /// Indica si se ve el tablero: no mientras se ve otra vista
+    fn board_visible(&self) -> bool {
+        self.visible
+    }
 
-        html! {
-            <div class={cell_classes} key={format!("{}-{}", i, j)}>
-                {
-                    match self.tablero.get(position) {
-                        Ok(Player::First) => html! {
-                            <div class="won-cell first-player is-flex is-justify-content-center is-align-items-center">{"X"}</div>
-                        },
-                        Ok(Player::Second) => html! {
-                            <div class="won-cell second-player is-flex is-justify-content-center is-align-items-center">{"O"}</div>
-                        },
-                        Err(tab) => self.render_sub_board(ctx, tab, position),
-                    }
-                }
-            </div>
+
+    /// Indica si se ve el tablero y se puede jugar en él
+    fn board_playable(&self) -> bool {
+        self.can_play() && self.board_visible()
+    }
+
+
+    /// Indica si se ve el cursor del teclado: en el tablero de la partida en
+    /// curso, se pueda jugar o no. Revisando la partida no se muestra.
+    fn shows_cursor(&self) -> bool {
+        self.board_visible() && self.review.is_none()
+    }
+
+
+    /// Atajos de teclado que hacen algo en este momento
+    fn enabled_shortcuts(&self) -> keyboard::Enabled {
+        let playable = self.board_playable();
+        keyboard::Enabled {
+            cursor: self.shows_cursor() && keyboard::start(&self.game).is_some(),
+            mark: playable && self.cursor.is_some_and(|mv| keyboard::focusable(&self.game, mv.board)),
+            marked: playable && self.pending.is_some(),
+            help: self.show_shortcuts,
+        }
+    }
+
+
+    /// Juega la jugada en la partida local y pasa el reloj al rival.
+    /// Si al jugador ya se le había acabado el tiempo, pierde la partida.
+    fn commit(&mut self, ctx: &Context<Self>, mv: Move) {
+        let (elapsed, running) = (self.turn_elapsed(), self.running_clock());
+        if let (Some(clock), Some(player)) = (self.clock.as_mut(), running) {
+            if !clock.stop(player, elapsed, true) {
+                self.flag(player);
+                return;
+            }
+        }
+
+        self.game.apply(mv);
+        if let Some(correspondence) = &self.correspondence {
+            correspondence.save(&self.game.moves().collect::<Vec<Move>>());
+        }
+        self.start_clock(ctx);
+        self.analyse();
+        self.schedule_bot(ctx);
+    }
+
+
+    /// Empieza la partida por correspondencia en esa posición y la guarda
+    fn start_correspondence(&mut self, correspondence: Correspondence, game: Game) {
+        correspondence.save(&game.moves().collect::<Vec<Move>>());
+        self.correspondence = Some(correspondence);
+        self.correspondence_error = None;
+        self.game = game;
+        self.pending = None;
+        self.review = None;
+        self.game_analysis = None;
+        self.bot_timer = None;
+        self.reset_clock();
+        self.analyse();
+    }
+
+
+    /// Vuelve a la partida por correspondencia guardada, si hay una
+    fn load_correspondence(&mut self) {
+        let Some((correspondence, moves)) = Correspondence::load() else {
+            return;
+        };
+        match Game::from_moves(&moves) {
+            Some(game) => {
+                self.correspondence = Some(correspondence);
+                self.game = game;
+                self.pending = None;
+                self.review = None;
+                self.game_analysis = None;
+                self.analyse();
+            },
+            None => Correspondence::clear(),
+        }
+    }
+
+
+    fn peer_callbacks(&self, ctx: &Context<Self>) -> PeerCallbacks {
+        PeerCallbacks {
+            on_signal: ctx.link().callback(SuperTresMsg::PeerSignal),
+            on_open: ctx.link().callback(|_| SuperTresMsg::PeerOpen),
+            on_message: ctx.link().callback(SuperTresMsg::PeerMessage),
+            on_close: ctx.link().callback(SuperTresMsg::PeerClosed),
+        }
+    }
+
+
+    /// Empieza a conectar la partida directa. La partida por correspondencia
+    /// sigue guardada para cuando termine.
+    fn start_peer(&mut self, peer: Peer, seat: Player) {
+        self.peer = Some(PeerGame { peer, seat, signal: None, open: false });
+        self.peer_error = None;
+        self.correspondence = None;
+        self.bot_timer = None;
+        self.reset_clock();
+    }
+
+
+    /// Termina la partida directa, con el motivo si no se dejó a propósito.
+    /// Al soltar la conexión se cierra.
+    fn close_peer(&mut self, reason: Option<String>) {
+        self.peer = None;
+        self.peer_error = reason;
+        self.load_correspondence();
+        self.reset_clock();
+    }
+
+
+    /// Entra en la sala de la partida en red, sentado o mirando. Se deja la
+    /// partida directa; la partida por correspondencia sigue guardada.
+    fn enter_room(&mut self, ctx: &Context<Self>, room: String, seat: Option<Player>, settings: RoomSettings) {
+        let Some(connection) = ctx.props().connection.clone() else {
+            return;
+        };
+
+        if let Some(peer) = self.peer.take().filter(|peer| peer.open) {
+            peer.peer.send(ClientMessage::Leave);
+        }
+        self.peer_error = None;
+        self.correspondence = None;
+        self.online = Some(Online { connection, room, seat, settings, opponent: false, away: None, offer: None, chat: Vec::new() });
+        self.online_error = None;
+        self.bot_timer = None;
+        self.reset_clock();
+    }
+
+
+    /// Sale de la sala de la partida en red y vuelve a un tablero vacío,
+    /// o a la partida por correspondencia guardada
+    fn leave_room(&mut self, ctx: &Context<Self>) {
+        self.online = None;
+        self.online_error = None;
+        self.game = Game::new();
+        self.pending = None;
+        self.review = None;
+        self.game_analysis = None;
+        self.load_correspondence();
+        self.reset_clock();
+        self.analyse();
+        self.schedule_bot(ctx);
+    }
+
+
+    /// Código de la última jugada si la hizo quien usa la aplicación, para mandarlo al rival
+    fn correspondence_code(&self) -> Option<MoveCode> {
+        let correspondence = self.correspondence.as_ref()?;
+        let moves: Vec<Move> = self.game.moves().collect();
+        let mover = if moves.len().is_multiple_of(2) { Player::Second } else { Player::First };
+        MoveCode::new(&correspondence.id, &moves).filter(|_| mover == correspondence.seat)
+    }
+
+
+    /// Vuelve a poner los relojes con el control de tiempo de la sala en red o
+    /// con el elegido para la partida local. Quedan parados.
+    fn reset_clock(&mut self) {
+        let time_control = match self.room() {
+            Some(online) => online.settings.time_control,
+            None if self.correspondence.is_some() || self.peer.is_some() => TimeControl::Unlimited,
+            None => self.time_control,
+        };
+        self.clock = Clock::new(time_control);
+        self.turn_started = None;
+        self.ticker = None;
+    }
+
+
+    /// Pone en marcha el reloj del jugador al que le toca en la partida local.
+    /// Los relojes corren desde la primera jugada, y el de cada jugador desde
+    /// que el rival confirma la suya con NEXT.
+    fn start_clock(&mut self, ctx: &Context<Self>) {
+        let runs = self.clock.is_some() && self.game.turn().is_some() && self.game.ply() > 0;
+        self.turn_started = runs.then(Date::now);
+        self.ticker = runs.then(|| Self::ticker(ctx));
+    }
+
+
+    /// Programa el paso siguiente del análisis de la partida terminada
+    fn review_step(ctx: &Context<Self>) -> Timeout {
+        let link = ctx.link().clone();
+        Timeout::new(0, move || link.send_message(SuperTresMsg::AnalyseStep))
+    }
+
+
+    fn ticker(ctx: &Context<Self>) -> Interval {
+        let link = ctx.link().clone();
+        Interval::new(CLOCK_TICK_MS, move || link.send_message(SuperTresMsg::Tick))
+    }
+
+
+    /// Jugador cuyo reloj corre
+    fn running_clock(&self) -> Option<Player> {
+        self.game.turn().filter(|_| self.turn_started.is_some())
+    }
+
+
+    /// Milisegundos que lleva corriendo el reloj
+    fn turn_elapsed(&self) -> u64 {
+        self.turn_started.map_or(0, |started| (Date::now() - started).max(0.0) as u64)
+    }
+
+
+    /// Milisegundos que le quedan a X y a O en este momento. En red el reloj
+    /// lo lleva el servidor y aquí solo se descuenta para mostrarlo.
+    fn clock_left(&self) -> Option<[u64; 2]> {
+        let clock = self.clock?;
+        let running = self.running_clock();
+        Some([Player::First, Player::Second].map(|player| match running {
+            Some(running) if running == player => clock.left(player, self.turn_elapsed()),
+            _ => clock.remaining[player.index()],
+        }))
+    }
+
+
+    /// Termina la partida local porque al jugador se le acabó el tiempo
+    fn flag(&mut self, player: Player) {
+        if let Some(clock) = self.clock.as_mut() {
+            clock.remaining[player.index()] = 0;
+        }
+        self.game.end(GameResult::OutOfTime(player));
+        self.pending = None;
+        self.turn_started = None;
+        self.ticker = None;
+        self.bot_timer = None;
+        self.analyse();
+    }
+
+
+    /// Programa la jugada del bot si le toca a uno y no se está revisando la partida
+    fn schedule_bot(&mut self, ctx: &Context<Self>) {
+        if self.bot_to_move() && self.review.is_none() && self.bot_timer.is_none() {
+            let link = ctx.link().clone();
+            self.bot_timer = Some(Timeout::new(BOT_DELAY_MS, move || link.send_message(SuperTresMsg::BotMove)));
+        }
+    }
+
+
+    /// Descarta el análisis de la posición anterior. Si el análisis está
+    /// activo, el de la posición mostrada se hace después de pintarla.
+    fn analyse(&mut self) {
+        self.analysis = None;
+        self.solution = None;
+        self.analysis_timer = None;
+        self.analysis_stale = self.show_analysis;
+    }
+
+
+    /// Programa el paso siguiente del análisis de la posición mostrada
+    fn analysis_step(ctx: &Context<Self>, msg: SuperTresMsg) -> Timeout {
+        let link = ctx.link().clone();
+        Timeout::new(0, move || link.send_message(msg))
+    }
+
+
+    /// Puntuación de la posición mostrada: la exacta si está resuelta, si no la de la búsqueda
+    fn analysis_score(&self, analysis: &SearchResult) -> i32 {
+        match (self.solution, self.displayed().turn()) {
+            (Some(solution), Some(turn)) => solution.value.score(turn),
+            _ => analysis.score,
         }
     }
 
 
-    fn render_sub_board(&self, ctx: &Context<Self>, tab: &TableroTres, position: Position) -> Html {
+    fn render_players(&self, ctx: &Context<Self>) -> Html {
+
+        // En red, en las partidas directas y por correspondencia cada lado lo juega una persona
+        if self.online.is_some() || self.correspondence.is_some() || self.peer.is_some() {
+            return html! {};
+        }
+
         html! {
-            <div class="sub-grid">
-                { for (0..3).map(|k| self.render_sub_row(ctx, tab, position, k)) }
-            </div>
+            <PlayersComponent
+                bots={self.bots.each_ref().map(|bot| bot.as_ref().map(|bot| AttrValue::from(bot.name())))}
+                error={self.bot_error.clone().map(AttrValue::from)}
+                on_upload={ctx.link().callback(|(player, file)| SuperTresMsg::UploadBot(player, file))}
+                on_human={ctx.link().callback(SuperTresMsg::Human)}
+                time_control={self.time_control}
+                on_time_control={ctx.link().callback(SuperTresMsg::SetTimeControl)}
+            />
         }
     }
 
 
-    fn render_sub_row(&self, ctx: &Context<Self>, tab: &TableroTres, pos1: Position, k: u8) -> Html {
+    /// Panel de las partidas por correspondencia, si no se está conectado al servidor
+    /// ni en una partida directa
+    fn render_correspondence(&self, ctx: &Context<Self>) -> Html {
+
+        if self.online.is_some() || self.peer.is_some() {
+            return html! {};
+        }
+
         html! {
-            <div class="columns is-gapless sub-row" key={k}>
-                { for (0..3).map(|l| self.render_sub_cell(ctx, tab, pos1, k, l)) }
-            </div>
+            <CorrespondenceComponent
+                game={self.correspondence.as_ref().map(|correspondence| AttrValue::from(correspondence.id.clone()))}
+                seat={self.correspondence.as_ref().map(|correspondence| correspondence.seat)}
+                turn={self.game.turn()}
+                code={self.correspondence_code().map(|code| AttrValue::from(code.to_string()))}
+                error={self.correspondence_error.clone().map(AttrValue::from)}
+                on_new={ctx.link().callback(|_| SuperTresMsg::NewCorrespondence)}
+                on_apply={ctx.link().callback(SuperTresMsg::ApplyCode)}
+                on_abandon={ctx.link().callback(|_| SuperTresMsg::AbandonCorrespondence)}
+            />
         }
     }
 
 
-    fn render_sub_cell(&self, ctx: &Context<Self>, tab: &TableroTres, pos1: Position, k: u8, l: u8) -> Html {
-        let pos2 = Position { x: k, y: l };
-        let is_disabled = disabled_table(self.active_table, pos1);
-        let total_pos = Position::total_pos(pos1, pos2);
-        let is_played = self.played_total_positions.contains(&total_pos);
+    /// Panel de las partidas directas, si no se está conectado al servidor
+    /// ni jugando por correspondencia
+    fn render_peer(&self, ctx: &Context<Self>) -> Html {
 
-        let cell_classes = classes!(
-            "button",
-            match tab.get(pos2) {
-                Some(Player::First) => "btn-fp",
-                Some(Player::Second) => "btn-sp",
-                None => "btn-none"
-            },
-            if is_played { "played" } else { "unplayed" }
-        );
+        if self.online.is_some() || self.correspondence.is_some() {
+            return html! {};
+        }
 
         html! {
-            <div class="column is-narrow sub-cell">
-                <button
-                    class={cell_classes}
-                    disabled={is_disabled}
-                    onclick={ctx.link().callback(move |_| SuperTresMsg::Mark(pos1, pos2))}
-                >
-                    {
-                        match tab.get(pos2) {
-                            Some(Player::First) => "X",
-                            Some(Player::Second) => "O",
-                            None => " "
-                        }
-                    }
-                </button>
-            </div>
+            <PeerComponent
+                seat={self.peer.as_ref().map(|peer| peer.seat)}
+                signal={self.peer.as_ref().and_then(|peer| peer.signal.clone()).map(AttrValue::from)}
+                open={self.peer.as_ref().is_some_and(|peer| peer.open)}
+                turn={self.game.turn()}
+                error={self.peer_error.clone().map(AttrValue::from)}
+                on_host={ctx.link().callback(|_| SuperTresMsg::HostPeer)}
+                on_join={ctx.link().callback(SuperTresMsg::JoinPeer)}
+                on_answer={ctx.link().callback(SuperTresMsg::PeerAnswer)}
+                on_leave={ctx.link().callback(|_| SuperTresMsg::LeavePeer)}
+            />
         }
     }
 
-}
+
+    /// Relojes de la partida, si se juega con control de tiempo
+    fn render_clock(&self) -> Html {
+        match self.clock_left() {
+            Some(left) => html! { <ClockComponent left={left} running={self.running_clock()} /> },
+            None => html! {},
+        }
+    }
 
 
-fn disabled_table(active_table: Option<Position>, actual_table: Position) -> bool {
-    (active_table.is_some())&&(active_table.unwrap() != actual_table)
-}
+    /// Panel de la sala de la partida en red, si se está en una
+    fn render_online(&self, ctx: &Context<Self>) -> Html {
 
+        let Some(online) = self.room() else {
+            return html! {};
+        };
 
-fn marcar_casilla_libre(super_tres: &mut SuperTresComponent, pos1: Position, pos2: Position, player: Player) -> bool {
-    if super_tres.turn_played {
-        // Si ya se jugó y no se hizo check, se revierte la jugada anterior
-        // para que no puedas marcar varias casillas en un solo turno
-        let (last_table, _) = Position::partial_pos(super_tres.last_total_played.unwrap());
-        super_tres.tablero.reset(last_table, super_tres.last_played.unwrap());
-        //self.last_total_played = None;
+        html! {
+            <OnlineComponent
+                room={AttrValue::from(online.room.clone())}
+                seat={online.seat}
+                settings={online.settings}
+                turn={self.game.turn()}
+                opponent={online.opponent}
+                away={online.away}
+                offer={online.offer}
+                error={self.online_error.clone().map(AttrValue::from)}
+                on_leave={ctx.link().callback(|_| SuperTresMsg::LeaveRoom)}
+                on_offer={ctx.link().callback(SuperTresMsg::Offer)}
+                on_answer={ctx.link().callback(|(offer, accept)| SuperTresMsg::Answer(offer, accept))}
+                on_resign={ctx.link().callback(|_| SuperTresMsg::Resign)}
+            />
+        }
     }
 
-    // Si no está marcado, se marca
-    // El siguiente tablero activo es el subtablero correspondiente a la casilla marcada
-    // Se jugó el turno
 
-    super_tres.tablero.put(pos1, pos2, player);
-    super_tres.last_played = Some(pos2);
-    // Se guarda la posición total jugada
-    super_tres.last_total_played = Some(Position::total_pos(pos1, pos2));
-    super_tres.turn_played = true;
+    fn render_chat(&self, ctx: &Context<Self>) -> Html {
 
-    true
-}
+        let Some(online) = self.room() else {
+            return html! {};
+        };
 
+        html! {
+            <ChatComponent
+                lines={online.chat.clone()}
+                seat={online.seat}
+                on_say={ctx.link().callback(SuperTresMsg::Say)}
+                on_react={ctx.link().callback(SuperTresMsg::React)}
+            />
+        }
+    }
 
-fn marcar_casilla_ocupada(super_tres: &mut SuperTresComponent, pos1: Position, pos2: Position) -> bool {
-    // Compruebo si la casilla ya fue jugada por el mismo jugador anteriormente
-    if !super_tres.played_total_positions.contains(&Position::total_pos(pos1, pos2)) {
-                                        
-        // Si ya está marcado, se resetea
-        // Si se resetea, se vuelve a jugar en el mismo tablero
-        // No se jugó el turno
 
-        super_tres.tablero.reset(pos1, pos2);
-        super_tres.last_played = Some(pos1);
-        super_tres.turn_played = false;
-        // No hay posición total jugada
-        super_tres.last_total_played = None;
+    fn render_review(&self, ctx: &Context<Self>) -> Html {
 
-        true
-    } else {
-        // Si ya está marcado anteriormente por el mismo jugador, no se hace nada
-        false
+        if self.game.result().is_none() {
+            return html! {};
+        }
+
+        match &self.game_analysis {
+            Some(Reviewing::Done(analysis)) => html! {
+                <GameReviewComponent
+                    analysis={analysis.clone()}
+                    viewed={self.review.as_ref().map(|(ply, _)| *ply)}
+                    on_review={ctx.link().callback(SuperTresMsg::Review)}
+                />
+            },
+            Some(Reviewing::Running { review, .. }) => {
+                let (done, total) = review.progress();
+                html! {
+                    <div class="review-progress">
+                        <progress class="progress is-small" value={done.to_string()} max={total.to_string()} />
+                        <p class="has-text-centered">{format!("Analysing move {} of {}", (done + 1).min(total), total)}</p>
+                    </div>
+                }
+            },
+            None => html! {
+                <button
+                    class={classes!("button", "is-high", "is-fullwidth")}
+                    onclick={ctx.link().callback(|_| SuperTresMsg::Analyse)}
+                >
+                    {"Analyse"}
+                </button>
+            },
+        }
+    }
+
+
+    fn render_analysis(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="is-flex is-align-items-center analysis">
+                <button
+                    class={classes!("button", "is-small", "analysis-toggle")}
+                    onclick={ctx.link().callback(|_| SuperTresMsg::ToggleAnalysis)}
+                >
+                    { if self.show_analysis { "Hide analysis" } else { "Show analysis" } }
+                </button>
+                <button
+                    class={classes!("button", "is-small", "is-text", "shortcuts-toggle")}
+                    onclick={ctx.link().callback(|_| SuperTresMsg::ToggleShortcuts)}
+                    title="Keyboard shortcuts (?)"
+                >
+                    {"Shortcuts"}
+                </button>
+                {
+                    match (&self.analysis, self.displayed().turn()) {
+                        (Some(analysis), Some(turn)) => html! {
+                            <>
+                                <PrincipalVariationComponent
+                                    score={analysis.score}
+                                    depth={analysis.depth}
+                                    pv={analysis.pv.clone()}
+                                    {turn}
+                                />
+                                {
+                                    match self.solution {
+                                        Some(solution) => html! {
+                                            <TablebaseComponent value={solution.value} best={solution.best} {turn} />
+                                        },
+                                        None => html! {},
+                                    }
+                                }
+                            </>
+                        },
+                        _ => html! {},
+                    }
+                }
+            </div>
+        }
     }
 }
+
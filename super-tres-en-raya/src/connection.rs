@@ -0,0 +1,167 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Conexión WebSocket con el servidor de partidas en red.
+// Una tarea del navegador reparte los mensajes del servidor y manda los de
+// la aplicación. La conexión se comparte entre las vistas: cada una recibe
+// los mensajes mientras conserva su escucha, y al descartar la última copia
+// de la conexión se cierra el socket.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::future::{select, Either};
+use futures::{SinkExt, StreamExt};
+use gloo_net::websocket::futures::WebSocket;
+use gloo_net::websocket::Message;
+use yew::platform::spawn_local;
+use yew::Callback;
+use super_tres_engine::net::{ClientMessage, ServerMessage};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONNECTION
+
+
+/// Conexión abierta con el servidor. Las copias comparten el socket.
+#[derive(Clone)]
+pub struct Connection {
+
+    /// Dirección del servidor
+    server: Rc<str>,
+    sender: UnboundedSender<ClientMessage>,
+    listeners: Rc<RefCell<Listeners>>,
+}
+
+
+/// Callbacks que reciben los mensajes del servidor
+#[derive(Default)]
+struct Listeners {
+    next: u32,
+    callbacks: Vec<(u32, Callback<ServerMessage>)>,
+}
+
+
+/// Escucha de los mensajes del servidor. Se deja de escuchar al descartarla.
+pub struct Listener {
+    id: u32,
+    listeners: Weak<RefCell<Listeners>>,
+}
+
+
+/// Lo que despierta a la tarea de la conexión
+enum Event {
+    Received(Option<Result<Message, gloo_net::websocket::WebSocketError>>),
+    Send(Option<ClientMessage>),
+}
+
+
+impl Connection {
+
+    /// Abre la conexión. Los mensajes del servidor llegan a quienes escuchan y,
+    /// cuando la conexión se cierra por cualquier motivo, se llama a `on_close`.
+    pub fn open(url: &str, on_close: Callback<Option<String>>) -> Result<Self, String> {
+        let mut socket = WebSocket::open(url).map_err(|err| err.to_string())?;
+        let (sender, mut receiver) = mpsc::unbounded::<ClientMessage>();
+        let listeners = Rc::new(RefCell::new(Listeners::default()));
+
+        let dispatch = {
+            let listeners = Rc::downgrade(&listeners);
+            move |message: ServerMessage| {
+                // Se copian antes de avisar, por si alguno deja de escuchar al recibirlo
+                let callbacks: Vec<_> = match listeners.upgrade() {
+                    Some(listeners) => listeners.borrow().callbacks.iter().map(|(_, callback)| callback.clone()).collect(),
+                    None => return,
+                };
+                for callback in callbacks {
+                    callback.emit(message.clone());
+                }
+            }
+        };
+
+        spawn_local(async move {
+            let error = loop {
+                let event = match select(socket.next(), receiver.next()).await {
+                    Either::Left((message, _)) => Event::Received(message),
+                    Either::Right((message, _)) => Event::Send(message),
+                };
+
+                match event {
+                    Event::Received(Some(Ok(Message::Text(text)))) => {
+                        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                            match line.parse() {
+                                Ok(message) => dispatch(message),
+                                // Un mensaje que no se entiende se muestra como error
+                                Err(err) => dispatch(ServerMessage::Error(err.to_string())),
+                            }
+                        }
+                    },
+                    Event::Received(Some(Ok(Message::Bytes(_)))) => {},
+                    Event::Received(Some(Err(err))) => break Some(err.to_string()),
+                    Event::Received(None) => break None,
+                    Event::Send(Some(message)) => {
+                        if let Err(err) = socket.send(Message::Text(message.to_string())).await {
+                            break Some(err.to_string());
+                        }
+                    },
+                    // Se descartaron todas las copias: al soltar el socket se cierra
+                    Event::Send(None) => return,
+                }
+            };
+
+            on_close.emit(error);
+        });
+
+        Ok(Self { server: url.into(), sender, listeners })
+    }
+
+
+    /// Dirección del servidor
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+
+    pub fn send(&self, message: ClientMessage) {
+        let _ = self.sender.unbounded_send(message);
+    }
+
+
+    /// Manda a `on_message` los mensajes del servidor mientras se conserve la escucha
+    pub fn listen(&self, on_message: Callback<ServerMessage>) -> Listener {
+        let mut listeners = self.listeners.borrow_mut();
+        let id = listeners.next;
+        listeners.next += 1;
+        listeners.callbacks.push((id, on_message));
+        Listener { id, listeners: Rc::downgrade(&self.listeners) }
+    }
+}
+
+
+/// Dos copias son iguales si son de la misma conexión
+impl PartialEq for Connection {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.listeners, &other.listeners)
+    }
+}
+
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Some(listeners) = self.listeners.upgrade() {
+            listeners.borrow_mut().callbacks.retain(|(id, _)| *id != self.id);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Partida por correspondencia guardada en el navegador.
+// Entre jugada y jugada pueden pasar días, así que la partida se guarda
+// con cada cambio y se recupera al abrir la aplicación.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use super_tres_engine::board::Player;
+use super_tres_engine::game::Move;
+use crate::session::storage;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CORRESPONDENCE
+
+
+/// Clave del almacenamiento local en la que se guarda la partida
+const STORAGE_KEY: &str = "super-tres-correspondence";
+
+
+/// Partida por correspondencia en curso
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Correspondence {
+
+    /// Identificador de la partida, que va en cada código de jugada
+    pub id: String,
+
+    /// Fichas con las que se juega
+    pub seat: Player,
+}
+
+
+impl Correspondence {
+
+    /// Partida guardada con sus jugadas, si hay una
+    pub fn load() -> Option<(Self, Vec<Move>)> {
+        let value = storage()?.get_item(STORAGE_KEY).ok()??;
+        let mut fields = value.split_whitespace();
+        let id = fields.next()?.to_string();
+        let seat = match fields.next()? {
+            "X" => Player::First,
+            "O" => Player::Second,
+            _ => return None,
+        };
+        let moves = fields.map(str::parse).collect::<Result<Vec<Move>, _>>().ok()?;
+        Some((Self { id, seat }, moves))
+    }
+
+
+    /// Guarda la partida con sus jugadas
+    pub fn save(&self, moves: &[Move]) {
+        if let Some(storage) = storage() {
+            let mut value = format!("{} {}", self.id, self.seat.symbol());
+            for mv in moves {
+                value.push_str(&format!(" {}", mv));
+            }
+            let _ = storage.set_item(STORAGE_KEY, &value);
+        }
+    }
+
+
+    pub fn clear() {
+        if let Some(storage) = storage() {
+            let _ = storage.remove_item(STORAGE_KEY);
+        }
+    }
+}
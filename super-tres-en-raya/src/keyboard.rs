@@ -0,0 +1,181 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Atajos de teclado para jugar sin ratón. Las flechas mueven un cursor por
+// las 81 casillas saltándose los subtableros en los que no se puede jugar;
+// Enter o espacio marcan la casilla del cursor, N confirma la jugada como
+// NEXT y U quita la marca. Las teclas se escuchan en todo el documento,
+// salvo mientras se escribe en un campo de texto, y solo se interceptan
+// cuando el componente va a hacer algo con ellas.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::cell::Cell;
+use std::rc::Rc;
+use gloo_events::{EventListener, EventListenerOptions};
+use web_sys::wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent};
+use yew::Callback;
+use super_tres_engine::board::{Position, CENTER};
+use super_tres_engine::game::{Game, Move};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SHORTCUTS
+
+
+/// Teclas y lo que hacen, para la ayuda
+pub const SHORTCUTS: [(&str, &str); 6] = [
+    ("← ↑ → ↓", "Move the cursor across the playable boards"),
+    ("Enter / Space", "Mark the cell under the cursor, or unmark it"),
+    ("N", "Confirm the marked move (NEXT)"),
+    ("U", "Undo the mark of this turn"),
+    ("?", "Show or hide this help"),
+    ("Esc", "Close this help"),
+];
+
+
+/// Acción de un atajo de teclado
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shortcut {
+
+    /// Mueve el cursor esas filas y columnas
+    Cursor(i8, i8),
+    Mark,
+    Next,
+    Undo,
+    Help,
+    Close,
+}
+
+
+/// Atajos a los que responde el componente en este momento.
+/// Las demás teclas siguen haciendo lo de siempre, como desplazar la página.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Enabled {
+
+    /// Se ve el tablero y el cursor se puede mover por él
+    pub cursor: bool,
+
+    /// El cursor está en una casilla que se puede marcar
+    pub mark: bool,
+
+    /// Hay una jugada marcada que se puede confirmar o quitar
+    pub marked: bool,
+
+    /// La ayuda está abierta
+    pub help: bool,
+}
+
+
+impl Enabled {
+
+    fn accepts(self, shortcut: Shortcut) -> bool {
+        match shortcut {
+            Shortcut::Cursor(..) => self.cursor && !self.help,
+            Shortcut::Mark => self.mark && !self.help,
+            Shortcut::Next | Shortcut::Undo => self.marked && !self.help,
+            Shortcut::Help => true,
+            Shortcut::Close => self.help,
+        }
+    }
+}
+
+
+impl Shortcut {
+
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "ArrowUp" => Some(Shortcut::Cursor(-1, 0)),
+            "ArrowDown" => Some(Shortcut::Cursor(1, 0)),
+            "ArrowLeft" => Some(Shortcut::Cursor(0, -1)),
+            "ArrowRight" => Some(Shortcut::Cursor(0, 1)),
+            "Enter" | " " => Some(Shortcut::Mark),
+            "n" | "N" => Some(Shortcut::Next),
+            "u" | "U" => Some(Shortcut::Undo),
+            "?" => Some(Shortcut::Help),
+            "Escape" => Some(Shortcut::Close),
+            _ => None,
+        }
+    }
+}
+
+
+/// Escucha los atajos en todo el documento mientras se conserve el manejador.
+/// Las teclas de los campos de texto son del campo, Enter y espacio sobre
+/// un botón fuera del tablero lo pulsan como siempre, y las de los atajos
+/// que no están en `enabled` no se tocan.
+pub fn listen(enabled: Rc<Cell<Enabled>>, on_shortcut: Callback<Shortcut>) -> Option<EventListener> {
+    let document = web_sys::window()?.document()?;
+
+    let listener = EventListener::new_with_options(&document, "keydown", EventListenerOptions::enable_prevent_default(), move |event| {
+        let Some(event) = event.dyn_ref::<KeyboardEvent>() else {
+            return;
+        };
+        if event.ctrl_key() || event.meta_key() || event.alt_key() {
+            return;
+        }
+        let Some(shortcut) = Shortcut::from_key(&event.key()).filter(|shortcut| enabled.get().accepts(*shortcut)) else {
+            return;
+        };
+
+        let target = event.target().and_then(|target| target.dyn_into::<Element>().ok());
+        if let Some(target) = &target {
+            let editing = matches!(target.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+                || target.dyn_ref::<HtmlElement>().is_some_and(|element| element.is_content_editable());
+            let control = matches!(target.tag_name().as_str(), "BUTTON" | "A")
+                && target.closest(".super-grid").ok().flatten().is_none();
+            if editing || (control && shortcut == Shortcut::Mark) {
+                return;
+            }
+        }
+
+        event.prevent_default();
+        on_shortcut.emit(shortcut);
+    });
+
+    Some(listener)
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CURSOR
+
+
+/// Indica si el cursor puede estar en el subtablero: se puede jugar en él y nadie lo ganó
+pub fn focusable(game: &Game, board: Position) -> bool {
+    game.board_enabled(board) && game.tablero().get(board).is_err()
+}
+
+
+/// Casilla en la que aparece el cursor: el centro del subtablero activo o,
+/// si se puede jugar en cualquiera, del central o el primero disponible
+pub fn start(game: &Game) -> Option<Move> {
+    let board = [CENTER].into_iter().chain(Position::all()).find(|board| focusable(game, *board))?;
+    Some(Move::new(board, CENTER))
+}
+
+
+/// Casilla a la que se mueve el cursor en esa dirección, saltándose los
+/// subtableros en los que no puede estar. None si no hay ninguna.
+pub fn step(game: &Game, from: Move, (rows, columns): (i8, i8)) -> Option<Move> {
+    let total = from.total();
+
+    (1..9)
+        .map(|distance| (total.x as i8 + rows * distance, total.y as i8 + columns * distance))
+        .take_while(|(x, y)| (0..9).contains(x) && (0..9).contains(y))
+        .map(|(x, y)| Move::from_total(Position { x: x as u8, y: y as u8 }))
+        .find(|mv| focusable(game, mv.board))
+}
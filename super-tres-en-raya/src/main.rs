@@ -1,13 +1,20 @@
 //-------------------------------------------------------------------//
 //  AUTHOR:    @sfmolina                                            //
-//  Version:   v1                                                  //
-//  Modified:  26oc24                                             //
+//  Version:   v8                                                  //
+//  Modified:  19oc26                                             //
 //---------------------------------------------------------------//
 
 
 
+mod account;
 mod app;
+mod archive;
 mod components;
+mod connection;
+mod correspondence;
+mod keyboard;
+mod peer;
+mod session;
 
 
 
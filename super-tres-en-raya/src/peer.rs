@@ -0,0 +1,269 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Conexión directa con otro navegador por un canal de datos WebRTC, sin
+// servidor de partidas ni de señalización. La oferta y la respuesta se pasan
+// a mano como códigos de texto: quien abre la partida manda su oferta, el
+// rival la pega y devuelve su respuesta. No se usan servidores STUN, así que
+// solo conecta en la misma red (o entre dos pestañas del mismo equipo).
+// Por el canal van los mensajes de `super_tres_engine::net::ClientMessage`.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::Reflect;
+use web_sys::wasm_bindgen::closure::Closure;
+use web_sys::wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    MessageEvent, RtcDataChannel, RtcDataChannelEvent, RtcIceConnectionState, RtcPeerConnection,
+    RtcPeerConnectionIceEvent, RtcSdpType, RtcSessionDescriptionInit,
+};
+use yew::platform::spawn_local;
+use yew::Callback;
+use super_tres_engine::net::ClientMessage;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// PEER
+
+
+/// Nombre del canal de datos de la partida
+const CHANNEL_LABEL: &str = "super-tres";
+
+
+/// Avisos de la conexión al componente
+#[derive(Clone)]
+pub struct PeerCallbacks {
+
+    /// Oferta o respuesta local lista para mandarla al rival
+    pub on_signal: Callback<String>,
+
+    /// El canal de datos se abrió
+    pub on_open: Callback<()>,
+    pub on_message: Callback<ClientMessage>,
+
+    /// La conexión se cerró o falló, con el error si lo hubo
+    pub on_close: Callback<Option<String>>,
+}
+
+
+/// Manejador de eventos del navegador que se conserva mientras viva la conexión
+type Handler = Closure<dyn FnMut(JsValue)>;
+
+
+/// Conexión directa con el otro jugador. Al descartarla se cierra.
+pub struct Peer {
+    connection: RtcPeerConnection,
+    channel: Rc<RefCell<Option<RtcDataChannel>>>,
+    handlers: Rc<RefCell<Vec<Handler>>>,
+    on_close: Callback<Option<String>>,
+}
+
+
+/// Codifica una descripción de sesión para copiarla y pegarla
+fn encode(sdp: &str) -> Option<String> {
+    web_sys::window()?.btoa(sdp).ok()
+}
+
+
+fn decode(code: &str) -> Result<String, String> {
+    let code: String = code.split_whitespace().collect();
+    web_sys::window()
+        .and_then(|window| window.atob(&code).ok())
+        .filter(|sdp| sdp.starts_with("v=0"))
+        .ok_or_else(|| "invalid connection code".to_string())
+}
+
+
+fn error_text(err: JsValue) -> String {
+    err.as_string()
+        .or_else(|| Reflect::get(&err, &"message".into()).ok().and_then(|message| message.as_string()))
+        .unwrap_or_else(|| "WebRTC error".to_string())
+}
+
+
+impl Peer {
+
+    /// Abre una partida: crea el canal y prepara la oferta, que llega a `on_signal`
+    pub fn host(callbacks: PeerCallbacks) -> Result<Self, String> {
+        let peer = Self::new(&callbacks)?;
+
+        let channel = peer.connection.create_data_channel(CHANNEL_LABEL);
+        attach(&channel, &callbacks, &peer.handlers);
+        *peer.channel.borrow_mut() = Some(channel);
+
+        let connection = peer.connection.clone();
+        spawn_local(async move {
+            let result = async {
+                let offer = JsFuture::from(connection.create_offer()).await?;
+                set_local(&connection, RtcSdpType::Offer, offer).await
+            }.await;
+            if let Err(err) = result {
+                callbacks.on_close.emit(Some(error_text(err)));
+            }
+        });
+
+        Ok(peer)
+    }
+
+
+    /// Se une a la partida con la oferta del rival y prepara la respuesta, que llega a `on_signal`
+    pub fn join(offer: &str, callbacks: PeerCallbacks) -> Result<Self, String> {
+        let sdp = decode(offer)?;
+        let peer = Self::new(&callbacks)?;
+
+        // El canal lo crea quien abre la partida
+        let on_channel = {
+            let (channel, handlers, callbacks) = (Rc::clone(&peer.channel), Rc::clone(&peer.handlers), callbacks.clone());
+            Closure::new(move |event: JsValue| {
+                let opened = event.unchecked_into::<RtcDataChannelEvent>().channel();
+                attach(&opened, &callbacks, &handlers);
+                *channel.borrow_mut() = Some(opened);
+            })
+        };
+        peer.connection.set_ondatachannel(Some(on_channel.as_ref().unchecked_ref()));
+        peer.handlers.borrow_mut().push(on_channel);
+
+        let connection = peer.connection.clone();
+        spawn_local(async move {
+            let result = async {
+                let description = RtcSessionDescriptionInit::new(RtcSdpType::Offer);
+                description.set_sdp(&sdp);
+                JsFuture::from(connection.set_remote_description(&description)).await?;
+                let answer = JsFuture::from(connection.create_answer()).await?;
+                set_local(&connection, RtcSdpType::Answer, answer).await
+            }.await;
+            if let Err(err) = result {
+                callbacks.on_close.emit(Some(error_text(err)));
+            }
+        });
+
+        Ok(peer)
+    }
+
+
+    /// Conexión sin canal. La descripción local se da al rival cuando ya
+    /// tiene todos sus candidatos, para que no haya que pasar más códigos.
+    fn new(callbacks: &PeerCallbacks) -> Result<Self, String> {
+        let connection = RtcPeerConnection::new().map_err(error_text)?;
+        let handlers = Rc::new(RefCell::new(Vec::new()));
+
+        let on_candidate: Handler = {
+            let (connection, on_signal) = (connection.clone(), callbacks.on_signal.clone());
+            Closure::new(move |event: JsValue| {
+                // Sin candidato: se terminaron de reunir
+                if event.unchecked_into::<RtcPeerConnectionIceEvent>().candidate().is_none() {
+                    if let Some(code) = connection.local_description().and_then(|description| encode(&description.sdp())) {
+                        on_signal.emit(code);
+                    }
+                }
+            })
+        };
+        connection.set_onicecandidate(Some(on_candidate.as_ref().unchecked_ref()));
+
+        let on_state: Handler = {
+            let (connection, on_close) = (connection.clone(), callbacks.on_close.clone());
+            Closure::new(move |_: JsValue| {
+                if connection.ice_connection_state() == RtcIceConnectionState::Failed {
+                    on_close.emit(Some("could not connect to the other player".to_string()));
+                }
+            })
+        };
+        connection.set_oniceconnectionstatechange(Some(on_state.as_ref().unchecked_ref()));
+
+        handlers.borrow_mut().extend([on_candidate, on_state]);
+        Ok(Self { connection, channel: Rc::new(RefCell::new(None)), handlers, on_close: callbacks.on_close.clone() })
+    }
+
+
+    /// Completa la conexión con la respuesta del rival. Un código que no se
+    /// entiende no cierra la conexión, para poder volver a pegarlo.
+    pub fn answer(&self, answer: &str) -> Result<(), String> {
+        let sdp = decode(answer)?;
+
+        let (connection, on_close) = (self.connection.clone(), self.on_close.clone());
+        spawn_local(async move {
+            let description = RtcSessionDescriptionInit::new(RtcSdpType::Answer);
+            description.set_sdp(&sdp);
+            if let Err(err) = JsFuture::from(connection.set_remote_description(&description)).await {
+                on_close.emit(Some(error_text(err)));
+            }
+        });
+        Ok(())
+    }
+
+
+    pub fn send(&self, message: ClientMessage) {
+        if let Some(channel) = self.channel.borrow().as_ref() {
+            let _ = channel.send_with_str(&message.to_string());
+        }
+    }
+}
+
+
+impl Drop for Peer {
+    fn drop(&mut self) {
+        // Los manejadores se sueltan con la conexión, así que no pueden volver a llamarse
+        if let Some(channel) = self.channel.borrow_mut().take() {
+            channel.set_onopen(None);
+            channel.set_onmessage(None);
+            channel.set_onclose(None);
+            channel.close();
+        }
+        self.connection.set_onicecandidate(None);
+        self.connection.set_oniceconnectionstatechange(None);
+        self.connection.set_ondatachannel(None);
+        self.connection.close();
+    }
+}
+
+
+/// Fija la descripción local a partir de la oferta o la respuesta creada
+async fn set_local(connection: &RtcPeerConnection, kind: RtcSdpType, created: JsValue) -> Result<(), JsValue> {
+    let sdp = Reflect::get(&created, &"sdp".into())?.as_string().unwrap_or_default();
+    let description = RtcSessionDescriptionInit::new(kind);
+    description.set_sdp(&sdp);
+    JsFuture::from(connection.set_local_description(&description)).await?;
+    Ok(())
+}
+
+
+/// Conecta los eventos del canal con los callbacks
+fn attach(channel: &RtcDataChannel, callbacks: &PeerCallbacks, handlers: &Rc<RefCell<Vec<Handler>>>) {
+    let on_open: Handler = {
+        let on_open = callbacks.on_open.clone();
+        Closure::new(move |_: JsValue| on_open.emit(()))
+    };
+    let on_message: Handler = {
+        let on_message = callbacks.on_message.clone();
+        Closure::new(move |event: JsValue| {
+            let data = event.unchecked_into::<MessageEvent>().data().as_string().unwrap_or_default();
+            // Lo que no se entiende se ignora; las jugadas las valida el componente
+            for message in data.lines().filter_map(|line| line.parse().ok()) {
+                on_message.emit(message);
+            }
+        })
+    };
+    let on_close: Handler = {
+        let on_close = callbacks.on_close.clone();
+        Closure::new(move |_: JsValue| on_close.emit(None))
+    };
+
+    channel.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    channel.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+    handlers.borrow_mut().extend([on_open, on_message, on_close]);
+}
@@ -0,0 +1,74 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Sesión de la partida en red guardada en el navegador.
+// Si la pestaña se recarga a media partida, la aplicación se vuelve a
+// conectar al mismo servidor y recupera el asiento con la sesión.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use web_sys::Storage;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SESSION
+
+
+/// Clave del almacenamiento local en la que se guarda la sesión
+const STORAGE_KEY: &str = "super-tres-session";
+
+
+/// Asiento de una sala de un servidor
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Session {
+    pub server: String,
+    pub room: String,
+    pub token: String,
+}
+
+
+/// Almacenamiento local del navegador, si lo hay
+pub fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+
+impl Session {
+
+    /// Sesión guardada, si hay una
+    pub fn load() -> Option<Self> {
+        let value = storage()?.get_item(STORAGE_KEY).ok()??;
+        let mut fields = value.split_whitespace();
+        let session = Self {
+            server: fields.next()?.to_string(),
+            room: fields.next()?.to_string(),
+            token: fields.next()?.to_string(),
+        };
+        Some(session)
+    }
+
+
+    /// Guarda la sesión. Sin almacenamiento local no se podrá recuperar el asiento.
+    pub fn save(&self) {
+        if let Some(storage) = storage() {
+            let _ = storage.set_item(STORAGE_KEY, &format!("{} {} {}", self.server, self.room, self.token));
+        }
+    }
+
+
+    pub fn clear() {
+        if let Some(storage) = storage() {
+            let _ = storage.remove_item(STORAGE_KEY);
+        }
+    }
+}
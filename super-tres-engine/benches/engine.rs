@@ -0,0 +1,131 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Rendimiento de la generación de jugadas y de la comprobación de líneas,
+// comparando las máscaras de bits con los tableros anidados.
+//
+//     cargo bench -p super-tres-engine
+
+
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use super_tres_engine::bitboard::{self, Bitboard};
+use super_tres_engine::board::{Player, Position, TableroSuperTres, TableroTres};
+use super_tres_engine::bot::{Bot, RandomBot};
+use super_tres_engine::game::{Game, Move};
+
+
+
+/// Posiciones de partidas aleatorias para medir con tableros variados
+fn sample_positions() -> Vec<Game> {
+    let mut positions = Vec::new();
+
+    for seed in 0..64 {
+        let mut bot = RandomBot::new(seed);
+        let mut game = Game::new();
+
+        while game.result().is_none() {
+            positions.push(game.clone());
+            let mv = bot.choose(&game).unwrap();
+            game.apply(mv);
+        }
+    }
+
+    positions
+}
+
+
+/// Generación de jugadas sobre los tableros anidados, como se hacía antes de las máscaras
+fn nested_moves(tablero: &TableroSuperTres, active_table: Option<Position>) -> Vec<Move> {
+    let mut moves = Vec::new();
+
+    for board in Position::all().filter(|b| active_table.is_none_or(|active| active == *b)) {
+        if let Err(tab) = tablero.get(board) {
+            for cell in Position::all().filter(|c| tab.get(*c).is_none()) {
+                moves.push(Move::new(board, cell));
+            }
+        }
+    }
+
+    moves
+}
+
+
+fn movegen(c: &mut Criterion) {
+    let positions = sample_positions();
+    let nested: Vec<_> = positions.iter().map(|g| (g.tablero(), g.active_table())).collect();
+    let total: usize = positions.iter().map(|g| g.legal_moves().len()).sum();
+
+    let mut group = c.benchmark_group("movegen");
+    group.throughput(Throughput::Elements(total as u64));
+
+    group.bench_function("bitboard", |b| {
+        b.iter(|| positions.iter().map(|g| black_box(g.legal_moves()).len()).sum::<usize>())
+    });
+    group.bench_function("nested", |b| {
+        b.iter(|| nested.iter().map(|(t, a)| black_box(nested_moves(t, *a)).len()).sum::<usize>())
+    });
+
+    group.finish();
+}
+
+
+fn win_check(c: &mut Criterion) {
+
+    // Todas las máscaras posibles de un subtablero
+    let masks: Vec<u16> = (0..512).collect();
+    let tableros: Vec<TableroTres> = masks.iter().map(|mask| {
+        let mut tab = TableroTres::new();
+        for i in bitboard::bits(*mask) {
+            tab.put(bitboard::position(i), Player::First);
+        }
+        tab
+    }).collect();
+
+    let mut group = c.benchmark_group("win_check/sub_board");
+    group.throughput(Throughput::Elements(masks.len() as u64));
+    group.bench_function("bitboard", |b| {
+        b.iter(|| masks.iter().filter(|m| bitboard::is_win(black_box(**m))).count())
+    });
+    group.bench_function("nested", |b| {
+        b.iter(|| tableros.iter().filter(|t| black_box(*t).check().is_some()).count())
+    });
+    group.finish();
+
+    // Supertableros de partidas reales
+    let positions = sample_positions();
+    let bits: Vec<Bitboard> = positions.iter().map(|g| *g.bitboard()).collect();
+    let nested: Vec<TableroSuperTres> = positions.iter().map(|g| g.tablero()).collect();
+
+    let mut group = c.benchmark_group("win_check/super_board");
+    group.throughput(Throughput::Elements(positions.len() as u64));
+    group.bench_function("bitboard", |b| {
+        b.iter(|| bits.iter().filter(|t| black_box(*t).winner().is_some()).count())
+    });
+    group.bench_function("nested", |b| {
+        b.iter(|| nested.iter().filter(|t| black_box(*t).winner().is_some()).count())
+    });
+    group.finish();
+}
+
+
+fn perft(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perft");
+
+    for depth in [4, 5] {
+        let nodes = Game::new().perft(depth);
+        group.throughput(Throughput::Elements(nodes));
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &depth, |b, depth| {
+            b.iter(|| Game::new().perft(*depth))
+        });
+    }
+
+    group.finish();
+}
+
+
+criterion_group!(benches, movegen, win_check, perft);
+criterion_main!(benches);
@@ -0,0 +1,149 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Interfaz binaria de los bots escritos como módulos WebAssembly.
+//
+// El módulo no importa nada y exporta:
+//
+//     memory                  su memoria lineal
+//     st_buffer() -> i32      dirección de un búfer de al menos BUFFER_BYTES bytes
+//     st_choose() -> i32      elige una jugada para la posición escrita en el búfer
+//
+// Antes de cada jugada el anfitrión escribe la posición en el búfer y llama a
+// `st_choose`, que devuelve la casilla elegida como `fila * 9 + columna`
+// (de 0 a 80, con las filas de arriba abajo y las columnas de la `a` a la `i`)
+// o un número negativo si no encuentra jugada. Una casilla ilegal pierde la partida.
+//
+// Posición en el búfer:
+//
+//     bytes 0..81     casillas por filas: 0 libre, 1 X, 2 O
+//     byte 81         jugador al que le toca: 1 X, 2 O
+//     byte 82         subtablero activo, `fila * 3 + columna` de 0 a 8, o 255 si es cualquiera
+//     byte 83         número N de jugadas legales
+//     bytes 84..84+N  jugadas legales, codificadas igual que la respuesta
+//
+// Las casillas de los subtableros ganados se mantienen; un subtablero ganado
+// es el que tiene tres en línea del mismo jugador.
+//
+// Opcionalmente, el módulo puede exportar `st_abi_version() -> i32` devolviendo
+// ABI_VERSION. El anfitrión limita las instrucciones de cada jugada.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::bitboard::{self, Bitboard};
+use crate::board::{Player, Position};
+use crate::game::{Game, Move};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+/// Versión de la interfaz
+pub const ABI_VERSION: i32 = 1;
+
+/// Tamaño mínimo del búfer de la posición
+pub const BUFFER_BYTES: usize = 256;
+
+/// Byte del subtablero activo cuando se puede jugar en cualquiera
+pub const ANY_BOARD: u8 = 255;
+
+const TURN: usize = 81;
+const ACTIVE: usize = 82;
+const MOVE_COUNT: usize = 83;
+const MOVES: usize = 84;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ENCODING
+
+
+/// Jugada codificada como `fila * 9 + columna`
+pub fn encode_move(mv: Move) -> i32 {
+    let total = mv.total();
+    total.x as i32 * 9 + total.y as i32
+}
+
+
+/// Jugada a partir de su código. None si está fuera del tablero.
+pub fn decode_move(code: i32) -> Option<Move> {
+    (0..81).contains(&code).then(|| Move::from_total(Position { x: (code / 9) as u8, y: (code % 9) as u8 }))
+}
+
+
+/// Posición tal y como se escribe en el búfer del bot
+pub fn encode_position(game: &Game) -> Vec<u8> {
+    let mut bytes = vec![0; MOVES];
+    let board = game.bitboard();
+
+    for code in 0..81 {
+        let mv = decode_move(code).expect("code on the board");
+        bytes[code as usize] = match board.get(bitboard::index(mv.board), bitboard::index(mv.cell)) {
+            Some(Player::First) => 1,
+            Some(Player::Second) => 2,
+            None => 0,
+        };
+    }
+
+    bytes[TURN] = match game.turn() {
+        Some(Player::First) => 1,
+        Some(Player::Second) => 2,
+        None => 0,
+    };
+    bytes[ACTIVE] = match game.active_table() {
+        Some(active) if game.turn().is_some() => bitboard::index(active) as u8,
+        _ => ANY_BOARD,
+    };
+
+    let moves = game.legal_moves();
+    bytes[MOVE_COUNT] = moves.len() as u8;
+    bytes.extend(moves.into_iter().map(|mv| encode_move(mv) as u8));
+
+    bytes
+}
+
+
+/// Partida en la posición del búfer, para los bots escritos en Rust sobre el motor.
+/// None si la posición no es válida o ya terminó.
+pub fn decode_position(bytes: &[u8]) -> Option<Game> {
+    if bytes.len() < MOVES {
+        return None;
+    }
+
+    let mut cells = [[0u16; 9]; 2];
+    for code in 0..81 {
+        let mv = decode_move(code).expect("code on the board");
+        let player = match bytes[code as usize] {
+            0 => continue,
+            1 => Player::First,
+            2 => Player::Second,
+            _ => return None,
+        };
+        cells[player.index()][bitboard::index(mv.board)] |= bitboard::bit(mv.cell);
+    }
+
+    let turn = match bytes[TURN] {
+        1 => Player::First,
+        2 => Player::Second,
+        _ => return None,
+    };
+    let active_table = match bytes[ACTIVE] {
+        ANY_BOARD => None,
+        b @ 0..=8 => Some(bitboard::position(b as usize)),
+        _ => return None,
+    };
+
+    Game::from_position(Bitboard::from_cells(cells), turn, active_table)
+}
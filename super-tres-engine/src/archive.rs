@@ -0,0 +1,372 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Archivo de partidas en red del servidor: filtros de búsqueda y datos de
+// cada partida archivada. Las búsquedas van en la dirección de la API como
+// parámetros de consulta, todos opcionales:
+//
+//     /api/games?player=alice&ruleset=standard&result=1-0&from=2026-10-01
+//         &to=2026-10-19&min_plies=20&max_plies=60&opening=e5&offset=50
+//
+// `result` es el marcador de `record` (`1-0`, `0-1` o `1/2-1/2`), las fechas
+// son días UTC y ambos extremos cuentan, y `opening` es la primera jugada.
+// Cada partida se guarda con la etiqueta `Termination`, que dice si terminó
+// en el tablero (`normal`), por abandono, por acuerdo o por tiempo.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::str::FromStr;
+use crate::clock::TimeControl;
+use crate::game::{Game, GameResult, Move};
+use crate::record::{GameRecord, ParseRecordError, RecordResult};
+use crate::ruleset::Ruleset;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Partidas de cada página de resultados
+pub const ARCHIVE_PAGE: usize = 50;
+
+/// Segundos de un día
+const DAY_SECONDS: u64 = 86_400;
+
+
+/// Día del calendario, en UTC
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+
+/// Filtros de una búsqueda en el archivo. Los que son None no filtran.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ArchiveQuery {
+
+    /// Cuenta que jugó la partida, con X o con O
+    pub player: Option<String>,
+    pub ruleset: Option<Ruleset>,
+    pub result: Option<RecordResult>,
+
+    /// Primer y último día en que pudo terminar la partida
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+
+    /// Número de jugadas mínimo y máximo
+    pub min_plies: Option<u32>,
+    pub max_plies: Option<u32>,
+
+    /// Primera jugada de la partida
+    pub opening: Option<Move>,
+
+    /// Partidas que se saltan, para pasar de página
+    pub offset: u32,
+}
+
+
+/// Partida del archivo, sin sus jugadas
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ArchivedGame {
+    pub id: u64,
+
+    /// Cuentas de X y de O. None si el jugador no entró con una.
+    pub players: [Option<String>; 2],
+    pub ruleset: Ruleset,
+    pub time_control: TimeControl,
+    pub result: RecordResult,
+
+    /// Cómo terminó, como en la etiqueta `Termination`
+    pub termination: String,
+    pub plies: u32,
+    pub opening: Option<Move>,
+
+    /// La partida contó para la puntuación de los jugadores
+    pub rated: bool,
+
+    /// Segundos desde la época
+    pub ended: u64,
+}
+
+
+/// Error al leer una búsqueda o una fecha
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseQueryError(pub String);
+
+impl fmt::Display for ParseQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseQueryError {}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATES
+
+
+impl Date {
+
+    /// Día de ese número de días desde la época
+    pub fn from_days(days: i64) -> Self {
+        // Algoritmo de días a fecha civil de Howard Hinnant
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+
+        Self { year: year_of_era + era * 400 + i64::from(month <= 2), month: month as u32, day: day as u32 }
+    }
+
+    /// Día de ese instante, en segundos desde la época
+    pub fn from_secs(seconds: u64) -> Self {
+        Self::from_days((seconds / DAY_SECONDS) as i64)
+    }
+
+    /// Días desde la época
+    pub fn days(self) -> i64 {
+        let year = self.year - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Primer segundo del día desde la época
+    pub fn start_secs(self) -> i64 {
+        self.days() * DAY_SECONDS as i64
+    }
+
+    fn days_in_month(year: i64, month: u32) -> u32 {
+        match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+}
+
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+
+impl FromStr for Date {
+    type Err = ParseQueryError;
+
+    /// Lee una fecha `AAAA-MM-DD`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseQueryError(format!("invalid date `{}`: expected YYYY-MM-DD", s));
+
+        let mut parts = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let year: i64 = year.parse().map_err(|_| invalid())?;
+        let month: u32 = month.parse().map_err(|_| invalid())?;
+        let day: u32 = day.parse().map_err(|_| invalid())?;
+
+        if !(1..=12).contains(&month) || day == 0 || day > Self::days_in_month(year, month) {
+            return Err(invalid());
+        }
+        Ok(Self { year, month, day })
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// QUERIES
+
+
+impl ArchiveQuery {
+
+    /// La misma búsqueda desde otra partida
+    pub fn at(&self, offset: u32) -> Self {
+        Self { offset, ..self.clone() }
+    }
+}
+
+
+impl fmt::Display for ArchiveQuery {
+
+    /// Parámetros de consulta de la búsqueda, sin el `?`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = Vec::new();
+        if let Some(player) = &self.player {
+            params.push(("player", player.clone()));
+        }
+        if let Some(ruleset) = self.ruleset {
+            params.push(("ruleset", ruleset.to_string()));
+        }
+        if let Some(result) = self.result {
+            params.push(("result", result.to_string()));
+        }
+        if let Some(from) = self.from {
+            params.push(("from", from.to_string()));
+        }
+        if let Some(to) = self.to {
+            params.push(("to", to.to_string()));
+        }
+        if let Some(min_plies) = self.min_plies {
+            params.push(("min_plies", min_plies.to_string()));
+        }
+        if let Some(max_plies) = self.max_plies {
+            params.push(("max_plies", max_plies.to_string()));
+        }
+        if let Some(opening) = self.opening {
+            params.push(("opening", opening.to_string()));
+        }
+        if self.offset > 0 {
+            params.push(("offset", self.offset.to_string()));
+        }
+
+        let params: Vec<String> = params.into_iter().map(|(name, value)| format!("{}={}", name, encode(&value))).collect();
+        write!(f, "{}", params.join("&"))
+    }
+}
+
+
+impl FromStr for ArchiveQuery {
+    type Err = ParseQueryError;
+
+    /// Lee los parámetros de consulta. Los vacíos se ignoran.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut query = ArchiveQuery::default();
+
+        for param in s.split('&').filter(|param| !param.is_empty()) {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            let value = decode(value).ok_or_else(|| ParseQueryError(format!("invalid encoding in `{}`", param)))?;
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+
+            let invalid = || ParseQueryError(format!("invalid value `{}` for {}", value, name));
+            match name {
+                "player" => query.player = Some(value.to_string()),
+                "ruleset" => query.ruleset = Some(value.parse().map_err(|_| invalid())?),
+                "result" => query.result = Some(value.parse().map_err(|_| invalid())?),
+                "from" => query.from = Some(value.parse()?),
+                "to" => query.to = Some(value.parse()?),
+                "min_plies" => query.min_plies = Some(value.parse().map_err(|_| invalid())?),
+                "max_plies" => query.max_plies = Some(value.parse().map_err(|_| invalid())?),
+                "opening" => query.opening = Some(value.parse().map_err(|_| invalid())?),
+                "offset" => query.offset = value.parse().map_err(|_| invalid())?,
+                _ => return Err(ParseQueryError(format!("unknown filter `{}`", name))),
+            }
+        }
+
+        Ok(query)
+    }
+}
+
+
+/// Codifica un valor para la dirección: todo lo que no es letra, cifra o `-_.~` va como `%XX`
+fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+
+/// Descodifica un valor de la dirección, con `+` como espacio
+fn decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            },
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// GAMES
+
+
+/// Valor de la etiqueta `Termination` de una partida que terminó así
+pub fn termination(result: GameResult) -> &'static str {
+    match result {
+        GameResult::Win(_) | GameResult::Draw => "normal",
+        GameResult::Resigned(_) => "resignation",
+        GameResult::AgreedDraw => "agreement",
+        GameResult::OutOfTime(_) => "time",
+    }
+}
+
+
+/// Reproduce la partida archivada. Si terminó antes de decidirse en el tablero,
+/// la termina igual que en la sala según su etiqueta `Termination`.
+pub fn replay(record: &GameRecord) -> Result<Game, ParseRecordError> {
+    let mut game = record.game()?;
+
+    if game.result().is_none() {
+        let winner = match record.result {
+            RecordResult::Finished(result) => result.winner(),
+            RecordResult::Unfinished => None,
+        };
+        let early = match (record.tag("Termination"), winner) {
+            (Some("resignation"), Some(winner)) => Some(GameResult::Resigned(winner.other())),
+            (Some("time"), Some(winner)) => Some(GameResult::OutOfTime(winner.other())),
+            (Some("agreement"), None) => Some(GameResult::AgreedDraw),
+            _ => None,
+        };
+        if let Some(result) = early {
+            game.end(result);
+        }
+    }
+
+    Ok(game)
+}
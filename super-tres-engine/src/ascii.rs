@@ -0,0 +1,220 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v4                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Dibujo en texto plano del tablero y de la partida, para herramientas de
+// línea de órdenes, trazas de depuración y comparaciones de posiciones.
+//
+//         a b c   d e f   g h i
+//       +-------+-------+-------+
+//     1 | . . . | \   / | . . . |
+//     2 | . O . |   X   | . . . |
+//     3 | . . . | /   \ | . . . |
+//       +-------+-------+-------+
+//     4 | . . . |[. . .]| . . . |
+//     5 | . . . |[. X .]| . . . |
+//     6 | . . . |[. . .]| . . . |
+//       +-------+-------+-------+
+//     7 | . . . | / - \ | . . . |
+//     8 | . . . | |   | | . . . |
+//     9 | . . . | \ - / | . . . |
+//       +-------+-------+-------+
+//     O to move, last move e5
+//
+// Los subtableros ganados se dibujan como una X o una O grande y los
+// subtableros en los que se puede jugar van entre corchetes.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use crate::bitboard::{self, Bitboard};
+use crate::board::{Player, Position};
+use crate::game::{Game, GameResult, Move};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+const COLUMNS: &str = "    a b c   d e f   g h i";
+const SEPARATOR: &str = "  +-------+-------+-------+";
+
+/// Filas de los subtableros ganados
+const BIG_X: [&str; 3] = ["\\   /", "  X  ", "/   \\"];
+const BIG_O: [&str; 3] = ["/ - \\", "|   |", "\\ - /"];
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// RENDERING
+
+
+/// Escribe el tablero. `active` indica qué subtableros se marcan como jugables.
+fn write_board(f: &mut fmt::Formatter<'_>, board: &Bitboard, active: impl Fn(usize) -> bool) -> fmt::Result {
+    writeln!(f, "{}", COLUMNS)?;
+    writeln!(f, "{}", SEPARATOR)?;
+
+    for row in 0..9u8 {
+        write!(f, "{} |", row + 1)?;
+
+        for board_col in 0..3u8 {
+            let first = Move::from_total(Position { x: row, y: board_col * 3 });
+            let sub = bitboard::index(first.board);
+            let (open, close) = if active(sub) { ('[', ']') } else { (' ', ' ') };
+
+            let cells = match board.owner(sub) {
+                Some(Player::First) => BIG_X[first.cell.x as usize].to_string(),
+                Some(Player::Second) => BIG_O[first.cell.x as usize].to_string(),
+                None => (0..3u8)
+                    .map(|i| {
+                        let mv = Move::from_total(Position { x: row, y: board_col * 3 + i });
+                        board.get(sub, bitboard::index(mv.cell)).map_or(".", Player::symbol)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            };
+
+            write!(f, "{}{}{}|", open, cells, close)?;
+        }
+        writeln!(f)?;
+
+        if row % 3 == 2 {
+            writeln!(f, "{}", SEPARATOR)?;
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Todas las casillas, con los subtableros ganados como una X o una O grande
+impl fmt::Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_board(f, self, |_| false)
+    }
+}
+
+
+/// El tablero con los subtableros jugables marcados y una línea con el turno o el resultado
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board = self.bitboard();
+        write_board(f, board, |sub| board.playable(sub) && self.board_enabled(bitboard::position(sub)))?;
+
+        match (self.result(), self.turn()) {
+            (Some(GameResult::Win(winner)), _) => write!(f, "{} wins", winner.symbol())?,
+            (Some(GameResult::Draw), _) => write!(f, "Draw")?,
+            (Some(GameResult::Resigned(player)), _) => write!(f, "{} resigns, {} wins", player.symbol(), player.other().symbol())?,
+            (Some(GameResult::AgreedDraw), _) => write!(f, "Draw agreed")?,
+            (Some(GameResult::OutOfTime(player)), _) => write!(f, "{} ran out of time, {} wins", player.symbol(), player.other().symbol())?,
+            (None, Some(turn)) => write!(f, "{} to move", turn.symbol())?,
+            (None, None) => {},
+        }
+
+        match self.last_move() {
+            Some(mv) => write!(f, ", last move {}", mv),
+            None => Ok(()),
+        }
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Dibujo esperado, escrito a partir de la línea siguiente a la comilla
+    fn snapshot(text: &str) -> &str {
+        text.strip_prefix('\n').unwrap()
+    }
+
+    fn game(moves: &[&str]) -> Game {
+        let moves: Vec<Move> = moves.iter().map(|mv| mv.parse().unwrap()).collect();
+        Game::from_moves(&moves).unwrap()
+    }
+
+    #[test]
+    fn start_position() {
+        assert_eq!(Game::new().to_string(), snapshot(r"
+    a b c   d e f   g h i
+  +-------+-------+-------+
+1 | . . . | . . . | . . . |
+2 | . . . | . . . | . . . |
+3 | . . . | . . . | . . . |
+  +-------+-------+-------+
+4 | . . . |[. . .]| . . . |
+5 | . . . |[. . .]| . . . |
+6 | . . . |[. . .]| . . . |
+  +-------+-------+-------+
+7 | . . . | . . . | . . . |
+8 | . . . | . . . | . . . |
+9 | . . . | . . . | . . . |
+  +-------+-------+-------+
+X to move"));
+    }
+
+    /// Tras cada jugada se marca el subtablero de la casilla jugada
+    #[test]
+    fn active_board() {
+        assert_eq!(game(&["e5", "e4"]).to_string(), snapshot(r"
+    a b c   d e f   g h i
+  +-------+-------+-------+
+1 | . . . |[. . .]| . . . |
+2 | . . . |[. . .]| . . . |
+3 | . . . |[. . .]| . . . |
+  +-------+-------+-------+
+4 | . . . | . O . | . . . |
+5 | . . . | . X . | . . . |
+6 | . . . | . . . | . . . |
+  +-------+-------+-------+
+7 | . . . | . . . | . . . |
+8 | . . . | . . . | . . . |
+9 | . . . | . . . | . . . |
+  +-------+-------+-------+
+X to move, last move e4"));
+    }
+
+    /// Los subtableros ganados se dibujan como una X o una O grande
+    #[test]
+    fn won_boards() {
+        let mut cells = [[0; 9]; 2];
+        cells[0][0] = 0b000_000_111;
+        cells[1][0] = 0b000_011_000;
+        cells[1][8] = 0b100_100_100;
+        cells[0][4] = 0b000_010_000;
+
+        assert_eq!(Bitboard::from_cells(cells).to_string(), snapshot(r"
+    a b c   d e f   g h i
+  +-------+-------+-------+
+1 | \   / | . . . | . . . |
+2 |   X   | . . . | . . . |
+3 | /   \ | . . . | . . . |
+  +-------+-------+-------+
+4 | . . . | . . . | . . . |
+5 | . . . | . X . | . . . |
+6 | . . . | . . . | . . . |
+  +-------+-------+-------+
+7 | . . . | . . . | / - \ |
+8 | . . . | . . . | |   | |
+9 | . . . | . . . | \ - / |
+  +-------+-------+-------+
+"));
+    }
+}
@@ -0,0 +1,263 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Representación compacta del supertablero para la búsqueda.
+// Cada subtablero es una máscara de 9 bits por jugador y los subtableros
+// ganados son otra máscara de 9 bits por jugador. Las líneas ganadoras
+// se comprueban con una tabla precalculada de las 512 máscaras posibles.
+//
+// El bit de la posición (x, y) es `x * 3 + y`, igual para casillas y subtableros.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::board::{Player, Position};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MASKS
+
+
+/// Máscara con las nueve posiciones
+pub const FULL: u16 = 0x1FF;
+
+/// Líneas ganadoras: filas, columnas y diagonales
+pub const WIN_MASKS: [u16; 8] = [
+    0b000_000_111,
+    0b000_111_000,
+    0b111_000_000,
+    0b001_001_001,
+    0b010_010_010,
+    0b100_100_100,
+    0b100_010_001,
+    0b001_010_100,
+];
+
+/// Para cada máscara de 9 bits, si contiene alguna línea ganadora
+const WINS: [bool; 512] = precompute_wins();
+
+const fn precompute_wins() -> [bool; 512] {
+    let mut wins = [false; 512];
+    let mut mask = 0;
+
+    while mask < 512 {
+        let mut line = 0;
+        while line < WIN_MASKS.len() {
+            if mask & WIN_MASKS[line] as usize == WIN_MASKS[line] as usize {
+                wins[mask] = true;
+            }
+            line += 1;
+        }
+        mask += 1;
+    }
+
+    wins
+}
+
+
+/// Indica si la máscara contiene una línea ganadora
+pub fn is_win(mask: u16) -> bool {
+    WINS[mask as usize]
+}
+
+/// Índice de una posición de un tablero de 3x3
+pub fn index(pos: Position) -> usize {
+    pos.x as usize * 3 + pos.y as usize
+}
+
+/// Posición correspondiente a un índice
+pub fn position(index: usize) -> Position {
+    Position { x: (index / 3) as u8, y: (index % 3) as u8 }
+}
+
+/// Bit de una posición de un tablero de 3x3
+pub fn bit(pos: Position) -> u16 {
+    1 << index(pos)
+}
+
+/// Itera los índices de los bits activos de una máscara
+pub fn bits(mut mask: u16) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if mask == 0 {
+            None
+        } else {
+            let i = mask.trailing_zeros() as usize;
+            mask &= mask - 1;
+            Some(i)
+        }
+    })
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Supertablero en máscaras de bits
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Bitboard {
+
+    /// Casillas de cada jugador en cada subtablero
+    cells: [[u16; 9]; 2],
+
+    /// Subtableros ganados por cada jugador
+    won: [u16; 2],
+}
+
+impl Bitboard {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Supertablero a partir de las casillas de cada jugador; los subtableros ganados se deducen
+    pub fn from_cells(cells: [[u16; 9]; 2]) -> Self {
+        let mut won = [0; 2];
+        for (player, boards) in cells.iter().enumerate() {
+            for (board, mask) in boards.iter().enumerate() {
+                if is_win(*mask) {
+                    won[player] |= 1 << board;
+                }
+            }
+        }
+        Self { cells, won }
+    }
+
+    /// Casillas de un jugador en un subtablero
+    pub fn cells(&self, player: Player, board: usize) -> u16 {
+        self.cells[player.index()][board]
+    }
+
+    /// Subtableros ganados por un jugador
+    pub fn won(&self, player: Player) -> u16 {
+        self.won[player.index()]
+    }
+
+    /// Subtableros ganados por cualquiera de los dos jugadores
+    pub fn decided(&self) -> u16 {
+        self.won[0] | self.won[1]
+    }
+
+    /// Casillas ocupadas de un subtablero
+    pub fn occupied(&self, board: usize) -> u16 {
+        self.cells[0][board] | self.cells[1][board]
+    }
+
+    /// Casillas libres de un subtablero en el que todavía se puede jugar
+    pub fn empty(&self, board: usize) -> u16 {
+        if self.decided() & (1 << board) != 0 {
+            0
+        } else {
+            FULL & !self.occupied(board)
+        }
+    }
+
+    /// Indica si en el subtablero todavía se puede jugar
+    pub fn playable(&self, board: usize) -> bool {
+        self.empty(board) != 0
+    }
+
+    /// Subtableros en los que todavía se puede jugar
+    pub fn playable_boards(&self) -> u16 {
+        (0..9).filter(|b| self.playable(*b)).fold(0, |mask, b| mask | 1 << b)
+    }
+
+    /// Jugador que ocupa una casilla
+    pub fn get(&self, board: usize, cell: usize) -> Option<Player> {
+        [Player::First, Player::Second].into_iter().find(|p| self.cells(*p, board) & (1 << cell) != 0)
+    }
+
+    /// Jugador que ganó un subtablero
+    pub fn owner(&self, board: usize) -> Option<Player> {
+        [Player::First, Player::Second].into_iter().find(|p| self.won(*p) & (1 << board) != 0)
+    }
+
+    /// Marca una casilla. Devuelve true si con ella el jugador gana el subtablero.
+    pub fn put(&mut self, player: Player, board: usize, cell: usize) -> bool {
+        let cells = &mut self.cells[player.index()][board];
+        *cells |= 1 << cell;
+
+        if is_win(*cells) {
+            self.won[player.index()] |= 1 << board;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Desmarca una casilla y, si el subtablero estaba ganado, deja de estarlo.
+    ///
+    /// En un subtablero ganado no se puede jugar, así que solo la última casilla marcada pudo ganarlo.
+    pub fn remove(&mut self, player: Player, board: usize, cell: usize) {
+        self.cells[player.index()][board] &= !(1 << cell);
+        self.won[player.index()] &= !(1 << board);
+    }
+
+    /// Ganador del supertablero
+    pub fn winner(&self) -> Option<Player> {
+        [Player::First, Player::Second].into_iter().find(|p| is_win(self.won(*p)))
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// La tabla precalculada coincide con comprobar las líneas una a una
+    #[test]
+    fn win_table() {
+        for mask in 0..=FULL {
+            let expected = WIN_MASKS.iter().any(|line| mask & line == *line);
+            assert_eq!(is_win(mask), expected, "mask {:09b}", mask);
+        }
+    }
+
+    #[test]
+    fn put_and_remove() {
+        let mut board = Bitboard::new();
+
+        assert!(!board.put(Player::First, 4, 0));
+        assert!(!board.put(Player::First, 4, 4));
+        assert!(!board.put(Player::Second, 4, 1));
+        assert!(board.put(Player::First, 4, 8));
+        assert_eq!((board.owner(4), board.empty(4), board.playable(4)), (Some(Player::First), 0, false));
+        assert_eq!(board.get(4, 1), Some(Player::Second));
+        assert_eq!(board, Bitboard::from_cells({
+            let mut cells = [[0; 9]; 2];
+            cells[0][4] = 0b100_010_001;
+            cells[1][4] = 0b000_000_010;
+            cells
+        }));
+
+        board.remove(Player::First, 4, 8);
+        assert_eq!((board.owner(4), board.empty(4)), (None, 0b111_101_100));
+    }
+
+    #[test]
+    fn positions_and_bits() {
+        for i in 0..9 {
+            assert_eq!(index(position(i)), i);
+        }
+        assert_eq!(bits(0b100_010_001).collect::<Vec<_>>(), [0, 4, 8]);
+        assert_eq!(bit(Position { x: 1, y: 2 }), 1 << 5);
+    }
+}
@@ -0,0 +1,327 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Tableros del super tres en raya: subtableros de 3x3 y supertablero.
+// Es la representación que se muestra; la búsqueda usa `Bitboard`.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::bitboard::{self, Bitboard};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Enumeración que representa a los jugadores
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Player {
+    First,
+    Second,
+}
+
+impl Player {
+
+    /// Devuelve el jugador contrario
+    pub fn other(self) -> Player {
+        match self {
+            Player::First => Player::Second,
+            Player::Second => Player::First,
+        }
+    }
+
+    /// Índice del jugador en las tablas por jugador
+    pub fn index(self) -> usize {
+        match self {
+            Player::First => 0,
+            Player::Second => 1,
+        }
+    }
+
+    /// Símbolo con el que se representa al jugador
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Player::First => "X",
+            Player::Second => "O",
+        }
+    }
+}
+
+/// Función que cambia de jugador
+pub fn switch(op: Option<Player>) -> Option<Player> {
+    op.map(Player::other)
+}
+
+/// Estructura que representa una posición en el tablero
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Position {
+    pub x: u8,
+    pub y: u8,
+}
+
+impl Position {
+
+    /// Proporciona la posición total en el supertablero a partir de la posición en un subtablero
+    pub fn total_pos(pos1: Position, pos2: Position) -> Position {
+        Position { x: pos1.x * 3 + pos2.x, y: pos1.y * 3 + pos2.y }
+    }
+
+    pub fn partial_pos(total_pos: Position) -> (Position, Position) {
+        (Position { x: total_pos.x / 3, y: total_pos.y / 3 }, Position { x: total_pos.x % 3, y: total_pos.y % 3 })
+    }
+
+    /// Itera las nueve posiciones de un tablero de 3x3, fila a fila
+    pub fn all() -> impl Iterator<Item = Position> {
+        (0..3).flat_map(|x| (0..3).map(move |y| Position { x, y }))
+    }
+}
+
+/// Posición inalcanzable.
+///
+/// X = 3, Y = 3
+///
+pub const UNREACHABLE: Position = Position { x: 9, y: 9 };
+
+/// Subtablero central
+pub const CENTER: Position = Position { x: 1, y: 1 };
+
+/// Estructura que representa un tablero de 3x3
+#[derive(Clone, PartialEq, Debug)]
+pub struct TableroTres {
+    data: [[Option<Player>; 3]; 3],
+}
+
+impl Default for TableroTres {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TableroTres {
+
+    pub fn new() -> Self {
+        Self {
+            data: [[None; 3]; 3],
+        }
+    }
+
+    pub fn put(&mut self, pos: Position, value: Player) {
+        self.data[pos.x as usize][pos.y as usize] = Some(value);
+    }
+
+    pub fn reset(&mut self, pos: Position) {
+        self.data[pos.x as usize][pos.y as usize] = None;
+    }
+
+    pub fn get(&self, pos: Position) -> Option<Player> {
+        self.data[pos.x as usize][pos.y as usize]
+    }
+
+    pub fn check(&self) -> Option<Player> {
+
+        let check_line = |line: [Option<Player>; 3]| -> Option<Player> {
+            if line.iter().all(|x| x.is_some()) && line[0] == line[1] && line[1] == line[2] {
+                // Si todos los elementos son iguales y no son None, devuelvo el valor, el tablero se completó
+                line[0]
+            } else {
+                None
+            }
+        };
+
+        // Check rows
+        for i in 0..3 {
+            if let Some(r) = check_line(self.data[i]) {
+                return Some(r);
+            }
+        }
+
+        // Check columns
+        for i in 0..3 {
+            if let Some(r) = check_line([self.data[0][i], self.data[1][i], self.data[2][i]]) {
+                return Some(r);
+            }
+        }
+
+        // Check diagonals
+        if let Some(r) = check_line([self.data[0][0], self.data[1][1], self.data[2][2]]) {
+            return Some(r);
+        }
+        if let Some(r) = check_line([self.data[0][2], self.data[1][1], self.data[2][0]]) {
+            return Some(r);
+        }
+
+        None
+    }
+
+    pub fn playable(&self) -> bool {
+        self.data.iter().any(|x| x.iter().any(|y| y.is_none()))
+    }
+
+
+}
+
+
+/// Estructura que representa un supertablero de 3x3
+#[derive(Clone, PartialEq, Debug)]
+pub struct TableroSuperTres {
+    tablero: [[Result<Player, TableroTres>; 3]; 3],
+}
+
+impl Default for TableroSuperTres {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TableroSuperTres {
+
+    pub fn new() -> Self {
+
+        let tablero = [
+            [Err(TableroTres::new()), Err(TableroTres::new()), Err(TableroTres::new())],
+            [Err(TableroTres::new()), Err(TableroTres::new()), Err(TableroTres::new())],
+            [Err(TableroTres::new()), Err(TableroTres::new()), Err(TableroTres::new())],
+        ];
+
+        Self {
+            tablero,
+        }
+    }
+
+    pub fn get(&self, pos1: Position) -> Result<&Player, &TableroTres> {
+        self.tablero[pos1.x as usize][pos1.y as usize].as_ref()
+    }
+
+    fn get_mut(&mut self, pos1: Position) -> Result<&mut Player, &mut TableroTres> {
+        self.tablero[pos1.x as usize][pos1.y as usize].as_mut()
+    }
+
+    pub fn put(&mut self, pos1: Position, pos2: Position, value: Player) {
+        let tab = self.get_mut(pos1);
+
+        match tab {
+            Err(tab3x3) => {
+                // Si tengo un tablero, lo modifico
+                tab3x3.put(pos2, value);
+            },
+            Ok(_) => {
+                // Si tengo un valor, el tablero ya se completó, no hago nada
+            },
+        }
+
+    }
+
+    pub fn reset(&mut self, pos1: Position, pos2: Position) {
+        let tab = self.get_mut(pos1);
+
+        match tab {
+            Err(tab3x3) => {
+                // Si tengo un tablero, lo modifico
+                tab3x3.reset(pos2);
+            },
+            Ok(_) => {
+                // Si tengo un valor, el tablero ya se completó, no hago nada
+            },
+        }
+    }
+
+    /// Chequea si el supertablero está completo.
+    /// Si el supertablero está completo, devuelve el valor.
+    ///
+    /// Los subtableros se chequean y si están completos, se cambian por el valor
+    pub fn check(&mut self) -> Option<Player> {
+
+        // Primero chequeo cada subtablero por si está completo y sustituyo dicho subtablero del supertablero
+        // por el valor ganador
+        for i in 0..3 {
+            for j in 0..3 {
+                if let Err(tab) = &self.tablero[i][j] {
+                    if let Some(value) = tab.check() {
+                        self.tablero[i][j] = Ok(value);
+                    }
+                }
+            }
+        }
+
+        // Después, chequeo si el supertablero está completo y devuelvo el valor ganador
+        self.winner()
+    }
+
+    /// Devuelve el ganador del supertablero sin modificar los subtableros
+    pub fn winner(&self) -> Option<Player> {
+
+        let check_line = |line: &[Result<Player, TableroTres>; 3]| -> Option<Player> {
+            if line.iter().all(|x| x.is_ok()) {
+                let first_value = line[0].as_ref().ok()?;
+                if line.iter().all(|x| x.as_ref().ok() == Some(first_value)) {
+                    // Si todos los elementos son iguales y no son None, devuelvo el valor, el tablero se completó
+                    return Some(*first_value);
+                }
+            }
+            None
+        };
+
+        // Check rows
+        for i in 0..3 {
+            if let Some(r) = check_line(&self.tablero[i]) {
+                return Some(r);
+            }
+        }
+
+        // Check columns
+        for i in 0..3 {
+            if let Some(r) = check_line(&[self.tablero[0][i].clone(), self.tablero[1][i].clone(), self.tablero[2][i].clone()]) {
+                return Some(r);
+            }
+        }
+
+        // Check diagonals
+        if let Some(r) = check_line(&[self.tablero[0][0].clone(), self.tablero[1][1].clone(), self.tablero[2][2].clone()]) {
+            return Some(r);
+        }
+        if let Some(r) = check_line(&[self.tablero[0][2].clone(), self.tablero[1][1].clone(), self.tablero[2][0].clone()]) {
+            return Some(r);
+        }
+
+        None
+
+    }
+
+}
+
+
+/// Supertablero equivalente a uno en máscaras de bits
+impl From<&Bitboard> for TableroSuperTres {
+    fn from(bits: &Bitboard) -> Self {
+        let mut tablero = TableroSuperTres::new();
+
+        for pos1 in Position::all() {
+            let board = bitboard::index(pos1);
+
+            match bits.owner(board) {
+                Some(player) => {
+                    tablero.tablero[pos1.x as usize][pos1.y as usize] = Ok(player);
+                },
+                None => {
+                    for pos2 in Position::all() {
+                        if let Some(player) = bits.get(board, bitboard::index(pos2)) {
+                            tablero.put(pos1, pos2, player);
+                        }
+                    }
+                },
+            }
+        }
+
+        tablero
+    }
+}
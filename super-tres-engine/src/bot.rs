@@ -0,0 +1,104 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Jugadores automáticos construidos sobre el motor.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::game::{Game, Move};
+use crate::rng::Rng;
+use crate::search::{search_with, SearchLimits};
+use crate::solver::Solver;
+use crate::tt::TranspositionTable;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// BOTS
+
+
+/// Jugador automático
+pub trait Bot {
+
+    /// Nombre con el que se identifica al bot
+    fn name(&self) -> String;
+
+    /// Elige una jugada legal para la posición. Devuelve None si la partida terminó.
+    fn choose(&mut self, game: &Game) -> Option<Move>;
+}
+
+
+/// Bot que juega jugadas legales al azar
+pub struct RandomBot {
+    rng: Rng,
+}
+
+impl RandomBot {
+
+    pub fn new(seed: u64) -> Self {
+        Self { rng: Rng::new(seed) }
+    }
+}
+
+impl Bot for RandomBot {
+
+    fn name(&self) -> String {
+        "random".to_string()
+    }
+
+    fn choose(&mut self, game: &Game) -> Option<Move> {
+        self.rng.choose(&game.legal_moves()).copied()
+    }
+}
+
+
+/// Tamaño de la tabla de transposición de cada bot
+const BOT_MEGABYTES: usize = 16;
+
+
+/// Bot que busca con alfa-beta y, si tiene solucionador, juega perfecto en los finales
+pub struct MinimaxBot {
+    limits: SearchLimits,
+    tt: TranspositionTable,
+    solver: Option<Solver>,
+}
+
+impl MinimaxBot {
+
+    pub fn new(limits: SearchLimits) -> Self {
+        Self { limits, tt: TranspositionTable::new(BOT_MEGABYTES), solver: None }
+    }
+
+    /// Usa el solucionador exacto en las posiciones que puede resolver
+    pub fn with_solver(mut self, solver: Solver) -> Self {
+        self.solver = Some(solver);
+        self
+    }
+}
+
+impl Bot for MinimaxBot {
+
+    fn name(&self) -> String {
+        match &self.solver {
+            Some(solver) => format!("minimax-d{}-s{}", self.limits.depth, solver.max_empty),
+            None => format!("minimax-d{}", self.limits.depth),
+        }
+    }
+
+    fn choose(&mut self, game: &Game) -> Option<Move> {
+        let solved = self.solver.as_mut()
+            .and_then(|solver| solver.solve(game))
+            .and_then(|solution| solution.best);
+
+        solved.or_else(|| search_with(game, self.limits, &mut self.tt).best_move())
+    }
+}
@@ -0,0 +1,257 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v3                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Controles de tiempo de las partidas.
+// Se escriben como en el ajedrez: `5+3` son cinco minutos y tres segundos
+// de incremento por jugada, `5d3` cinco minutos con tres segundos de retardo
+// antes de que empiece a correr el reloj, y `none` sin reloj. El tiempo base
+// también se puede dar en segundos: `90s+2`.
+// El reloj de una partida no mide el tiempo por sí mismo: quien lo usa le
+// dice cuánto duró cada turno, así sirve igual en el servidor que en el
+// navegador.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::str::FromStr;
+use crate::board::Player;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Control de tiempo. Todos los tiempos van en segundos.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum TimeControl {
+
+    /// Sin reloj
+    #[default]
+    Unlimited,
+
+    /// Tiempo base más un incremento que se suma tras cada jugada
+    Fischer { base: u32, increment: u32 },
+
+    /// Tiempo base; en cada jugada el reloj no corre hasta pasado el retardo
+    Delay { base: u32, delay: u32 },
+}
+
+
+/// Reloj de una partida con control de tiempo
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Clock {
+    pub control: TimeControl,
+
+    /// Milisegundos que le quedan a X y a O sin contar el turno en curso
+    pub remaining: [u64; 2],
+}
+
+
+/// Error al leer un control de tiempo
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseTimeControlError(pub String);
+
+impl fmt::Display for ParseTimeControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid time control `{}`: expected none, <minutes>+<seconds> or <minutes>d<seconds>", self.0)
+    }
+}
+
+impl std::error::Error for ParseTimeControlError {}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TIME CONTROLS
+
+
+impl TimeControl {
+
+    /// Controles que ofrecen las interfaces
+    pub const PRESETS: [TimeControl; 6] = [
+        TimeControl::Unlimited,
+        TimeControl::Fischer { base: 60, increment: 0 },
+        TimeControl::Fischer { base: 180, increment: 2 },
+        TimeControl::Fischer { base: 300, increment: 3 },
+        TimeControl::Fischer { base: 600, increment: 5 },
+        TimeControl::Delay { base: 300, delay: 3 },
+    ];
+
+
+    /// Tiempo inicial de cada jugador. None si no hay reloj.
+    pub fn base(self) -> Option<u32> {
+        match self {
+            TimeControl::Unlimited => None,
+            TimeControl::Fischer { base, .. } | TimeControl::Delay { base, .. } => Some(base),
+        }
+    }
+}
+
+
+impl Clock {
+
+    /// Reloj al empezar la partida. None si no hay control de tiempo.
+    pub fn new(control: TimeControl) -> Option<Self> {
+        let base = u64::from(control.base()?) * 1000;
+        Some(Self { control, remaining: [base, base] })
+    }
+
+
+    /// Milisegundos que le quedan al jugador si su turno dura ya `elapsed`.
+    /// Con retardo, los primeros segundos del turno no cuentan.
+    pub fn left(&self, player: Player, elapsed: u64) -> u64 {
+        let used = match self.control {
+            TimeControl::Delay { delay, .. } => elapsed.saturating_sub(u64::from(delay) * 1000),
+            _ => elapsed,
+        };
+        self.remaining[player.index()].saturating_sub(used)
+    }
+
+
+    /// Para el reloj del jugador tras un turno de `elapsed` milisegundos.
+    /// Si el turno acabó en una jugada se le suma el incremento.
+    /// Devuelve false si se le acabó el tiempo.
+    pub fn stop(&mut self, player: Player, elapsed: u64, moved: bool) -> bool {
+        let left = self.left(player, elapsed);
+        let increment = match self.control {
+            TimeControl::Fischer { increment, .. } if moved && left > 0 => u64::from(increment) * 1000,
+            _ => 0,
+        };
+        self.remaining[player.index()] = left + increment;
+        left > 0
+    }
+}
+
+
+/// Escribe un tiempo base en minutos si son exactos y si no en segundos
+fn write_base(f: &mut fmt::Formatter<'_>, base: u32) -> fmt::Result {
+    if base.is_multiple_of(60) {
+        write!(f, "{}", base / 60)
+    } else {
+        write!(f, "{}s", base)
+    }
+}
+
+
+fn parse_base(s: &str) -> Option<u32> {
+    match s.strip_suffix('s') {
+        Some(seconds) => seconds.parse().ok(),
+        None => s.parse::<u32>().ok()?.checked_mul(60),
+    }
+}
+
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TimeControl::Unlimited => write!(f, "none"),
+            TimeControl::Fischer { base, increment } => {
+                write_base(f, base)?;
+                write!(f, "+{}", increment)
+            },
+            TimeControl::Delay { base, delay } => {
+                write_base(f, base)?;
+                write!(f, "d{}", delay)
+            },
+        }
+    }
+}
+
+
+impl FromStr for TimeControl {
+    type Err = ParseTimeControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseTimeControlError(s.to_string());
+
+        if s == "none" {
+            return Ok(TimeControl::Unlimited);
+        }
+
+        let control = if let Some((base, increment)) = s.split_once('+') {
+            TimeControl::Fischer { base: parse_base(base).ok_or_else(err)?, increment: increment.parse().map_err(|_| err())? }
+        } else if let Some((base, delay)) = s.split_once('d') {
+            TimeControl::Delay { base: parse_base(base).ok_or_else(err)?, delay: delay.parse().map_err(|_| err())? }
+        } else {
+            return Err(err());
+        };
+
+        // Una partida sin tiempo base se perdería en la primera jugada
+        match control.base() {
+            Some(0) => Err(err()),
+            _ => Ok(control),
+        }
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Los controles se leen y se vuelven a escribir igual
+    #[test]
+    fn parse_and_display_round_trip() {
+        for (text, control) in [
+            ("none", TimeControl::Unlimited),
+            ("5+3", TimeControl::Fischer { base: 300, increment: 3 }),
+            ("5d3", TimeControl::Delay { base: 300, delay: 3 }),
+            ("90s+2", TimeControl::Fischer { base: 90, increment: 2 }),
+            ("45sd0", TimeControl::Delay { base: 45, delay: 0 }),
+        ] {
+            assert_eq!(text.parse(), Ok(control));
+            assert_eq!(control.to_string(), text);
+        }
+
+        for control in TimeControl::PRESETS {
+            assert_eq!(control.to_string().parse(), Ok(control));
+        }
+    }
+
+    /// Los minutos exactos dados en segundos se escriben en minutos
+    #[test]
+    fn whole_minutes_display_in_minutes() {
+        assert_eq!("120s+1".parse::<TimeControl>().unwrap().to_string(), "2+1");
+    }
+
+    #[test]
+    fn invalid_time_controls() {
+        for text in ["", "5", "none+3", "0+3", "0sd2", "5+", "+3", "5+x", "s+3", "5x3", "-5+3"] {
+            assert_eq!(text.parse::<TimeControl>(), Err(ParseTimeControlError(text.to_string())), "{}", text);
+        }
+    }
+
+    /// El incremento se suma al jugar a tiempo y el retardo no cuenta
+    #[test]
+    fn increment_and_delay() {
+        let mut fischer = Clock::new(TimeControl::Fischer { base: 60, increment: 2 }).unwrap();
+        assert!(fischer.stop(Player::First, 10_000, true));
+        assert_eq!(fischer.remaining, [52_000, 60_000]);
+        assert!(!fischer.stop(Player::Second, 60_000, true));
+        assert_eq!(fischer.remaining, [52_000, 0]);
+
+        let delay = Clock::new(TimeControl::Delay { base: 60, delay: 3 }).unwrap();
+        assert_eq!(delay.left(Player::First, 2_000), 60_000);
+        assert_eq!(delay.left(Player::First, 5_000), 58_000);
+
+        assert_eq!(Clock::new(TimeControl::Unlimited), None);
+    }
+}
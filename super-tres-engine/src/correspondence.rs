@@ -0,0 +1,292 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Códigos de jugada para jugar por correspondencia, sin servidor.
+// Cada jugada confirmada se convierte en un código corto que el jugador
+// manda a su rival por donde quiera:
+//
+//     K7QM2P-12-e5-3f9a2c
+//
+// Son el identificador de la partida, el número de la jugada, la jugada y
+// una comprobación de la partida entera hasta ella. Quien recibe el código
+// solo lo aplica si es de su partida, es la siguiente jugada, es legal y la
+// comprobación coincide con sus propias jugadas; así se detectan los códigos
+// mal copiados, los repetidos y los que se saltan una jugada.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::str::FromStr;
+use crate::game::{Game, Move};
+use crate::rng::Rng;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Letras de los identificadores de partida, sin las que se confunden entre sí
+const ID_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Longitud de los identificadores de partida
+const ID_LENGTH: usize = 6;
+
+/// Bits de la comprobación, que se escribe en hexadecimal
+const CHECK_BITS: u32 = 24;
+
+
+/// Código de una jugada de una partida por correspondencia
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MoveCode {
+
+    /// Identificador de la partida
+    pub game: String,
+
+    /// Número de la jugada, empezando en 1
+    pub ply: usize,
+
+    pub mv: Move,
+
+    /// Comprobación del identificador y de todas las jugadas hasta esta
+    pub check: u32,
+}
+
+
+/// Motivo por el que no se puede aplicar un código
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MoveCodeError {
+
+    /// El texto no tiene la forma de un código
+    Malformed(String),
+
+    /// El código es de otra partida
+    OtherGame(String),
+
+    /// El código es de otra jugada: esperada y recibida
+    OutOfOrder { expected: usize, found: usize },
+
+    IllegalMove(Move),
+
+    /// La comprobación no coincide con las jugadas de la partida
+    Mismatch,
+}
+
+impl fmt::Display for MoveCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveCodeError::Malformed(code) => write!(f, "invalid move code `{}`", code),
+            MoveCodeError::OtherGame(game) => write!(f, "the code is for another game ({})", game),
+            MoveCodeError::OutOfOrder { expected, found } if found < expected => {
+                write!(f, "the code is for move {}, which was already played", found)
+            },
+            MoveCodeError::OutOfOrder { expected, found } => {
+                write!(f, "the code is for move {}, but the next move is {}: a code is missing", found, expected)
+            },
+            MoveCodeError::IllegalMove(mv) => write!(f, "illegal move {}", mv),
+            MoveCodeError::Mismatch => write!(f, "the code does not match the moves of this game"),
+        }
+    }
+}
+
+impl std::error::Error for MoveCodeError {}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MOVE CODES
+
+
+/// Identificador al azar para una partida nueva
+pub fn new_game_id(rng: &mut Rng) -> String {
+    (0..ID_LENGTH).map(|_| ID_ALPHABET[rng.below(ID_ALPHABET.len())] as char).collect()
+}
+
+
+/// Comprobación del identificador y las jugadas (FNV-1a, plegado a `CHECK_BITS`).
+/// No depende de la plataforma, así que coincide en todas las aplicaciones.
+fn check(game: &str, moves: &[Move]) -> u32 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+
+    feed(game.as_bytes());
+    for mv in moves {
+        feed(b" ");
+        feed(mv.to_string().as_bytes());
+    }
+
+    ((hash ^ (hash >> CHECK_BITS) ^ (hash >> (2 * CHECK_BITS))) & ((1 << CHECK_BITS) - 1)) as u32
+}
+
+
+impl MoveCode {
+
+    /// Código de la última jugada de la partida. None si no hay jugadas.
+    pub fn new(game: &str, moves: &[Move]) -> Option<Self> {
+        Some(Self {
+            game: game.to_string(),
+            ply: moves.len(),
+            mv: *moves.last()?,
+            check: check(game, moves),
+        })
+    }
+
+
+    /// Comprueba que el código es la siguiente jugada de la partida con esas jugadas
+    pub fn verify(&self, game: &str, moves: &[Move]) -> Result<(), MoveCodeError> {
+        if self.game != game {
+            return Err(MoveCodeError::OtherGame(self.game.clone()));
+        }
+        if self.ply != moves.len() + 1 {
+            return Err(MoveCodeError::OutOfOrder { expected: moves.len() + 1, found: self.ply });
+        }
+        if !Game::from_moves(moves).is_some_and(|position| position.is_legal(self.mv)) {
+            return Err(MoveCodeError::IllegalMove(self.mv));
+        }
+
+        let mut moves = moves.to_vec();
+        moves.push(self.mv);
+        if self.check != check(game, &moves) {
+            return Err(MoveCodeError::Mismatch);
+        }
+        Ok(())
+    }
+}
+
+
+impl fmt::Display for MoveCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{}-{:06x}", self.game, self.ply, self.mv, self.check)
+    }
+}
+
+
+impl FromStr for MoveCode {
+    type Err = MoveCodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let err = || MoveCodeError::Malformed(s.to_string());
+
+        let fields: Vec<&str> = s.split('-').collect();
+        let [game, ply, mv, check] = fields[..] else {
+            return Err(err());
+        };
+
+        let game = game.to_uppercase();
+        if game.len() != ID_LENGTH || !game.bytes().all(|byte| ID_ALPHABET.contains(&byte)) {
+            return Err(err());
+        }
+
+        Ok(Self {
+            game,
+            ply: ply.parse().ok().filter(|ply| *ply > 0).ok_or_else(err)?,
+            mv: mv.to_lowercase().parse().map_err(|_| err())?,
+            check: u32::from_str_radix(check, 16).ok().filter(|check| *check < 1 << CHECK_BITS).ok_or_else(err)?,
+        })
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const GAME: &str = "K7QM2P";
+
+    /// Primeras jugadas de una partida al azar
+    fn moves(count: usize) -> Vec<Move> {
+        let mut rng = Rng::new(46);
+        let mut game = Game::new();
+        for _ in 0..count {
+            let &mv = rng.choose(&game.legal_moves()).unwrap();
+            game.apply(mv);
+        }
+        game.moves().collect()
+    }
+
+    /// El código de cada jugada se escribe, se vuelve a leer y vale como siguiente jugada
+    #[test]
+    fn good_codes_round_trip_and_verify() {
+        let moves = moves(8);
+
+        for ply in 1..=moves.len() {
+            let code = MoveCode::new(GAME, &moves[..ply]).unwrap();
+            let parsed: MoveCode = code.to_string().parse().unwrap();
+
+            assert_eq!(parsed, code);
+            assert_eq!(parsed.ply, ply);
+            assert_eq!(parsed.verify(GAME, &moves[..ply - 1]), Ok(()));
+        }
+
+        // Las letras del código se pueden escribir en minúsculas
+        let code = MoveCode::new(GAME, &moves).unwrap();
+        assert_eq!(code.to_string().to_lowercase().parse(), Ok(code));
+        assert_eq!(MoveCode::new(GAME, &[]), None);
+    }
+
+    /// Un código mal copiado o con la comprobación de otra partida no coincide con la comprobación
+    #[test]
+    fn wrong_check_is_a_mismatch() {
+        let moves = moves(6);
+        let mut code = MoveCode::new(GAME, &moves).unwrap();
+
+        code.check ^= 1;
+        assert_eq!(code.verify(GAME, &moves[..5]), Err(MoveCodeError::Mismatch));
+
+        // La misma jugada con la comprobación de otra partida
+        let code = MoveCode { check: check("ABCDEF", &moves), ..MoveCode::new(GAME, &moves).unwrap() };
+        assert_eq!(code.verify(GAME, &moves[..5]), Err(MoveCodeError::Mismatch));
+    }
+
+    /// Los códigos repetidos, los que se saltan una jugada y los de otra partida no se aplican
+    #[test]
+    fn wrong_ply_or_game() {
+        let moves = moves(6);
+        let code = MoveCode::new(GAME, &moves[..4]).unwrap();
+
+        assert_eq!(code.verify(GAME, &moves[..4]), Err(MoveCodeError::OutOfOrder { expected: 5, found: 4 }));
+        assert_eq!(code.verify(GAME, &moves[..2]), Err(MoveCodeError::OutOfOrder { expected: 3, found: 4 }));
+        assert_eq!(code.verify("ABCDEF", &moves[..3]), Err(MoveCodeError::OtherGame(GAME.to_string())));
+    }
+
+    #[test]
+    fn malformed_codes() {
+        for text in [
+            "",
+            "K7QM2P-1-e5",
+            "K7QM2P-1-e5-3f9a2c-1",
+            "K7QM2-1-e5-3f9a2c",
+            "K7QM2I-1-e5-3f9a2c",
+            "K7QM2P-0-e5-3f9a2c",
+            "K7QM2P-x-e5-3f9a2c",
+            "K7QM2P-1-j5-3f9a2c",
+            "K7QM2P-1-e5-1000000",
+            "K7QM2P-1-e5-3g9a2c",
+        ] {
+            assert_eq!(text.parse::<MoveCode>(), Err(MoveCodeError::Malformed(text.to_string())), "{}", text);
+        }
+    }
+}
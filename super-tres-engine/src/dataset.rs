@@ -0,0 +1,297 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Posiciones de partidas con su resultado final y el valor de la búsqueda,
+// para ajustar la evaluación. Se guardan en CSV o en binario.
+//
+// CSV, una posición por línea tras la cabecera:
+//
+//     board,turn,active,result,score
+//     ....X....(81 casillas)...,O,e5,1-0,37
+//
+// El tablero son las 81 casillas por filas, de arriba abajo y de la `a` a la `i`,
+// con `X`, `O` o `.`. El subtablero activo se da por su casilla central (`b2`, `e5`...)
+// o `-` si se puede jugar en cualquiera. La puntuación es desde el punto de vista de X.
+//
+// Binario: la cabecera `STD1` y después 43 bytes por posición en little endian:
+// las 18 máscaras de casillas (u16, primero X), el turno (0 X, 1 O), el subtablero
+// activo (0 a 8, 9 cualquiera), el resultado (1 gana X, 0 tablas, -1 gana O)
+// y la puntuación (i32).
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use crate::bitboard::{self, Bitboard};
+use crate::board::{Player, Position};
+use crate::game::{Game, GameResult, Move};
+use crate::record::RecordResult;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Cabecera de los ficheros binarios
+pub const MAGIC: &[u8; 4] = b"STD1";
+
+/// Bytes de cada posición en binario
+pub const SAMPLE_BYTES: usize = 18 * 2 + 1 + 1 + 1 + 4;
+
+/// Cabecera de los ficheros CSV
+pub const CSV_HEADER: &str = "board,turn,active,result,score";
+
+
+/// Posición de una partida
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Sample {
+    pub board: Bitboard,
+    pub turn: Player,
+    pub active_table: Option<Position>,
+
+    /// Resultado final de la partida
+    pub result: GameResult,
+
+    /// Valor de la búsqueda desde el punto de vista de X
+    pub score: i32,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SAMPLES
+
+
+impl Sample {
+
+    /// Posición actual de una partida en curso
+    pub fn new(game: &Game, result: GameResult, score: i32) -> Option<Self> {
+        Some(Self {
+            board: *game.bitboard(),
+            turn: game.turn()?,
+            active_table: game.active_table(),
+            result,
+            score,
+        })
+    }
+
+    /// Partida en la posición, para evaluarla o buscar desde ella
+    pub fn game(&self) -> Option<Game> {
+        Game::from_position(self.board, self.turn, self.active_table)
+    }
+
+    /// Resultado para X: 1 si gana, 0.5 en tablas y 0 si pierde
+    pub fn outcome(&self) -> f64 {
+        RecordResult::Finished(self.result).points(Player::First).expect("finished result")
+    }
+
+    /// Línea CSV, sin salto de línea
+    pub fn to_csv(&self) -> String {
+        let mut cells = String::with_capacity(81);
+        for x in 0..9 {
+            for y in 0..9 {
+                let mv = Move::from_total(Position { x, y });
+                cells.push(match self.board.get(bitboard::index(mv.board), bitboard::index(mv.cell)) {
+                    Some(player) => player.symbol().chars().next().expect("symbol"),
+                    None => '.',
+                });
+            }
+        }
+
+        let active = match self.active_table {
+            Some(board) => Move::new(board, Position { x: 1, y: 1 }).to_string(),
+            None => "-".to_string(),
+        };
+
+        format!("{},{},{},{},{}", cells, self.turn.symbol(), active, RecordResult::Finished(self.result), self.score)
+    }
+
+    /// Lee una línea CSV
+    pub fn from_csv(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        let [cells, turn, active, result, score] = fields.as_slice() else {
+            return None;
+        };
+
+        if cells.len() != 81 {
+            return None;
+        }
+
+        let mut masks = [[0u16; 9]; 2];
+        for (i, c) in cells.chars().enumerate() {
+            let mv = Move::from_total(Position { x: (i / 9) as u8, y: (i % 9) as u8 });
+            let player = match c {
+                'X' => Player::First,
+                'O' => Player::Second,
+                '.' => continue,
+                _ => return None,
+            };
+            masks[player.index()][bitboard::index(mv.board)] |= bitboard::bit(mv.cell);
+        }
+
+        let turn = match *turn {
+            "X" => Player::First,
+            "O" => Player::Second,
+            _ => return None,
+        };
+
+        let active_table = match *active {
+            "-" => None,
+            center => Some(center.parse::<Move>().ok()?.board),
+        };
+
+        let RecordResult::Finished(result) = result.parse().ok()? else {
+            return None;
+        };
+
+        Some(Self { board: Bitboard::from_cells(masks), turn, active_table, result, score: score.parse().ok()? })
+    }
+
+    /// Bytes de la posición en binario
+    pub fn to_bytes(&self) -> [u8; SAMPLE_BYTES] {
+        let mut bytes = [0; SAMPLE_BYTES];
+        let mut i = 0;
+
+        for player in [Player::First, Player::Second] {
+            for board in 0..9 {
+                bytes[i..i + 2].copy_from_slice(&self.board.cells(player, board).to_le_bytes());
+                i += 2;
+            }
+        }
+
+        bytes[i] = self.turn.index() as u8;
+        bytes[i + 1] = self.active_table.map_or(9, |board| bitboard::index(board) as u8);
+        bytes[i + 2] = match self.result.winner() {
+            Some(Player::First) => 1,
+            None => 0,
+            Some(Player::Second) => -1i8 as u8,
+        };
+        bytes[i + 3..].copy_from_slice(&self.score.to_le_bytes());
+
+        bytes
+    }
+
+    /// Lee una posición en binario
+    pub fn from_bytes(bytes: &[u8; SAMPLE_BYTES]) -> Option<Self> {
+        let mut masks = [[0u16; 9]; 2];
+        let mut i = 0;
+
+        for player in masks.iter_mut() {
+            for mask in player.iter_mut() {
+                *mask = u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+                i += 2;
+            }
+        }
+
+        let turn = match bytes[i] {
+            0 => Player::First,
+            1 => Player::Second,
+            _ => return None,
+        };
+        let active_table = match bytes[i + 1] {
+            9 => None,
+            b @ 0..=8 => Some(bitboard::position(b as usize)),
+            _ => return None,
+        };
+        let result = match bytes[i + 2] as i8 {
+            1 => GameResult::Win(Player::First),
+            0 => GameResult::Draw,
+            -1 => GameResult::Win(Player::Second),
+            _ => return None,
+        };
+        let score = i32::from_le_bytes(bytes[i + 3..].try_into().ok()?);
+
+        Some(Self { board: Bitboard::from_cells(masks), turn, active_table, result, score })
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// FILES
+
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+
+/// Escribe las posiciones en CSV con cabecera
+pub fn write_csv(writer: &mut impl Write, samples: &[Sample]) -> io::Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for sample in samples {
+        writeln!(writer, "{}", sample.to_csv())?;
+    }
+    Ok(())
+}
+
+
+/// Lee posiciones en CSV
+pub fn read_csv(reader: impl BufRead) -> io::Result<Vec<Sample>> {
+    let mut samples = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() || line.trim() == CSV_HEADER {
+            continue;
+        }
+        samples.push(Sample::from_csv(&line).ok_or_else(|| invalid(&format!("invalid sample `{}`", line)))?);
+    }
+
+    Ok(samples)
+}
+
+
+/// Escribe las posiciones en binario con cabecera
+pub fn write_binary(writer: &mut impl Write, samples: &[Sample]) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    for sample in samples {
+        writer.write_all(&sample.to_bytes())?;
+    }
+    Ok(())
+}
+
+
+/// Lee posiciones en binario
+pub fn read_binary(mut reader: impl Read) -> io::Result<Vec<Sample>> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a binary dataset"));
+    }
+
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() % SAMPLE_BYTES != 0 {
+        return Err(invalid("truncated binary dataset"));
+    }
+
+    data.chunks_exact(SAMPLE_BYTES)
+        .map(|chunk| Sample::from_bytes(chunk.try_into().expect("exact chunk")).ok_or_else(|| invalid("invalid sample")))
+        .collect()
+}
+
+
+/// Lee un fichero de posiciones en cualquiera de los dos formatos
+pub fn read_file(path: impl AsRef<Path>) -> io::Result<Vec<Sample>> {
+    let data = fs::read(path)?;
+
+    if data.starts_with(MAGIC) {
+        read_binary(data.as_slice())
+    } else {
+        read_csv(data.as_slice())
+    }
+}
@@ -0,0 +1,284 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v3                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Evaluación heurística de una posición.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use crate::bitboard::{self, WIN_MASKS};
+use crate::board::{Player, CENTER};
+use crate::game::{Game, GameResult};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+/// Valor de una victoria. Las victorias más cercanas valen más.
+pub const WIN: i32 = 100_000;
+
+/// Las puntuaciones por encima de este umbral son victorias forzadas
+pub const WIN_THRESHOLD: i32 = WIN - 1_000;
+
+/// Número de pesos de la evaluación
+pub const FEATURES: usize = 6;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Pesos de los términos de la evaluación, en centésimas.
+///
+/// La evaluación es lineal en los pesos: la suma de cada peso por su término,
+/// lo que permite ajustarlos con posiciones de partidas.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Weights {
+
+    /// Subtablero ganado
+    pub won_board: i32,
+
+    /// Extra por ganar el subtablero central
+    pub center_board: i32,
+
+    /// Dos subtableros ganados en línea con el tercero todavía disputable
+    pub board_threat: i32,
+
+    /// Dos casillas en línea con la tercera libre dentro de un subtablero
+    pub cell_threat: i32,
+
+    /// Casilla central de un subtablero
+    pub center_cell: i32,
+
+    /// Penalización por conceder al rival la elección de subtablero
+    pub free_move: i32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            won_board: 100,
+            center_board: 40,
+            board_threat: 80,
+            cell_threat: 12,
+            center_cell: 4,
+            free_move: 25,
+        }
+    }
+}
+
+
+/// Pesos con los que evalúan las búsquedas de todo el programa
+static WEIGHTS: OnceLock<Weights> = OnceLock::new();
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// WEIGHTS
+
+
+impl Weights {
+
+    /// Nombres de los pesos en los ficheros, en el orden de `to_array`
+    pub const NAMES: [&'static str; FEATURES] = [
+        "won_board",
+        "center_board",
+        "board_threat",
+        "cell_threat",
+        "center_cell",
+        "free_move",
+    ];
+
+    pub fn to_array(&self) -> [i32; FEATURES] {
+        [self.won_board, self.center_board, self.board_threat, self.cell_threat, self.center_cell, self.free_move]
+    }
+
+    pub fn from_array(w: [i32; FEATURES]) -> Self {
+        Self {
+            won_board: w[0],
+            center_board: w[1],
+            board_threat: w[2],
+            cell_threat: w[3],
+            center_cell: w[4],
+            free_move: w[5],
+        }
+    }
+
+    /// Lee un fichero de pesos
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err: ParseWeightsError| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+}
+
+
+/// Fichero de pesos: una línea `nombre = valor` por peso; `#` empieza un comentario
+impl fmt::Display for Weights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in Self::NAMES.iter().zip(self.to_array()) {
+            writeln!(f, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Error al leer un fichero de pesos
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseWeightsError(pub String);
+
+impl fmt::Display for ParseWeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid weights line `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseWeightsError {}
+
+
+/// Los pesos que no aparecen conservan su valor por defecto
+impl FromStr for Weights {
+    type Err = ParseWeightsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Weights::default().to_array();
+
+        for line in s.lines() {
+            let content = line.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let err = || ParseWeightsError(line.to_string());
+            let (name, value) = content.split_once('=').ok_or_else(err)?;
+            let i = Self::NAMES.iter().position(|n| *n == name.trim()).ok_or_else(err)?;
+            weights[i] = value.trim().parse().map_err(|_| err())?;
+        }
+
+        Ok(Self::from_array(weights))
+    }
+}
+
+
+/// Fija los pesos de la evaluación para todo el programa.
+///
+/// Solo se puede hacer una vez y antes de la primera evaluación; si no se hace,
+/// se usan los pesos por defecto. Devuelve los pesos si ya estaban fijados.
+pub fn set_weights(weights: Weights) -> Result<(), Weights> {
+    WEIGHTS.set(weights)
+}
+
+
+/// Pesos con los que se evalúa
+pub fn weights() -> &'static Weights {
+    WEIGHTS.get_or_init(Weights::default)
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// EVALUATION
+
+
+/// Evalúa la posición desde el punto de vista de X con los pesos del programa.
+///
+/// Valores positivos favorecen a X y negativos a O.
+pub fn evaluate(game: &Game) -> i32 {
+    evaluate_with(game, weights())
+}
+
+
+/// Evalúa la posición desde el punto de vista de X con unos pesos dados
+pub fn evaluate_with(game: &Game, weights: &Weights) -> i32 {
+
+    match game.result() {
+        Some(GameResult::Win(player)) => return sign(player) * WIN,
+        Some(GameResult::Resigned(player) | GameResult::OutOfTime(player)) => return -sign(player) * WIN,
+        Some(GameResult::Draw | GameResult::AgreedDraw) => return 0,
+        None => {},
+    }
+
+    features(game).iter().zip(weights.to_array()).map(|(f, w)| f * w).sum()
+}
+
+
+/// Términos de la evaluación de una posición sin terminar, en el orden de `Weights::to_array`.
+///
+/// Cada término es la diferencia entre lo que tiene X y lo que tiene O.
+pub fn features(game: &Game) -> [i32; FEATURES] {
+    let board = game.bitboard();
+    let center = bitboard::index(CENTER);
+    let playable = board.playable_boards();
+    let mut features = [0; FEATURES];
+
+    for player in [Player::First, Player::Second] {
+        let won = board.won(player);
+        let s = sign(player);
+
+        // Subtableros ganados
+        features[0] += s * won.count_ones() as i32;
+        if won & (1 << center) != 0 {
+            features[1] += s;
+        }
+
+        // Amenazas en el supertablero
+        features[2] += s * threats(won, playable);
+
+        // Amenazas y casilla central dentro de los subtableros que se pueden jugar
+        for b in bitboard::bits(playable) {
+            let own = board.cells(player, b);
+            features[3] += s * threats(own, board.empty(b));
+            if own & (1 << center) != 0 {
+                features[4] += s;
+            }
+        }
+    }
+
+    // El jugador que puede elegir subtablero tiene ventaja
+    if game.active_table().is_none() {
+        if let Some(turn) = game.turn() {
+            features[5] = sign(turn);
+        }
+    }
+
+    features
+}
+
+
+/// Número de líneas con dos posiciones propias y la tercera todavía libre
+fn threats(own: u16, free: u16) -> i32 {
+    WIN_MASKS
+        .iter()
+        .filter(|line| (own & **line).count_ones() == 2 && free & **line & !own != 0)
+        .count() as i32
+}
+
+
+/// Signo de las puntuaciones de cada jugador
+pub fn sign(player: Player) -> i32 {
+    match player {
+        Player::First => 1,
+        Player::Second => -1,
+    }
+}
@@ -0,0 +1,521 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v6                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Estado de una partida: reglas, jugadas legales y deshacer.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::str::FromStr;
+use crate::bitboard::{self, Bitboard};
+use crate::board::{Player, Position, TableroSuperTres, CENTER, UNREACHABLE, switch};
+use crate::zobrist;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Jugada: subtablero y casilla dentro del subtablero
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Move {
+    pub board: Position,
+    pub cell: Position,
+}
+
+impl Move {
+
+    pub fn new(board: Position, cell: Position) -> Self {
+        Self { board, cell }
+    }
+
+    /// Posición total en el supertablero
+    pub fn total(&self) -> Position {
+        Position::total_pos(self.board, self.cell)
+    }
+
+    pub fn from_total(total: Position) -> Self {
+        let (board, cell) = Position::partial_pos(total);
+        Self { board, cell }
+    }
+}
+
+/// Notación de las jugadas.
+///
+/// Las columnas del tablero de 9x9 se nombran de la `a` a la `i` y las filas
+/// del `1` al `9` empezando por arriba: la casilla central es `e5`.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        write!(f, "{}{}", (b'a' + total.y) as char, total.x + 1)
+    }
+}
+
+/// Error al leer una jugada en notación
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseMoveError(pub String);
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid move `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+
+        match bytes {
+            [col @ b'a'..=b'i', row @ b'1'..=b'9'] => {
+                Ok(Move::from_total(Position { x: row - b'1', y: col - b'a' }))
+            },
+            _ => Err(ParseMoveError(s.to_string())),
+        }
+    }
+}
+
+
+/// Resultado de una partida terminada
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameResult {
+
+    /// Tres subtableros en línea
+    Win(Player),
+
+    /// Sin casillas libres
+    Draw,
+
+    /// El jugador abandonó la partida
+    Resigned(Player),
+
+    /// Tablas acordadas por los dos jugadores
+    AgreedDraw,
+
+    /// Al jugador se le acabó el tiempo
+    OutOfTime(Player),
+}
+
+
+impl GameResult {
+
+    /// Ganador de la partida, sea en el tablero, por abandono o por tiempo
+    pub fn winner(self) -> Option<Player> {
+        match self {
+            GameResult::Win(player) => Some(player),
+            GameResult::Resigned(player) | GameResult::OutOfTime(player) => Some(player.other()),
+            GameResult::Draw | GameResult::AgreedDraw => None,
+        }
+    }
+
+
+    /// La partida terminó antes de decidirse en el tablero
+    pub fn is_early(self) -> bool {
+        matches!(self, GameResult::Resigned(_) | GameResult::AgreedDraw | GameResult::OutOfTime(_))
+    }
+}
+
+
+/// Información necesaria para deshacer una jugada
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Undo {
+    mv: Move,
+    active_table: Option<Position>,
+    turn: Option<Player>,
+    result: Option<GameResult>,
+}
+
+
+/// Estado completo de una partida
+#[derive(Clone, PartialEq, Debug)]
+pub struct Game {
+
+    /// Supertablero de juego en máscaras de bits
+    board: Bitboard,
+
+    /// Jugador al que le toca jugar.
+    /// Si es None, el juego terminó.
+    turn: Option<Player>,
+
+    /// Subtablero activo.
+    /// Si es None, cualquier subtablero está activo.
+    active_table: Option<Position>,
+
+    /// Resultado de la partida, si terminó
+    result: Option<GameResult>,
+
+    /// Jugadas realizadas
+    history: Vec<Undo>,
+
+    /// Clave de Zobrist de la posición, actualizada en cada jugada
+    key: u64,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+
+    /// Partida nueva: empieza X en el subtablero central
+    pub fn new() -> Self {
+        Self {
+            board: Bitboard::new(),
+            turn: Some(Player::First),
+            active_table: Some(CENTER),
+            result: None,
+            history: Vec::new(),
+            key: zobrist::active(Some(CENTER)) ^ zobrist::turn(Some(Player::First)),
+        }
+    }
+
+    /// Partida obtenida aplicando una lista de jugadas desde el inicio
+    pub fn from_moves(moves: &[Move]) -> Option<Self> {
+        let mut game = Self::new();
+        for mv in moves {
+            if !game.is_legal(*mv) {
+                return None;
+            }
+            game.apply(*mv);
+        }
+        Some(game)
+    }
+
+    /// Partida sin historial en una posición dada, por ejemplo leída de un conjunto de datos.
+    /// Devuelve None si la posición ya terminó o el subtablero activo no se puede jugar.
+    pub fn from_position(board: Bitboard, turn: Player, active_table: Option<Position>) -> Option<Self> {
+        if board.winner().is_some() || board.playable_boards() == 0
+            || active_table.is_some_and(|active| active.x > 2 || active.y > 2 || !board.playable(bitboard::index(active))) {
+            return None;
+        }
+
+        let mut game = Self {
+            board,
+            turn: Some(turn),
+            active_table,
+            result: None,
+            history: Vec::new(),
+            key: 0,
+        };
+        game.key = game.compute_key();
+        Some(game)
+    }
+
+    /// Supertablero para mostrarlo, con los subtableros ganados sustituidos por su ganador
+    pub fn tablero(&self) -> TableroSuperTres {
+        TableroSuperTres::from(&self.board)
+    }
+
+    /// Supertablero en máscaras de bits
+    pub fn bitboard(&self) -> &Bitboard {
+        &self.board
+    }
+
+    pub fn turn(&self) -> Option<Player> {
+        self.turn
+    }
+
+    pub fn active_table(&self) -> Option<Position> {
+        self.active_table
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.result.and_then(GameResult::winner)
+    }
+
+    /// Jugadas realizadas desde el inicio
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.history.iter().map(|u| u.mv)
+    }
+
+    /// Número de jugadas realizadas
+    pub fn ply(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn last_move(&self) -> Option<Move> {
+        self.history.last().map(|u| u.mv)
+    }
+
+    /// Clave de la posición para las tablas de transposición.
+    ///
+    /// Dos partidas que llegan a la misma posición por distinto orden de jugadas
+    /// tienen la misma clave.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    /// Calcula la clave de Zobrist desde cero.
+    /// Sirve para comprobar la clave que se actualiza en cada jugada.
+    pub fn compute_key(&self) -> u64 {
+        let boards = Position::all()
+            .map(|board| match self.board.owner(bitboard::index(board)) {
+                Some(player) => zobrist::decided(board, player),
+                None => zobrist::sub_board(board, &self.board),
+            })
+            .fold(0, |key, k| key ^ k);
+
+        boards ^ zobrist::active(self.active_table) ^ zobrist::turn(self.turn)
+    }
+
+    /// Número de casillas libres en subtableros que todavía se pueden jugar
+    pub fn empty_cells(&self) -> usize {
+        (0..9).map(|board| self.board.empty(board).count_ones() as usize).sum()
+    }
+
+    /// Indica si el subtablero está habilitado para la siguiente jugada
+    pub fn board_enabled(&self, board: Position) -> bool {
+        self.turn.is_some() && self.active_table.is_none_or(|active| active == board)
+    }
+
+    pub fn is_legal(&self, mv: Move) -> bool {
+        mv.board.x < 3 && mv.board.y < 3 && mv.cell.x < 3 && mv.cell.y < 3
+            && self.board_enabled(mv.board)
+            && self.board.empty(bitboard::index(mv.board)) & bitboard::bit(mv.cell) != 0
+    }
+
+    /// Jugadas legales en la posición actual
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(81);
+
+        if self.turn.is_none() {
+            return moves;
+        }
+
+        let boards = match self.active_table {
+            Some(board) => bitboard::bit(board),
+            None => bitboard::FULL,
+        };
+
+        for board in bitboard::bits(boards) {
+            for cell in bitboard::bits(self.board.empty(board)) {
+                moves.push(Move { board: bitboard::position(board), cell: bitboard::position(cell) });
+            }
+        }
+
+        moves
+    }
+
+    /// Aplica una jugada legal para el jugador al que le toca
+    pub fn apply(&mut self, mv: Move) {
+        let player = self.turn.expect("apply on a finished game");
+
+        let undo = Undo {
+            mv,
+            active_table: self.active_table,
+            turn: self.turn,
+            result: self.result,
+        };
+
+        let settled = self.board.put(player, bitboard::index(mv.board), bitboard::index(mv.cell));
+        let cells_key = self.cells_key(mv, player, settled);
+
+        // Solo puede ganar el supertablero quien acaba de ganar un subtablero
+        if settled && bitboard::is_win(self.board.won(player)) {
+            // El juego terminó, el tablero activo es inalcanzable
+            self.active_table = Some(UNREACHABLE);
+            self.turn = None;
+            self.result = Some(GameResult::Win(player));
+        } else if self.board.playable_boards() == 0 {
+            // No quedan casillas libres: empate
+            self.active_table = Some(UNREACHABLE);
+            self.turn = None;
+            self.result = Some(GameResult::Draw);
+        } else {
+            // El subtablero en el que se juega es el que corresponde a la casilla jugada,
+            // salvo que esté completo, en cuyo caso se juega en cualquiera
+            self.active_table = if self.board.playable(bitboard::index(mv.cell)) { Some(mv.cell) } else { None };
+            self.turn = switch(self.turn);
+        }
+
+        self.key ^= cells_key
+            ^ zobrist::active(undo.active_table) ^ zobrist::active(self.active_table)
+            ^ zobrist::turn(undo.turn) ^ zobrist::turn(self.turn);
+
+        self.history.push(undo);
+    }
+
+    /// Termina la partida en curso sin jugar más, por abandono o por acuerdo
+    pub fn end(&mut self, result: GameResult) {
+        if self.turn.is_none() {
+            return;
+        }
+
+        self.key ^= zobrist::active(self.active_table) ^ zobrist::active(Some(UNREACHABLE))
+            ^ zobrist::turn(self.turn) ^ zobrist::turn(None);
+
+        self.active_table = Some(UNREACHABLE);
+        self.turn = None;
+        self.result = Some(result);
+    }
+
+    /// Deshace la última jugada. Devuelve la jugada deshecha.
+    pub fn undo(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        let player = undo.turn.expect("undo of a move without player");
+        let board = bitboard::index(undo.mv.board);
+
+        let settled = self.board.owner(board).is_some();
+        let cells_key = self.cells_key(undo.mv, player, settled);
+
+        self.key ^= cells_key
+            ^ zobrist::active(undo.active_table) ^ zobrist::active(self.active_table)
+            ^ zobrist::turn(undo.turn) ^ zobrist::turn(self.turn);
+
+        self.board.remove(player, board, bitboard::index(undo.mv.cell));
+        self.active_table = undo.active_table;
+        self.turn = undo.turn;
+        self.result = undo.result;
+
+        Some(undo.mv)
+    }
+
+    /// Número de posiciones alcanzables con exactamente `depth` jugadas.
+    /// Sirve para comprobar y medir la generación de jugadas.
+    pub fn perft(&mut self, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for mv in moves {
+            self.apply(mv);
+            nodes += self.perft(depth - 1);
+            self.undo();
+        }
+        nodes
+    }
+
+    /// Cambio en la clave de Zobrist por las casillas de una jugada ya marcada en el tablero.
+    ///
+    /// Si la jugada decide el subtablero, sus casillas dejan de contar y pasa a contar el subtablero ganado.
+    /// Las casillas del subtablero ya incluyen la jugada, así que su clave la cancela.
+    fn cells_key(&self, mv: Move, player: Player, settled: bool) -> u64 {
+        let cell = zobrist::cell(mv.total(), player);
+
+        if settled {
+            cell ^ zobrist::sub_board(mv.board, &self.board) ^ zobrist::decided(mv.board, player)
+        } else {
+            cell
+        }
+    }
+
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::rng::Rng;
+
+    fn game(moves: &[&str]) -> Game {
+        let moves: Vec<Move> = moves.iter().map(|mv| mv.parse().unwrap()).collect();
+        Game::from_moves(&moves).unwrap()
+    }
+
+    /// Posiciones desde el inicio: X empieza en el subtablero central
+    #[test]
+    fn perft_from_start() {
+        let counts: Vec<u64> = (0..=4).map(|depth| Game::new().perft(depth)).collect();
+        assert_eq!(counts, [1, 9, 80, 704, 6120]);
+    }
+
+    /// Deshacer todas las jugadas de una partida completa deja la posición inicial
+    #[test]
+    fn undo_restores_position() {
+        let mut rng = Rng::new(30);
+
+        for _ in 0..100 {
+            let mut game = Game::new();
+            while let Some(&mv) = rng.choose(&game.legal_moves()) {
+                game.apply(mv);
+            }
+            assert!(game.result().is_some() && game.legal_moves().is_empty());
+
+            while game.undo().is_some() {}
+            assert_eq!(*game.bitboard(), Bitboard::new());
+            assert_eq!((game.turn(), game.active_table(), game.result()), (Some(Player::First), Some(CENTER), None));
+        }
+    }
+
+    /// La clave incremental coincide con la calculada desde cero tras cada jugada
+    /// y cada jugada deshecha, en partidas aleatorias completas
+    #[test]
+    fn incremental_key_matches_computed_key() {
+        let mut rng = Rng::new(29);
+
+        for _ in 0..200 {
+            let mut game = Game::new();
+            let start = game.key();
+            assert_eq!(start, game.compute_key());
+
+            while let Some(&mv) = rng.choose(&game.legal_moves()) {
+                game.apply(mv);
+                assert_eq!(game.key(), game.compute_key(), "after {}", mv);
+
+                // De vez en cuando deshace la jugada y la vuelve a jugar
+                if rng.below(4) == 0 {
+                    let key = game.key();
+                    game.undo();
+                    assert_eq!(game.key(), game.compute_key(), "after undoing {}", mv);
+                    game.apply(mv);
+                    assert_eq!(game.key(), key);
+                }
+            }
+
+            while game.undo().is_some() {
+                assert_eq!(game.key(), game.compute_key());
+            }
+            assert_eq!(game.key(), start);
+        }
+    }
+
+    #[test]
+    fn transpositions_share_key() {
+        let key = game(&["d4", "b2", "f6", "h8"]).key();
+
+        assert_eq!(key, game(&["f6", "h8", "d4", "b2"]).key());
+        assert_ne!(key, game(&["d4", "b2", "f6"]).key());
+    }
+
+    #[test]
+    fn early_end_updates_key() {
+        let mut game = game(&["d4"]);
+        let key = game.key();
+
+        game.end(GameResult::Resigned(Player::Second));
+        assert_ne!(game.key(), key);
+        assert_eq!(game.key(), game.compute_key());
+    }
+}
@@ -0,0 +1,36 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Motor del super tres en raya: reglas, evaluación y búsqueda.
+// Se comparte entre la aplicación web y cualquier otra interfaz.
+
+
+
+pub mod board;
+pub mod bitboard;
+pub mod game;
+pub mod eval;
+pub mod search;
+pub mod review;
+pub mod solver;
+pub mod rng;
+pub mod bot;
+pub mod zobrist;
+pub mod tt;
+pub mod protocol;
+pub mod mcts;
+pub mod record;
+pub mod dataset;
+pub mod abi;
+pub mod ascii;
+pub mod svg;
+pub mod net;
+pub mod ruleset;
+pub mod clock;
+pub mod correspondence;
+pub mod archive;
+#[cfg(feature = "wasm-bots")]
+pub mod wasm_bot;
@@ -0,0 +1,192 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Búsqueda de Monte Carlo en árbol (UCT) con partidas aleatorias hasta el final.
+// No usa la evaluación heurística, así que sirve de rival independiente del minimax.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::board::Player;
+use crate::bot::Bot;
+use crate::game::{Game, GameResult, Move};
+use crate::rng::Rng;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Constante de exploración habitual de UCT
+pub const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+
+/// Nodo del árbol de búsqueda
+struct Node {
+
+    /// Jugada que lleva al nodo desde su padre
+    mv: Option<Move>,
+
+    /// Jugador que hizo esa jugada
+    player: Option<Player>,
+
+    parent: usize,
+    children: Vec<usize>,
+
+    /// Jugadas que todavía no tienen nodo
+    untried: Vec<Move>,
+
+    visits: u32,
+
+    /// Suma de resultados para el jugador que hizo la jugada: 1 gana, 0.5 empata
+    reward: f64,
+}
+
+impl Node {
+
+    fn new(mv: Option<Move>, player: Option<Player>, parent: usize, game: &Game) -> Self {
+        Self { mv, player, parent, children: Vec::new(), untried: game.legal_moves(), visits: 0, reward: 0.0 }
+    }
+}
+
+
+/// Bot de Monte Carlo: juega la jugada más visitada tras un número fijo de iteraciones
+pub struct MctsBot {
+    iterations: u32,
+    exploration: f64,
+    rng: Rng,
+}
+
+impl MctsBot {
+
+    pub fn new(iterations: u32, seed: u64) -> Self {
+        Self { iterations, exploration: DEFAULT_EXPLORATION, rng: Rng::new(seed) }
+    }
+
+    /// Cambia la constante de exploración de UCT
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+}
+
+impl Bot for MctsBot {
+
+    fn name(&self) -> String {
+        if self.exploration == DEFAULT_EXPLORATION {
+            format!("mcts-{}", self.iterations)
+        } else {
+            format!("mcts-{}-c{}", self.iterations, self.exploration)
+        }
+    }
+
+    fn choose(&mut self, game: &Game) -> Option<Move> {
+        game.turn()?;
+
+        let mut game = game.clone();
+        let mut tree = vec![Node::new(None, None, 0, &game)];
+
+        for _ in 0..self.iterations.max(1) {
+            let mut node = 0;
+            let mut depth = 0;
+
+            // Selección: se baja por los hijos con mejor UCT mientras el nodo esté completo
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select(&tree, node);
+                game.apply(tree[node].mv.expect("only the root has no move"));
+                depth += 1;
+            }
+
+            // Expansión: se añade un hijo con una jugada todavía sin probar
+            if !tree[node].untried.is_empty() {
+                let i = self.rng.below(tree[node].untried.len());
+                let mv = tree[node].untried.swap_remove(i);
+                let player = game.turn();
+                game.apply(mv);
+                depth += 1;
+
+                tree.push(Node::new(Some(mv), player, node, &game));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+
+            // Simulación: partida aleatoria hasta el final
+            let result = self.playout(&mut game);
+
+            // Retropropagación
+            loop {
+                let n = &mut tree[node];
+                n.visits += 1;
+                n.reward += reward(result, n.player);
+                if node == 0 {
+                    break;
+                }
+                node = n.parent;
+            }
+
+            for _ in 0..depth {
+                game.undo();
+            }
+        }
+
+        tree[0].children.iter()
+            .max_by_key(|c| tree[**c].visits)
+            .and_then(|c| tree[*c].mv)
+    }
+}
+
+
+impl MctsBot {
+
+    /// Hijo con mayor valor UCT
+    fn select(&self, tree: &[Node], node: usize) -> usize {
+        let log_visits = (tree[node].visits as f64).ln();
+
+        let uct = |child: usize| {
+            let c = &tree[child];
+            let visits = c.visits as f64;
+            c.reward / visits + self.exploration * (log_visits / visits).sqrt()
+        };
+
+        *tree[node].children.iter()
+            .max_by(|a, b| uct(**a).total_cmp(&uct(**b)))
+            .expect("select on a node without children")
+    }
+
+    /// Juega al azar hasta el final, deshace lo jugado y devuelve el resultado
+    fn playout(&mut self, game: &mut Game) -> GameResult {
+        let mut plies = 0;
+
+        while game.result().is_none() {
+            let moves = game.legal_moves();
+            game.apply(moves[self.rng.below(moves.len())]);
+            plies += 1;
+        }
+
+        let result = game.result().expect("playout ends with a result");
+        for _ in 0..plies {
+            game.undo();
+        }
+        result
+    }
+}
+
+
+/// Recompensa de un resultado para el jugador que hizo la jugada del nodo
+fn reward(result: GameResult, player: Option<Player>) -> f64 {
+    match (result.winner(), player) {
+        (None, _) => 0.5,
+        (Some(winner), Some(player)) if winner == player => 1.0,
+        _ => 0.0,
+    }
+}
@@ -0,0 +1,614 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v8                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Protocolo de las salas de partidas en red entre la aplicación y el servidor.
+// Cada mensaje de WebSocket es una línea de texto; las jugadas van en la
+// notación de `Move`. El servidor valida las jugadas y manda la partida entera
+// tras cada cambio, así que los clientes nunca aplican jugadas por su cuenta.
+// Los clientes que no están en una sala reciben la lista de salas abiertas
+// cada vez que cambia. Los espectadores reciben la partida igual que los
+// jugadores, empezando por la partida entera al entrar. Un jugador que se
+// desconecta conserva su asiento durante un tiempo de gracia y, al volver con
+// su sesión, recibe de nuevo la partida entera. En las salas con control de
+// tiempo cada partida va seguida del reloj; los clientes lo descuentan por su
+// cuenta, pero quien decide si se acabó el tiempo es el servidor. Los
+// jugadores pueden entrar con una cuenta; las partidas entre dos cuentas
+// cuentan para su puntuación de Glicko-2.
+//
+// Del cliente al servidor:
+//
+//     list                     pide las salas abiertas
+//     login <nombre> [<clave>] entra con la cuenta de ese nombre, o la crea si no existe
+//     ratings                  pide la clasificación
+//     create [<reglas> <tiempo> <X|O|random>]
+//                              crea una sala y se sienta en ella con esas fichas
+//     join <sala>              se sienta en la sala con ese código
+//     watch <sala>             mira la partida de la sala sin jugar
+//     resume <token>           vuelve al asiento de esa sesión tras desconectarse
+//     move <jugada>            juega en la sala
+//     say <texto>              escribe en el chat de la sala
+//     react <emoji>            manda una de las reacciones rápidas
+//     offer draw|takeback      propone tablas o deshacer su última jugada
+//     accept draw|takeback     acepta la propuesta del rival
+//     decline draw|takeback    la rechaza
+//     resign                   abandona la partida
+//     leave                    deja la sala
+//
+// Del servidor al cliente:
+//
+//     rooms [<sala> <reglas> <tiempo> <X|O|->, ...]
+//                              salas abiertas con el asiento que queda libre
+//     joined <sala> <X|O> <reglas> <tiempo>
+//                              sentado en la sala con esas fichas
+//     watching <sala> <reglas> <tiempo>
+//                              mirando la partida de la sala
+//     session <token>          sesión del asiento, para volver a él con resume
+//     account <nombre> <clave> sesión iniciada con esa cuenta; con la clave se vuelve a entrar
+//     ratings [<nombre> <puntuación> <desviación> <partidas>, ...]
+//                              jugadores con partidas puntuadas, de mayor a menor puntuación
+//     closed                   la sala se cerró porque se fueron los jugadores
+//     state [<jugada> ...]     jugadas de la partida de la sala
+//     opponent joined|left     el rival se sentó o se fue
+//     opponent away <segundos> el rival se desconectó; se le guarda el asiento ese tiempo
+//     offered draw|takeback <X|O>
+//                              el jugador hizo esa propuesta
+//     declined draw|takeback <X|O>
+//                              el jugador rechazó la propuesta
+//     clock <ms X> <ms O> <X|O> <ms>|-
+//                              tiempo de cada jugador y, si corre un reloj, de quién
+//                              y cuánto lleva su turno; va tras state
+//     ended resigned <X|O>|agreed|time <X|O>
+//                              la partida terminó por abandono, por acuerdo o por tiempo;
+//                              va tras state.
+//                              También se entiende `ended won <X|O>` y `ended drawn`.
+//     chat <X|O|-> <texto>     mensaje del chat de un jugador o de un espectador (-)
+//     reaction <X|O|-> <emoji> reacción rápida
+//     notice <texto>           aviso de la sala: jugadas, subtableros ganados, resultado...
+//     error <texto>            la última petición no se pudo atender
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::str::FromStr;
+use crate::board::Player;
+use crate::clock::TimeControl;
+use crate::game::{GameResult, Move, ParseMoveError};
+use crate::ruleset::Ruleset;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+/// Reacciones rápidas que se pueden mandar a la sala
+pub const REACTIONS: [&str; 6] = ["👍", "👏", "😮", "😂", "🤔", "😢"];
+
+/// Longitud máxima de un mensaje del chat, en caracteres
+pub const MAX_CHAT_LENGTH: usize = 200;
+
+/// Longitud máxima del nombre de una cuenta, en caracteres
+pub const MAX_NAME_LENGTH: usize = 16;
+
+
+/// Indica si el nombre vale para una cuenta: de 2 a `MAX_NAME_LENGTH`
+/// letras, cifras, `_` o `-`
+pub fn is_valid_name(name: &str) -> bool {
+    (2..=MAX_NAME_LENGTH).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Reglas y control de tiempo con los que se crea una sala
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RoomSettings {
+    pub ruleset: Ruleset,
+    pub time_control: TimeControl,
+}
+
+
+/// Sala de la lista del vestíbulo
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RoomInfo {
+    pub code: String,
+    pub settings: RoomSettings,
+
+    /// Fichas con las que se juega al unirse. None si la sala está llena
+    /// y solo se puede mirar.
+    pub seat: Option<Player>,
+}
+
+
+/// Jugador de la clasificación
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RatingInfo {
+    pub name: String,
+
+    /// Puntuación y desviación de Glicko-2, redondeadas
+    pub rating: u32,
+    pub deviation: u32,
+
+    /// Partidas puntuadas que jugó
+    pub games: u32,
+}
+
+
+/// Propuesta de un jugador a la que tiene que responder el rival
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Offer {
+    Draw,
+
+    /// Deshacer la última jugada de quien lo pide
+    Takeback,
+}
+
+
+/// Mensaje del cliente al servidor
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ClientMessage {
+    List,
+
+    /// Sin clave solo se puede crear una cuenta nueva
+    Login { name: String, key: Option<String> },
+    Ratings,
+
+    /// Fichas del creador; None para sortearlas
+    Create { settings: RoomSettings, side: Option<Player> },
+    Join(String),
+    Watch(String),
+    Resume(String),
+    Move(Move),
+    Say(String),
+    React(String),
+    Offer(Offer),
+    Accept(Offer),
+    Decline(Offer),
+    Resign,
+    Leave,
+}
+
+
+/// Mensaje del servidor al cliente
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ServerMessage {
+    Rooms(Vec<RoomInfo>),
+    Joined { room: String, seat: Player, settings: RoomSettings },
+    Watching { room: String, settings: RoomSettings },
+    Session(String),
+    Account { name: String, key: String },
+    Ratings(Vec<RatingInfo>),
+    Closed,
+    State(Vec<Move>),
+    Opponent(bool),
+
+    /// Segundos que se guarda el asiento del rival desconectado
+    OpponentAway(u32),
+    Offered { offer: Offer, from: Player },
+    Declined { offer: Offer, by: Player },
+
+    /// Milisegundos que le quedan a X y a O sin contar el turno en curso, y
+    /// jugador cuyo reloj corre con los milisegundos que lleva su turno
+    Clock { remaining: [u64; 2], running: Option<(Player, u64)> },
+
+    /// Final de la partida por abandono, acuerdo o tiempo, que no se deduce de las jugadas
+    Ended(GameResult),
+
+    /// Mensaje del chat. `from` es None si lo escribe un espectador.
+    Chat { from: Option<Player>, text: String },
+    Reaction { from: Option<Player>, emoji: String },
+    Notice(String),
+    Error(String),
+}
+
+
+/// Error al leer un mensaje
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseMessageError {
+    Empty,
+    Unknown(String),
+    Invalid(String),
+    Move(ParseMoveError),
+}
+
+impl fmt::Display for ParseMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseMessageError::Empty => write!(f, "empty message"),
+            ParseMessageError::Unknown(name) => write!(f, "unknown message `{}`", name),
+            ParseMessageError::Invalid(name) => write!(f, "invalid arguments for `{}`", name),
+            ParseMessageError::Move(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseMessageError {}
+
+impl From<ParseMoveError> for ParseMessageError {
+    fn from(err: ParseMoveError) -> Self {
+        ParseMessageError::Move(err)
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// FORMAT
+
+
+/// Nombre del mensaje y el resto de la línea
+fn split(line: &str) -> Result<(&str, &str), ParseMessageError> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Err(ParseMessageError::Empty);
+    }
+    Ok(line.split_once(' ').map_or((line, ""), |(name, rest)| (name, rest.trim())))
+}
+
+
+fn parse_seat(s: &str) -> Option<Player> {
+    match s {
+        "X" => Some(Player::First),
+        "O" => Some(Player::Second),
+        _ => None,
+    }
+}
+
+
+impl fmt::Display for Offer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Offer::Draw => write!(f, "draw"),
+            Offer::Takeback => write!(f, "takeback"),
+        }
+    }
+}
+
+
+fn parse_offer(s: &str) -> Option<Offer> {
+    match s {
+        "draw" => Some(Offer::Draw),
+        "takeback" => Some(Offer::Takeback),
+        _ => None,
+    }
+}
+
+
+/// Propuesta y fichas de quien la hace o la rechaza
+fn parse_offer_seat(s: &str) -> Option<(Offer, Player)> {
+    let (offer, seat) = s.split_once(' ')?;
+    Some((parse_offer(offer)?, parse_seat(seat)?))
+}
+
+
+/// Fichas de quien habla en el chat; `-` para los espectadores
+fn speaker(from: Option<Player>) -> &'static str {
+    from.map_or("-", |player| player.symbol())
+}
+
+
+/// Quien habla en el chat y el resto de la línea
+fn parse_speaker(s: &str) -> Option<(Option<Player>, &str)> {
+    let (from, rest) = s.split_once(' ')?;
+    let from = match from {
+        "-" => None,
+        from => Some(parse_seat(from)?),
+    };
+    Some((from, rest))
+}
+
+
+/// Lee las reglas y el control de tiempo de los dos primeros campos
+fn parse_settings<'a>(fields: &mut impl Iterator<Item = &'a str>) -> Option<RoomSettings> {
+    Some(RoomSettings {
+        ruleset: fields.next()?.parse().ok()?,
+        time_control: fields.next()?.parse().ok()?,
+    })
+}
+
+
+impl fmt::Display for RoomSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.ruleset, self.time_control)
+    }
+}
+
+
+impl fmt::Display for RoomInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.code, self.settings, self.seat.map_or("-", |seat| seat.symbol()))
+    }
+}
+
+
+impl FromStr for RoomInfo {
+    type Err = ParseMessageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseMessageError::Invalid("rooms".to_string());
+        let mut fields = s.split_whitespace();
+
+        let code = fields.next().ok_or_else(invalid)?.to_string();
+        let settings = parse_settings(&mut fields).ok_or_else(invalid)?;
+        let seat = match fields.next().ok_or_else(invalid)? {
+            "-" => None,
+            seat => Some(parse_seat(seat).ok_or_else(invalid)?),
+        };
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(RoomInfo { code, settings, seat })
+    }
+}
+
+
+impl fmt::Display for RatingInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {} {}", self.name, self.rating, self.deviation, self.games)
+    }
+}
+
+
+impl FromStr for RatingInfo {
+    type Err = ParseMessageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseMessageError::Invalid("ratings".to_string());
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [name, rating, deviation, games] = fields[..] else {
+            return Err(invalid());
+        };
+
+        Ok(RatingInfo {
+            name: name.to_string(),
+            rating: rating.parse().map_err(|_| invalid())?,
+            deviation: deviation.parse().map_err(|_| invalid())?,
+            games: games.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+
+impl fmt::Display for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientMessage::List => write!(f, "list"),
+            ClientMessage::Login { name, key: Some(key) } => write!(f, "login {} {}", name, key),
+            ClientMessage::Login { name, key: None } => write!(f, "login {}", name),
+            ClientMessage::Ratings => write!(f, "ratings"),
+            ClientMessage::Create { settings, side } => {
+                let side = side.map_or("random", |side| side.symbol());
+                write!(f, "create {} {}", settings, side)
+            },
+            ClientMessage::Join(room) => write!(f, "join {}", room),
+            ClientMessage::Watch(room) => write!(f, "watch {}", room),
+            ClientMessage::Resume(token) => write!(f, "resume {}", token),
+            ClientMessage::Move(mv) => write!(f, "move {}", mv),
+            ClientMessage::Say(text) => write!(f, "say {}", text),
+            ClientMessage::React(emoji) => write!(f, "react {}", emoji),
+            ClientMessage::Offer(offer) => write!(f, "offer {}", offer),
+            ClientMessage::Accept(offer) => write!(f, "accept {}", offer),
+            ClientMessage::Decline(offer) => write!(f, "decline {}", offer),
+            ClientMessage::Resign => write!(f, "resign"),
+            ClientMessage::Leave => write!(f, "leave"),
+        }
+    }
+}
+
+
+impl FromStr for ClientMessage {
+    type Err = ParseMessageError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (name, rest) = split(line)?;
+        let invalid = || ParseMessageError::Invalid(name.to_string());
+
+        match name {
+            "list" if rest.is_empty() => Ok(ClientMessage::List),
+            "login" => {
+                let fields: Vec<&str> = rest.split_whitespace().collect();
+                match fields[..] {
+                    [name] if is_valid_name(name) => Ok(ClientMessage::Login { name: name.to_string(), key: None }),
+                    [name, key] if is_valid_name(name) => Ok(ClientMessage::Login { name: name.to_string(), key: Some(key.to_string()) }),
+                    _ => Err(invalid()),
+                }
+            },
+            "ratings" if rest.is_empty() => Ok(ClientMessage::Ratings),
+            "create" if rest.is_empty() => Ok(ClientMessage::Create { settings: RoomSettings::default(), side: Some(Player::First) }),
+            "create" => {
+                let mut fields = rest.split_whitespace();
+                let settings = parse_settings(&mut fields).ok_or_else(invalid)?;
+                let side = match fields.next() {
+                    Some("random") => None,
+                    Some(side) => Some(parse_seat(side).ok_or_else(invalid)?),
+                    None => return Err(invalid()),
+                };
+                if fields.next().is_some() {
+                    return Err(invalid());
+                }
+                Ok(ClientMessage::Create { settings, side })
+            },
+            "join" if !rest.is_empty() && !rest.contains(' ') => Ok(ClientMessage::Join(rest.to_uppercase())),
+            "watch" if !rest.is_empty() && !rest.contains(' ') => Ok(ClientMessage::Watch(rest.to_uppercase())),
+            "resume" if !rest.is_empty() && !rest.contains(' ') => Ok(ClientMessage::Resume(rest.to_string())),
+            "move" => Ok(ClientMessage::Move(rest.parse()?)),
+            "say" if !rest.is_empty() => Ok(ClientMessage::Say(rest.to_string())),
+            "react" if !rest.is_empty() => Ok(ClientMessage::React(rest.to_string())),
+            "offer" => Ok(ClientMessage::Offer(parse_offer(rest).ok_or_else(invalid)?)),
+            "accept" => Ok(ClientMessage::Accept(parse_offer(rest).ok_or_else(invalid)?)),
+            "decline" => Ok(ClientMessage::Decline(parse_offer(rest).ok_or_else(invalid)?)),
+            "resign" if rest.is_empty() => Ok(ClientMessage::Resign),
+            "leave" if rest.is_empty() => Ok(ClientMessage::Leave),
+            "list" | "ratings" | "join" | "watch" | "resume" | "say" | "react" | "resign" | "leave" => Err(invalid()),
+            _ => Err(ParseMessageError::Unknown(name.to_string())),
+        }
+    }
+}
+
+
+impl fmt::Display for ServerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerMessage::Rooms(rooms) => {
+                write!(f, "rooms")?;
+                for (i, room) in rooms.iter().enumerate() {
+                    write!(f, "{}{}", if i == 0 { " " } else { ", " }, room)?;
+                }
+                Ok(())
+            },
+            ServerMessage::Joined { room, seat, settings } => write!(f, "joined {} {} {}", room, seat.symbol(), settings),
+            ServerMessage::Watching { room, settings } => write!(f, "watching {} {}", room, settings),
+            ServerMessage::Session(token) => write!(f, "session {}", token),
+            ServerMessage::Account { name, key } => write!(f, "account {} {}", name, key),
+            ServerMessage::Ratings(ratings) => {
+                write!(f, "ratings")?;
+                for (i, rating) in ratings.iter().enumerate() {
+                    write!(f, "{}{}", if i == 0 { " " } else { ", " }, rating)?;
+                }
+                Ok(())
+            },
+            ServerMessage::Closed => write!(f, "closed"),
+            ServerMessage::State(moves) => {
+                write!(f, "state")?;
+                for mv in moves {
+                    write!(f, " {}", mv)?;
+                }
+                Ok(())
+            },
+            ServerMessage::Opponent(true) => write!(f, "opponent joined"),
+            ServerMessage::Opponent(false) => write!(f, "opponent left"),
+            ServerMessage::OpponentAway(seconds) => write!(f, "opponent away {}", seconds),
+            ServerMessage::Offered { offer, from } => write!(f, "offered {} {}", offer, from.symbol()),
+            ServerMessage::Declined { offer, by } => write!(f, "declined {} {}", offer, by.symbol()),
+            ServerMessage::Clock { remaining, running } => {
+                write!(f, "clock {} {}", remaining[0], remaining[1])?;
+                match running {
+                    Some((player, elapsed)) => write!(f, " {} {}", player.symbol(), elapsed),
+                    None => write!(f, " -"),
+                }
+            },
+            ServerMessage::Ended(GameResult::Resigned(player)) => write!(f, "ended resigned {}", player.symbol()),
+            ServerMessage::Ended(GameResult::AgreedDraw) => write!(f, "ended agreed"),
+            ServerMessage::Ended(GameResult::OutOfTime(player)) => write!(f, "ended time {}", player.symbol()),
+            // El resto de finales se deducen de las jugadas
+            ServerMessage::Ended(GameResult::Win(player)) => write!(f, "ended won {}", player.symbol()),
+            ServerMessage::Ended(GameResult::Draw) => write!(f, "ended drawn"),
+            ServerMessage::Chat { from, text } => write!(f, "chat {} {}", speaker(*from), text),
+            ServerMessage::Reaction { from, emoji } => write!(f, "reaction {} {}", speaker(*from), emoji),
+            ServerMessage::Notice(text) => write!(f, "notice {}", text),
+            ServerMessage::Error(text) => write!(f, "error {}", text),
+        }
+    }
+}
+
+
+impl FromStr for ServerMessage {
+    type Err = ParseMessageError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (name, rest) = split(line)?;
+        let invalid = || ParseMessageError::Invalid(name.to_string());
+
+        match name {
+            "rooms" => Ok(ServerMessage::Rooms(
+                rest.split(',').filter(|room| !room.trim().is_empty()).map(str::parse).collect::<Result<_, _>>()?
+            )),
+            "joined" => {
+                let mut fields = rest.split_whitespace();
+                let room = fields.next().ok_or_else(invalid)?.to_string();
+                let seat = fields.next().and_then(parse_seat).ok_or_else(invalid)?;
+                let settings = parse_settings(&mut fields).ok_or_else(invalid)?;
+                if fields.next().is_some() {
+                    return Err(invalid());
+                }
+                Ok(ServerMessage::Joined { room, seat, settings })
+            },
+            "watching" => {
+                let mut fields = rest.split_whitespace();
+                let room = fields.next().ok_or_else(invalid)?.to_string();
+                let settings = parse_settings(&mut fields).ok_or_else(invalid)?;
+                if fields.next().is_some() {
+                    return Err(invalid());
+                }
+                Ok(ServerMessage::Watching { room, settings })
+            },
+            "session" if !rest.is_empty() && !rest.contains(' ') => Ok(ServerMessage::Session(rest.to_string())),
+            "account" => match rest.split_once(' ') {
+                Some((name, key)) if !key.contains(' ') => Ok(ServerMessage::Account { name: name.to_string(), key: key.to_string() }),
+                _ => Err(invalid()),
+            },
+            "ratings" => Ok(ServerMessage::Ratings(
+                rest.split(',').filter(|rating| !rating.trim().is_empty()).map(str::parse).collect::<Result<_, _>>()?
+            )),
+            "closed" if rest.is_empty() => Ok(ServerMessage::Closed),
+            "state" => Ok(ServerMessage::State(
+                rest.split_whitespace().map(str::parse).collect::<Result<Vec<Move>, _>>()?
+            )),
+            "opponent" => match rest {
+                "joined" => Ok(ServerMessage::Opponent(true)),
+                "left" => Ok(ServerMessage::Opponent(false)),
+                _ => match rest.strip_prefix("away ").map(str::parse) {
+                    Some(Ok(seconds)) => Ok(ServerMessage::OpponentAway(seconds)),
+                    _ => Err(invalid()),
+                },
+            },
+            "offered" => {
+                let (offer, from) = parse_offer_seat(rest).ok_or_else(invalid)?;
+                Ok(ServerMessage::Offered { offer, from })
+            },
+            "declined" => {
+                let (offer, by) = parse_offer_seat(rest).ok_or_else(invalid)?;
+                Ok(ServerMessage::Declined { offer, by })
+            },
+            "clock" => {
+                let fields: Vec<&str> = rest.split_whitespace().collect();
+                let remaining = |i: usize| fields[i].parse::<u64>().map_err(|_| invalid());
+                let running = match fields.len() {
+                    3 if fields[2] == "-" => None,
+                    4 => Some((parse_seat(fields[2]).ok_or_else(invalid)?, remaining(3)?)),
+                    _ => return Err(invalid()),
+                };
+                Ok(ServerMessage::Clock { remaining: [remaining(0)?, remaining(1)?], running })
+            },
+            "ended" => {
+                let result = match rest.split_once(' ') {
+                    Some(("resigned", seat)) => GameResult::Resigned(parse_seat(seat).ok_or_else(invalid)?),
+                    Some(("time", seat)) => GameResult::OutOfTime(parse_seat(seat).ok_or_else(invalid)?),
+                    Some(("won", seat)) => GameResult::Win(parse_seat(seat).ok_or_else(invalid)?),
+                    None if rest == "agreed" => GameResult::AgreedDraw,
+                    None if rest == "drawn" => GameResult::Draw,
+                    _ => return Err(invalid()),
+                };
+                Ok(ServerMessage::Ended(result))
+            },
+            "chat" => {
+                let (from, text) = parse_speaker(rest).ok_or_else(invalid)?;
+                Ok(ServerMessage::Chat { from, text: text.to_string() })
+            },
+            "reaction" => {
+                let (from, emoji) = parse_speaker(rest).ok_or_else(invalid)?;
+                Ok(ServerMessage::Reaction { from, emoji: emoji.to_string() })
+            },
+            "notice" => Ok(ServerMessage::Notice(rest.to_string())),
+            "error" => Ok(ServerMessage::Error(rest.to_string())),
+            "session" | "closed" => Err(invalid()),
+            _ => Err(ParseMessageError::Unknown(name.to_string())),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Protocolo de texto para motores, al estilo de UCI.
+// Una orden por línea; las jugadas van en la notación de `Move`.
+//
+//     uci                              -> id name ..., id author ..., option ..., uciok
+//     isready                          -> readyok
+//     ucinewgame
+//     setoption name <nombre> value <valor>
+//     position startpos [moves e5 d4 ...]
+//     go [depth N] [nodes N] [movetime MS] [infinite]
+//                                      -> info depth ... score ... pv ..., bestmove <jugada>
+//     stop
+//     perft N                          -> perft N <nodos>
+//     d                                -> la posición dibujada como en `ascii`
+//     quit
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use crate::board::Player;
+use crate::game::{Move, ParseMoveError};
+use crate::search::{forced_win, SearchResult};
+use crate::eval::sign;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Parámetros de `go`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct GoParams {
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+
+    /// Tiempo de búsqueda en milisegundos
+    pub movetime: Option<u64>,
+
+    /// Buscar hasta recibir `stop`
+    pub infinite: bool,
+}
+
+
+/// Orden que recibe el motor
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+    Uci,
+    IsReady,
+    NewGame,
+    SetOption { name: String, value: String },
+    Position(Vec<Move>),
+    Go(GoParams),
+    Stop,
+    Perft(u8),
+    Display,
+    Quit,
+}
+
+
+/// Error al leer una orden
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseCommandError {
+    Empty,
+    Unknown(String),
+    Invalid(String),
+    Move(ParseMoveError),
+}
+
+impl fmt::Display for ParseCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCommandError::Empty => write!(f, "empty command"),
+            ParseCommandError::Unknown(cmd) => write!(f, "unknown command `{}`", cmd),
+            ParseCommandError::Invalid(cmd) => write!(f, "invalid arguments for `{}`", cmd),
+            ParseCommandError::Move(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseCommandError {}
+
+impl From<ParseMoveError> for ParseCommandError {
+    fn from(err: ParseMoveError) -> Self {
+        ParseCommandError::Move(err)
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// PARSING
+
+
+impl Command {
+
+    /// Lee una línea del protocolo
+    pub fn parse(line: &str) -> Result<Command, ParseCommandError> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or(ParseCommandError::Empty)?;
+        let invalid = || ParseCommandError::Invalid(name.to_string());
+
+        match name {
+            "uci" => Ok(Command::Uci),
+            "isready" => Ok(Command::IsReady),
+            "ucinewgame" => Ok(Command::NewGame),
+            "stop" => Ok(Command::Stop),
+            "quit" => Ok(Command::Quit),
+            "d" => Ok(Command::Display),
+            "setoption" => {
+                // setoption name <nombre> value <valor>, donde el nombre puede tener espacios
+                let rest: Vec<&str> = words.collect();
+                let value_at = rest.iter().position(|w| *w == "value");
+                match (rest.first(), value_at) {
+                    (Some(&"name"), Some(v)) if v > 1 => Ok(Command::SetOption {
+                        name: rest[1..v].join(" "),
+                        value: rest[v + 1..].join(" "),
+                    }),
+                    (Some(&"name"), None) if rest.len() > 1 => Ok(Command::SetOption {
+                        name: rest[1..].join(" "),
+                        value: String::new(),
+                    }),
+                    _ => Err(invalid()),
+                }
+            },
+            "position" => {
+                if words.next() != Some("startpos") {
+                    return Err(invalid());
+                }
+                match words.next() {
+                    None => Ok(Command::Position(Vec::new())),
+                    Some("moves") => {
+                        let moves = words.map(str::parse).collect::<Result<Vec<Move>, _>>()?;
+                        Ok(Command::Position(moves))
+                    },
+                    Some(_) => Err(invalid()),
+                }
+            },
+            "go" => {
+                let mut params = GoParams::default();
+                while let Some(word) = words.next() {
+                    match word {
+                        "infinite" => params.infinite = true,
+                        "depth" => params.depth = Some(number(words.next()).ok_or_else(invalid)?),
+                        "nodes" => params.nodes = Some(number(words.next()).ok_or_else(invalid)?),
+                        "movetime" => params.movetime = Some(number(words.next()).ok_or_else(invalid)?),
+                        _ => return Err(invalid()),
+                    }
+                }
+                Ok(Command::Go(params))
+            },
+            "perft" => Ok(Command::Perft(number(words.next()).ok_or_else(invalid)?)),
+            _ => Err(ParseCommandError::Unknown(name.to_string())),
+        }
+    }
+}
+
+
+fn number<T: std::str::FromStr>(word: Option<&str>) -> Option<T> {
+    word?.parse().ok()
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// RESPONSES
+
+
+/// Línea `position` que lleva a la partida tras las jugadas dadas
+pub fn format_position(moves: &[Move]) -> String {
+    if moves.is_empty() {
+        "position startpos".to_string()
+    } else {
+        let moves: Vec<String> = moves.iter().map(Move::to_string).collect();
+        format!("position startpos moves {}", moves.join(" "))
+    }
+}
+
+
+/// Línea `info` de una iteración. La puntuación es relativa al jugador al que le toca:
+/// `score cp` en centésimas o `score mate` en jugadas, negativo si pierde.
+pub fn format_info(result: &SearchResult, turn: Player, millis: u128) -> String {
+    let score = match forced_win(result.score) {
+        Some((winner, plies)) if winner == turn => format!("mate {}", plies),
+        Some((_, plies)) => format!("mate -{}", plies),
+        None => format!("cp {}", sign(turn) * result.score),
+    };
+    let pv: Vec<String> = result.pv.iter().map(Move::to_string).collect();
+
+    format!("info depth {} score {} nodes {} time {} pv {}", result.depth, score, result.nodes, millis, pv.join(" "))
+}
+
+
+/// Línea `bestmove`. Sin jugada, `bestmove (none)`.
+pub fn format_bestmove(mv: Option<Move>) -> String {
+    match mv {
+        Some(mv) => format!("bestmove {}", mv),
+        None => "bestmove (none)".to_string(),
+    }
+}
+
+
+/// Lee una línea `bestmove`. Devuelve None si la línea no es `bestmove`.
+pub fn parse_bestmove(line: &str) -> Option<Option<Move>> {
+    let mut words = line.split_whitespace();
+    if words.next() != Some("bestmove") {
+        return None;
+    }
+    Some(words.next().and_then(|mv| mv.parse().ok()))
+}
@@ -0,0 +1,306 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Formato de texto para guardar partidas, parecido a PGN.
+// Primero van las etiquetas, una por línea, y tras una línea en blanco
+// las jugadas numeradas terminadas con el resultado:
+//
+//     [Event "Tournament"]
+//     [X "minimax-d6"]
+//     [O "mcts-5000"]
+//     [Result "1-0"]
+//
+//     1. e5 d4 2. b1 e1 3. f1 i1 ... 1-0
+//
+// El resultado es `1-0` si gana X, `0-1` si gana O, `1/2-1/2` en tablas
+// y `*` si la partida no terminó. Un fichero puede tener varias partidas seguidas.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::str::FromStr;
+use crate::board::Player;
+use crate::game::{Game, GameResult, Move, ParseMoveError};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Resultado de una partida tal y como se guarda
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordResult {
+    Finished(GameResult),
+    Unfinished,
+}
+
+impl RecordResult {
+
+    pub fn from_game(game: &Game) -> Self {
+        match game.result() {
+            Some(result) => RecordResult::Finished(result),
+            None => RecordResult::Unfinished,
+        }
+    }
+
+    /// Puntos del jugador: 1, 0.5 o 0. None si la partida no terminó.
+    pub fn points(self, player: Player) -> Option<f64> {
+        match self {
+            RecordResult::Finished(result) => Some(match result.winner() {
+                Some(winner) if winner == player => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            }),
+            RecordResult::Unfinished => None,
+        }
+    }
+}
+
+impl fmt::Display for RecordResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Solo se guarda el marcador, no cómo terminó la partida
+        match self {
+            RecordResult::Finished(result) => match result.winner() {
+                Some(Player::First) => write!(f, "1-0"),
+                Some(Player::Second) => write!(f, "0-1"),
+                None => write!(f, "1/2-1/2"),
+            },
+            RecordResult::Unfinished => write!(f, "*"),
+        }
+    }
+}
+
+impl FromStr for RecordResult {
+    type Err = ParseRecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(RecordResult::Finished(GameResult::Win(Player::First))),
+            "0-1" => Ok(RecordResult::Finished(GameResult::Win(Player::Second))),
+            "1/2-1/2" => Ok(RecordResult::Finished(GameResult::Draw)),
+            "*" => Ok(RecordResult::Unfinished),
+            _ => Err(ParseRecordError::Result(s.to_string())),
+        }
+    }
+}
+
+
+/// Partida guardada: etiquetas, jugadas y resultado
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameRecord {
+
+    /// Etiquetas en el orden en que se escriben, sin la del resultado
+    pub tags: Vec<(String, String)>,
+
+    pub moves: Vec<Move>,
+
+    /// Resultado. No siempre se deduce de las jugadas: un jugador puede perder
+    /// por no responder o por abandonar.
+    pub result: RecordResult,
+}
+
+
+/// Error al leer una partida guardada
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseRecordError {
+    Tag(String),
+    Move(ParseMoveError),
+    Result(String),
+    IllegalMove(usize, Move),
+    Empty,
+}
+
+impl fmt::Display for ParseRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRecordError::Tag(line) => write!(f, "invalid tag `{}`", line),
+            ParseRecordError::Move(err) => write!(f, "{}", err),
+            ParseRecordError::Result(s) => write!(f, "invalid result `{}`", s),
+            ParseRecordError::IllegalMove(ply, mv) => write!(f, "illegal move {} at ply {}", mv, ply + 1),
+            ParseRecordError::Empty => write!(f, "empty record"),
+        }
+    }
+}
+
+impl std::error::Error for ParseRecordError {}
+
+impl From<ParseMoveError> for ParseRecordError {
+    fn from(err: ParseMoveError) -> Self {
+        ParseRecordError::Move(err)
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// RECORDS
+
+
+/// Ancho máximo de las líneas de jugadas
+const LINE_WIDTH: usize = 80;
+
+
+impl GameRecord {
+
+    /// Partida con sus jugadas y su resultado
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            tags: Vec::new(),
+            moves: game.moves().collect(),
+            result: RecordResult::from_game(game),
+        }
+    }
+
+    /// Valor de una etiqueta
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Cambia o añade una etiqueta
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value,
+            None => self.tags.push((name.to_string(), value)),
+        }
+    }
+
+    /// Reproduce las jugadas desde el inicio
+    pub fn game(&self) -> Result<Game, ParseRecordError> {
+        let mut game = Game::new();
+        for (ply, mv) in self.moves.iter().enumerate() {
+            if !game.is_legal(*mv) {
+                return Err(ParseRecordError::IllegalMove(ply, *mv));
+            }
+            game.apply(*mv);
+        }
+        Ok(game)
+    }
+
+    /// Lee todas las partidas de un texto
+    pub fn parse_all(text: &str) -> Result<Vec<GameRecord>, ParseRecordError> {
+        let mut records = Vec::new();
+        let mut current = String::new();
+        let mut in_moves = false;
+
+        for line in text.lines() {
+            let line = line.trim();
+
+            // Una etiqueta después de las jugadas empieza otra partida
+            if line.starts_with('[') && in_moves {
+                records.push(current.parse()?);
+                current.clear();
+                in_moves = false;
+            } else if !line.is_empty() && !line.starts_with('[') {
+                in_moves = true;
+            }
+
+            current.push_str(line);
+            current.push('\n');
+        }
+
+        if !current.trim().is_empty() {
+            records.push(current.parse()?);
+        }
+
+        Ok(records)
+    }
+}
+
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        writeln!(f, "[Result \"{}\"]", self.result)?;
+        writeln!(f)?;
+
+        let mut tokens = Vec::new();
+        for (ply, mv) in self.moves.iter().enumerate() {
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            tokens.push(mv.to_string());
+        }
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+
+impl FromStr for GameRecord {
+    type Err = ParseRecordError;
+
+    /// Lee una partida. Si no tiene resultado, se deduce de las jugadas.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = Vec::new();
+        let mut moves = Vec::new();
+        let mut result = None;
+
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if line.starts_with('[') {
+                let (name, value) = parse_tag(line).ok_or_else(|| ParseRecordError::Tag(line.to_string()))?;
+                if name == "Result" {
+                    result = Some(value.parse()?);
+                } else {
+                    tags.push((name, value));
+                }
+                continue;
+            }
+
+            for token in line.split_whitespace() {
+                if token.ends_with('.') && token[..token.len() - 1].chars().all(|c| c.is_ascii_digit()) {
+                    continue;
+                }
+                match token.parse::<RecordResult>() {
+                    Ok(r) => result = Some(r),
+                    Err(_) => moves.push(token.parse()?),
+                }
+            }
+        }
+
+        if tags.is_empty() && moves.is_empty() && result.is_none() {
+            return Err(ParseRecordError::Empty);
+        }
+
+        let mut record = GameRecord { tags, moves, result: RecordResult::Unfinished };
+        let game = record.game()?;
+        record.result = result.unwrap_or_else(|| RecordResult::from_game(&game));
+
+        Ok(record)
+    }
+}
+
+
+/// Lee una etiqueta `[Nombre "valor"]`
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    Some((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
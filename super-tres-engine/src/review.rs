@@ -0,0 +1,266 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Análisis de una partida terminada: clasifica cada jugada según
+// lo que empeora la evaluación respecto a la mejor jugada del motor.
+// El análisis se puede hacer de una vez o jugada a jugada, para repartirlo
+// entre varios pasos.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::board::Player;
+use crate::eval::sign;
+use crate::game::{Game, Move};
+use crate::search::{search_with, SearchLimits, SearchResult};
+use crate::tt::TranspositionTable;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+/// Las puntuaciones se recortan a este valor para calcular pérdidas.
+/// Así perder una victoria forzada cuenta como un error grave y no como infinito.
+const LOSS_CAP: i32 = 1_000;
+
+/// Pérdida mínima para considerar una jugada imprecisa
+const INACCURACY: i32 = 50;
+
+/// Pérdida mínima para considerar una jugada un error
+const MISTAKE: i32 = 150;
+
+/// Pérdida mínima para considerar una jugada un error grave
+const BLUNDER: i32 = 300;
+
+/// Tamaño de la tabla compartida por las búsquedas de toda la partida
+const REVIEW_MEGABYTES: usize = 8;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Clasificación de una jugada
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveClass {
+    Best,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+
+    fn from_loss(loss: i32) -> Self {
+        if loss >= BLUNDER {
+            MoveClass::Blunder
+        } else if loss >= MISTAKE {
+            MoveClass::Mistake
+        } else if loss >= INACCURACY {
+            MoveClass::Inaccuracy
+        } else {
+            MoveClass::Best
+        }
+    }
+
+    /// Anotación habitual de la jugada
+    pub fn symbol(self) -> &'static str {
+        match self {
+            MoveClass::Best => "",
+            MoveClass::Inaccuracy => "?!",
+            MoveClass::Mistake => "?",
+            MoveClass::Blunder => "??",
+        }
+    }
+}
+
+
+/// Jugada anotada
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnnotatedMove {
+
+    /// Número de jugadas hechas antes de esta
+    pub ply: usize,
+
+    pub player: Player,
+
+    /// Jugada realizada
+    pub played: Move,
+
+    /// Mejor jugada según el motor
+    pub best: Option<Move>,
+
+    /// Evaluación antes de la jugada, desde el punto de vista de X
+    pub score_before: i32,
+
+    /// Evaluación después de la jugada, desde el punto de vista de X
+    pub score_after: i32,
+
+    /// Lo que empeora la posición para quien juega
+    pub loss: i32,
+
+    pub class: MoveClass,
+}
+
+
+/// Resumen de un jugador
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct PlayerSummary {
+    pub moves: usize,
+    pub inaccuracies: usize,
+    pub mistakes: usize,
+    pub blunders: usize,
+
+    /// Pérdida media por jugada
+    pub average_loss: i32,
+}
+
+
+/// Análisis completo de una partida
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameAnalysis {
+    pub moves: Vec<AnnotatedMove>,
+}
+
+impl GameAnalysis {
+
+    /// Jugadas imprecisas, errores y errores graves
+    pub fn flagged(&self) -> impl Iterator<Item = &AnnotatedMove> {
+        self.moves.iter().filter(|m| m.class != MoveClass::Best)
+    }
+
+    pub fn summary(&self, player: Player) -> PlayerSummary {
+        let mut summary = PlayerSummary::default();
+        let mut total_loss = 0;
+
+        for annotated in self.moves.iter().filter(|m| m.player == player) {
+            summary.moves += 1;
+            total_loss += annotated.loss;
+
+            match annotated.class {
+                MoveClass::Inaccuracy => summary.inaccuracies += 1,
+                MoveClass::Mistake => summary.mistakes += 1,
+                MoveClass::Blunder => summary.blunders += 1,
+                MoveClass::Best => {},
+            }
+        }
+
+        if summary.moves > 0 {
+            summary.average_loss = total_loss / summary.moves as i32;
+        }
+
+        summary
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ANALYSIS
+
+
+/// Analiza cada posición de la partida con el motor y anota las jugadas
+pub fn analyse_game(moves: &[Move], limits: SearchLimits) -> GameAnalysis {
+    let mut review = GameReview::new(moves, limits);
+    while review.step() {}
+    review.finish()
+}
+
+
+/// Análisis de una partida en curso, jugada a jugada
+pub struct GameReview {
+    moves: Vec<Move>,
+    limits: SearchLimits,
+
+    /// Posición antes de la siguiente jugada que se analiza
+    game: Game,
+
+    /// Tabla compartida por las búsquedas de toda la partida
+    tt: TranspositionTable,
+
+    /// Búsqueda sobre la posición antes de la siguiente jugada, si ya se hizo
+    before: Option<SearchResult>,
+    annotated: Vec<AnnotatedMove>,
+}
+
+impl GameReview {
+
+    /// Prepara el análisis de la partida. Todavía no se busca nada.
+    pub fn new(moves: &[Move], limits: SearchLimits) -> Self {
+        Self {
+            moves: moves.to_vec(),
+            limits,
+            game: Game::new(),
+            tt: TranspositionTable::new(REVIEW_MEGABYTES),
+            before: None,
+            annotated: Vec::with_capacity(moves.len()),
+        }
+    }
+
+
+    /// Jugadas analizadas y jugadas que hay que analizar en total
+    pub fn progress(&self) -> (usize, usize) {
+        (self.annotated.len(), self.moves.len())
+    }
+
+
+    /// Analiza la siguiente jugada. Devuelve si quedan jugadas por analizar.
+    pub fn step(&mut self) -> bool {
+        let Some(&mv) = self.moves.get(self.annotated.len()) else {
+            return false;
+        };
+        // Las jugadas que siguen a una partida terminada no se analizan
+        let Some(player) = self.game.turn() else {
+            self.moves.truncate(self.annotated.len());
+            return false;
+        };
+
+        let before = match self.before.take() {
+            Some(before) => before,
+            None => search_with(&self.game, self.limits, &mut self.tt),
+        };
+        self.game.apply(mv);
+        let after = search_with(&self.game, self.limits, &mut self.tt);
+
+        let best = before.best_move();
+        let loss = if best == Some(mv) {
+            0
+        } else {
+            let cap = |score: i32| sign(player) * score.clamp(-LOSS_CAP, LOSS_CAP);
+            (cap(before.score) - cap(after.score)).max(0)
+        };
+
+        self.annotated.push(AnnotatedMove {
+            ply: self.annotated.len(),
+            player,
+            played: mv,
+            best,
+            score_before: before.score,
+            score_after: after.score,
+            loss,
+            class: MoveClass::from_loss(loss),
+        });
+
+        self.before = Some(after);
+        self.annotated.len() < self.moves.len()
+    }
+
+
+    /// Análisis de las jugadas analizadas hasta ahora
+    pub fn finish(self) -> GameAnalysis {
+        GameAnalysis { moves: self.annotated }
+    }
+}
@@ -0,0 +1,117 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Reglas con las que se decide el resultado de una partida.
+// Todas comparten las jugadas legales de `Game`; solo cambia quién gana
+// cuando la partida termina sin tres subtableros en línea.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use crate::board::Player;
+use crate::game::{Game, GameResult};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Reglas de una partida
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Ruleset {
+
+    /// Gana quien consigue tres subtableros en línea; si no, tablas
+    #[default]
+    Standard,
+
+    /// Si nadie consigue tres en línea, gana quien ganó más subtableros
+    Majority,
+}
+
+
+/// Error al leer unas reglas
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseRulesetError(pub String);
+
+impl fmt::Display for ParseRulesetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown ruleset `{}`: expected standard or majority", self.0)
+    }
+}
+
+impl std::error::Error for ParseRulesetError {}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// RULESETS
+
+
+impl Ruleset {
+
+    pub const ALL: [Ruleset; 2] = [Ruleset::Standard, Ruleset::Majority];
+
+
+    /// Resultado de la partida con estas reglas. None si no terminó.
+    pub fn result(self, game: &Game) -> Option<GameResult> {
+        match (self, game.result()?) {
+            (Ruleset::Majority, GameResult::Draw) => {
+                let board = game.bitboard();
+                let x = board.won(Player::First).count_ones();
+                let o = board.won(Player::Second).count_ones();
+
+                Some(match x.cmp(&o) {
+                    Ordering::Greater => GameResult::Win(Player::First),
+                    Ordering::Less => GameResult::Win(Player::Second),
+                    Ordering::Equal => GameResult::Draw,
+                })
+            },
+            (_, result) => Some(result),
+        }
+    }
+
+
+    /// Explicación corta para las interfaces
+    pub fn description(self) -> &'static str {
+        match self {
+            Ruleset::Standard => "Three sub-boards in a row wins",
+            Ruleset::Majority => "Without three in a row, most sub-boards wins",
+        }
+    }
+}
+
+
+impl fmt::Display for Ruleset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ruleset::Standard => write!(f, "standard"),
+            Ruleset::Majority => write!(f, "majority"),
+        }
+    }
+}
+
+
+impl FromStr for Ruleset {
+    type Err = ParseRulesetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Ruleset::Standard),
+            "majority" => Ok(Ruleset::Majority),
+            _ => Err(ParseRulesetError(s.to_string())),
+        }
+    }
+}
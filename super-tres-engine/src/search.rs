@@ -0,0 +1,338 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v4                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Búsqueda alfa-beta con profundización iterativa, tabla de transposición
+// y variante principal.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::board::Player;
+use crate::eval::{evaluate, sign, WIN, WIN_THRESHOLD};
+use crate::game::{Game, GameResult, Move};
+use crate::tt::{Bound, TranspositionTable};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Límites de una búsqueda
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SearchLimits {
+
+    /// Profundidad máxima en jugadas
+    pub depth: u8,
+
+    /// Número máximo de nodos. La iteración que lo supera se descarta.
+    pub nodes: u64,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self { depth: 6, nodes: 200_000 }
+    }
+}
+
+
+/// Resultado de una búsqueda
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SearchResult {
+
+    /// Puntuación desde el punto de vista de X
+    pub score: i32,
+
+    /// Profundidad completada
+    pub depth: u8,
+
+    /// Variante principal, empezando por la mejor jugada
+    pub pv: Vec<Move>,
+
+    /// Nodos visitados
+    pub nodes: u64,
+}
+
+impl SearchResult {
+
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+}
+
+
+/// Control externo de una búsqueda en curso
+pub trait SearchObserver {
+
+    /// Se consulta periódicamente. Si devuelve true, la búsqueda se interrumpe
+    /// y se devuelve la última iteración completa.
+    fn should_stop(&self) -> bool {
+        false
+    }
+
+    /// Se llama cada vez que se completa una iteración
+    fn on_iteration(&mut self, _result: &SearchResult) {}
+}
+
+/// Observador que no interrumpe ni informa
+impl SearchObserver for () {}
+
+
+/// Tamaño de la tabla de las búsquedas que no reciben una
+const SEARCH_MEGABYTES: usize = 2;
+
+/// Cada cuántos nodos se pregunta al observador si hay que parar
+pub(crate) const STOP_CHECK_NODES: u64 = 1024;
+
+
+struct Searcher<'a> {
+    tt: &'a mut TranspositionTable,
+    observer: &'a mut dyn SearchObserver,
+    nodes: u64,
+    max_nodes: u64,
+    aborted: bool,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SEARCH
+
+
+/// Busca la mejor jugada de la posición con una tabla de transposición nueva
+pub fn search(game: &Game, limits: SearchLimits) -> SearchResult {
+    let mut tt = TranspositionTable::new(SEARCH_MEGABYTES);
+    search_with(game, limits, &mut tt)
+}
+
+
+/// Busca la mejor jugada de la posición reutilizando una tabla de transposición.
+///
+/// Los bots conservan su tabla entre jugadas para aprovechar lo buscado antes.
+pub fn search_with(game: &Game, limits: SearchLimits, tt: &mut TranspositionTable) -> SearchResult {
+    search_observed(game, limits, tt, &mut ())
+}
+
+
+/// Busca la mejor jugada informando al observador de cada iteración.
+/// El observador puede interrumpir la búsqueda, por ejemplo al acabarse el tiempo.
+pub fn search_observed(game: &Game, limits: SearchLimits, tt: &mut TranspositionTable, observer: &mut dyn SearchObserver) -> SearchResult {
+
+    let mut game = game.clone();
+    let mut result = SearchResult { score: evaluate(&game), ..SearchResult::default() };
+
+    let Some(turn) = game.turn() else {
+        return result;
+    };
+
+    tt.new_search();
+    let mut searcher = Searcher { tt, observer, nodes: 0, max_nodes: limits.nodes, aborted: false };
+
+    for depth in 1..=limits.depth.max(1) {
+        let mut pv = Vec::new();
+        let score = searcher.negamax(&mut game, depth, 0, -WIN - 1, WIN + 1, &mut pv, &result.pv);
+
+        // La primera iteración siempre se completa para tener alguna jugada
+        if searcher.aborted && depth > 1 {
+            break;
+        }
+
+        // Los cortes por la tabla acortan la variante; se completa con sus mejores jugadas
+        searcher.extend_pv(&mut game, &mut pv, depth);
+
+        result.score = sign(turn) * score;
+        result.depth = depth;
+        result.pv = pv;
+        result.nodes = searcher.nodes;
+        searcher.observer.on_iteration(&result);
+
+        if searcher.aborted {
+            break;
+        }
+
+        // Si se encontró una victoria forzada no hace falta seguir
+        if score.abs() >= WIN_THRESHOLD {
+            break;
+        }
+    }
+
+    result.nodes = searcher.nodes;
+    result
+}
+
+
+impl Searcher<'_> {
+
+    /// Negamax con poda alfa-beta. La puntuación es relativa al jugador al que le toca.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(&mut self, game: &mut Game, depth: u8, ply: i32, mut alpha: i32, beta: i32, pv: &mut Vec<Move>, hint: &[Move]) -> i32 {
+
+        self.nodes += 1;
+        if self.nodes > self.max_nodes
+            || (self.nodes.is_multiple_of(STOP_CHECK_NODES) && self.observer.should_stop()) {
+            self.aborted = true;
+        }
+
+        match (game.result(), game.turn()) {
+            // Si la partida terminó, ganó quien acaba de jugar.
+            // Los abandonos y las tablas acordadas no salen de jugar, así que aquí no aparecen.
+            (Some(GameResult::Win(_) | GameResult::Resigned(_) | GameResult::OutOfTime(_)), _) => return -(WIN - ply),
+            (Some(GameResult::Draw | GameResult::AgreedDraw), _) => return 0,
+            (None, Some(turn)) if depth == 0 || (self.aborted && ply > 0) => {
+                return sign(turn) * evaluate(game);
+            },
+            _ => {},
+        }
+
+        let key = game.key();
+        let original_alpha = alpha;
+        let mut first = hint.first().copied();
+
+        if let Some(entry) = self.tt.probe(key) {
+            // En la raíz siempre se busca para tener la variante principal
+            if ply > 0 && entry.depth >= depth {
+                let score = from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {},
+                }
+            }
+            first = first.or(entry.best);
+        }
+
+        let mut moves = game.legal_moves();
+
+        // La jugada de la variante anterior o de la tabla se explora primero
+        let hinted = first.and_then(|h| moves.iter().position(|m| *m == h));
+        if let Some(i) = hinted {
+            moves.swap(0, i);
+        }
+        let follows_hint = hinted.is_some() && hint.first().copied() == first;
+
+        let mut best = -WIN - 1;
+        let mut best_move = None;
+        let mut child_pv = Vec::new();
+
+        for (i, mv) in moves.into_iter().enumerate() {
+            let child_hint = if i == 0 && follows_hint { &hint[1..] } else { &[] };
+
+            game.apply(mv);
+            child_pv.clear();
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut child_pv, child_hint);
+            game.undo();
+
+            if score > best {
+                best = score;
+                best_move = Some(mv);
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(&child_pv);
+            }
+
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        // Lo encontrado en una búsqueda interrumpida no es fiable
+        if !self.aborted {
+            let bound = if best <= original_alpha {
+                Bound::Upper
+            } else if best >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.tt.store(key, depth, to_tt(best, ply), bound, best_move);
+        }
+
+        best
+    }
+
+
+    /// Alarga la variante principal con las mejores jugadas guardadas en la tabla
+    fn extend_pv(&self, game: &mut Game, pv: &mut Vec<Move>, depth: u8) {
+        for mv in pv.iter() {
+            game.apply(*mv);
+        }
+
+        let mut applied = pv.len();
+        while pv.len() < depth as usize {
+            match self.tt.probe(game.key()).and_then(|entry| entry.best) {
+                Some(mv) if game.is_legal(mv) => {
+                    game.apply(mv);
+                    pv.push(mv);
+                    applied += 1;
+                },
+                _ => break,
+            }
+        }
+
+        for _ in 0..applied {
+            game.undo();
+        }
+    }
+}
+
+
+/// Las victorias se guardan relativas a la posición y no a la raíz de la búsqueda
+fn to_tt(score: i32, ply: i32) -> i32 {
+    if score >= WIN_THRESHOLD {
+        score + ply
+    } else if score <= -WIN_THRESHOLD {
+        score - ply
+    } else {
+        score
+    }
+}
+
+
+fn from_tt(score: i32, ply: i32) -> i32 {
+    if score >= WIN_THRESHOLD {
+        score - ply
+    } else if score <= -WIN_THRESHOLD {
+        score + ply
+    } else {
+        score
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SCORES
+
+
+/// Si la puntuación es una victoria forzada, devuelve el ganador y
+/// el número de jugadas hasta el final
+pub fn forced_win(score: i32) -> Option<(Player, i32)> {
+    if score >= WIN_THRESHOLD {
+        Some((Player::First, WIN - score))
+    } else if score <= -WIN_THRESHOLD {
+        Some((Player::Second, WIN + score))
+    } else {
+        None
+    }
+}
+
+
+/// Formatea una puntuación para mostrarla: `+1.25`, `-0.40` o `X in 3`
+pub fn format_score(score: i32) -> String {
+    match forced_win(score) {
+        Some((player, plies)) => format!("{} in {}", player.symbol(), plies),
+        None => format!("{:+.2}", score as f64 / 100.0),
+    }
+}
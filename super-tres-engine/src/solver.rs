@@ -0,0 +1,367 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v5                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Resolución exacta de posiciones finales: explora el árbol completo
+// y devuelve el valor teórico de la posición con juego perfecto.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use crate::board::Player;
+use crate::eval::{sign, WIN};
+use crate::game::{Game, GameResult, Move};
+use crate::search::{SearchObserver, STOP_CHECK_NODES};
+use crate::tt::{Bound, TranspositionTable};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Valor teórico de una posición para el jugador al que le toca,
+/// con el número de jugadas hasta el final de la partida
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Value {
+    Win(u32),
+    Draw,
+    Loss(u32),
+}
+
+impl Value {
+
+    /// Valor a partir de una puntuación exacta del solucionador
+    fn from_score(score: i32) -> Self {
+        match score {
+            0 => Value::Draw,
+            s if s > 0 => Value::Win((WIN - s) as u32),
+            s => Value::Loss((WIN + s) as u32),
+        }
+    }
+
+    /// Puntuación equivalente desde el punto de vista de X, compatible con la de la búsqueda
+    pub fn score(self, turn: Player) -> i32 {
+        let relative = match self {
+            Value::Win(plies) => WIN - plies as i32,
+            Value::Draw => 0,
+            Value::Loss(plies) => -(WIN - plies as i32),
+        };
+        sign(turn) * relative
+    }
+
+    /// Ganador con juego perfecto, si lo hay
+    pub fn winner(self, turn: Player) -> Option<Player> {
+        match self {
+            Value::Win(_) => Some(turn),
+            Value::Draw => None,
+            Value::Loss(_) => Some(turn.other()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Win(plies) => write!(f, "win in {}", plies),
+            Value::Draw => write!(f, "draw"),
+            Value::Loss(plies) => write!(f, "loss in {}", plies),
+        }
+    }
+}
+
+
+/// Posición resuelta
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Solution {
+
+    /// Valor para el jugador al que le toca
+    pub value: Value,
+
+    /// Jugada que consigue ese valor
+    pub best: Option<Move>,
+
+    /// Nodos visitados
+    pub nodes: u64,
+}
+
+
+/// Profundidad con la que se guardan las entradas: el valor es exacto hasta el final
+const SOLVED_DEPTH: u8 = u8::MAX;
+
+
+/// Solucionador exacto con tabla de transposición.
+///
+/// Solo resuelve posiciones con pocas casillas libres; la tabla se conserva
+/// entre llamadas para aprovechar lo resuelto en posiciones anteriores.
+pub struct Solver {
+
+    /// Número máximo de casillas libres de las posiciones que se intentan resolver
+    pub max_empty: usize,
+
+    /// Número máximo de nodos por resolución. Si se supera, la posición no se resuelve.
+    pub max_nodes: u64,
+
+    table: TranspositionTable,
+    nodes: u64,
+    root_best: Option<Move>,
+}
+
+impl Solver {
+
+    pub fn new(max_empty: usize, max_nodes: u64, table: TranspositionTable) -> Self {
+        Self {
+            max_empty,
+            max_nodes,
+            table,
+            nodes: 0,
+            root_best: None,
+        }
+    }
+
+    /// Resuelve la posición si tiene pocas casillas libres.
+    /// Devuelve None si la posición es demasiado grande o se supera el límite de nodos.
+    pub fn solve(&mut self, game: &Game) -> Option<Solution> {
+        self.solve_observed(game, &())
+    }
+
+    /// Como `solve`, pero el observador puede interrumpir la resolución,
+    /// por ejemplo al acabarse el tiempo. Si la interrumpe, devuelve None.
+    pub fn solve_observed(&mut self, game: &Game, observer: &dyn SearchObserver) -> Option<Solution> {
+
+        if game.empty_cells() > self.max_empty {
+            return None;
+        }
+
+        self.nodes = 0;
+        self.root_best = None;
+        self.table.new_search();
+
+        let mut game = game.clone();
+        let score = self.negamax(&mut game, -WIN - 1, WIN + 1, true, observer)?;
+
+        Some(Solution {
+            value: Value::from_score(score),
+            best: self.root_best,
+            nodes: self.nodes,
+        })
+    }
+
+    /// Vacía la tabla de transposición
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    /// Negamax exacto. Las puntuaciones son relativas al nodo: ganar en `n` jugadas vale `WIN - n`,
+    /// de modo que no dependen de la profundidad a la que se encuentra la posición.
+    fn negamax(&mut self, game: &mut Game, mut alpha: i32, mut beta: i32, root: bool, observer: &dyn SearchObserver) -> Option<i32> {
+
+        self.nodes += 1;
+        if self.nodes > self.max_nodes
+            || (self.nodes.is_multiple_of(STOP_CHECK_NODES) && observer.should_stop()) {
+            return None;
+        }
+
+        match game.result() {
+            // Si la partida terminó, ganó quien acaba de jugar.
+            // Los abandonos y las tablas acordadas no salen de jugar, así que aquí no aparecen.
+            Some(GameResult::Win(_) | GameResult::Resigned(_) | GameResult::OutOfTime(_)) => return Some(-WIN),
+            Some(GameResult::Draw | GameResult::AgreedDraw) => return Some(0),
+            None => {},
+        }
+
+        let key = game.key();
+        let original_alpha = alpha;
+        let mut hint = None;
+
+        if let Some(entry) = self.table.probe(key) {
+            // En la raíz siempre se busca para tener la mejor jugada
+            if !root {
+                match entry.bound {
+                    Bound::Exact => return Some(entry.score),
+                    Bound::Lower => alpha = alpha.max(entry.score),
+                    Bound::Upper => beta = beta.min(entry.score),
+                }
+                if alpha >= beta {
+                    return Some(entry.score);
+                }
+            }
+            hint = entry.best;
+        }
+
+        let mut moves = game.legal_moves();
+        if let Some(i) = hint.and_then(|h| moves.iter().position(|m| *m == h)) {
+            moves.swap(0, i);
+        }
+
+        let mut best_score = -WIN - 1;
+        let mut best_move = None;
+
+        for mv in moves {
+            game.apply(mv);
+            let child = self.negamax(game, -stretch(beta), -stretch(alpha), false, observer);
+            game.undo();
+
+            // Una jugada más hasta el final
+            let score = shrink(-child?);
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+
+        self.table.store(key, SOLVED_DEPTH, best_score, bound, best_move);
+
+        if root {
+            self.root_best = best_move;
+        }
+
+        Some(best_score)
+    }
+}
+
+
+/// Aleja una puntuación de victoria o derrota una jugada: `WIN - n` pasa a `WIN - n - 1`
+fn shrink(score: i32) -> i32 {
+    match score {
+        s if s > 0 => s - 1,
+        s if s < 0 => s + 1,
+        s => s,
+    }
+}
+
+
+/// Inversa de `shrink` para trasladar la ventana al nodo hijo
+fn stretch(bound: i32) -> i32 {
+    match bound {
+        b if !(-WIN..=WIN).contains(&b) => b,
+        b if b > 0 => b + 1,
+        b if b < 0 => b - 1,
+        b => b,
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::rng::Rng;
+    use crate::search::{forced_win, search, SearchLimits};
+
+    /// Casillas libres de las posiciones de prueba: el árbol completo es pequeño
+    const EMPTY: usize = 10;
+
+    /// Posiciones sin terminar con pocas casillas libres, de partidas aleatorias
+    fn near_final_positions(count: usize) -> Vec<Game> {
+        let mut rng = Rng::new(28);
+        let mut positions = Vec::new();
+
+        while positions.len() < count {
+            let mut game = Game::new();
+            while game.result().is_none() && game.empty_cells() > EMPTY {
+                let &mv = rng.choose(&game.legal_moves()).unwrap();
+                game.apply(mv);
+            }
+            if game.result().is_none() {
+                positions.push(game);
+            }
+        }
+
+        positions
+    }
+
+    /// Negamax completo, sin poda ni tabla, con las puntuaciones del solucionador
+    fn plain_negamax(game: &mut Game) -> i32 {
+        match game.result() {
+            Some(GameResult::Draw | GameResult::AgreedDraw) => return 0,
+            Some(_) => return -WIN,
+            None => {},
+        }
+
+        let mut best = -WIN - 1;
+        for mv in game.legal_moves() {
+            game.apply(mv);
+            best = best.max(shrink(-plain_negamax(game)));
+            game.undo();
+        }
+        best
+    }
+
+    fn solver() -> Solver {
+        Solver::new(EMPTY, u64::MAX, TranspositionTable::new(1))
+    }
+
+    /// El valor del solucionador es el del negamax completo, y su mejor jugada lo consigue.
+    /// La tabla se conserva entre posiciones, como en el análisis.
+    #[test]
+    fn matches_plain_negamax() {
+        let mut solver = solver();
+
+        for mut game in near_final_positions(40) {
+            let solution = solver.solve(&game).unwrap();
+            assert_eq!(solution.value, Value::from_score(plain_negamax(&mut game)), "{:?}", game.moves().collect::<Vec<_>>());
+
+            game.apply(solution.best.unwrap());
+            assert_eq!(Value::from_score(shrink(-plain_negamax(&mut game))), solution.value);
+        }
+    }
+
+    /// En las victorias forzadas, la puntuación del valor tiene el signo de la de la
+    /// búsqueda y las dos dan el mismo ganador
+    #[test]
+    fn score_agrees_with_search() {
+        let mut solver = solver();
+        let mut forced = 0;
+
+        for game in near_final_positions(40) {
+            let solution = solver.solve(&game).unwrap();
+            let Some(winner) = solution.value.winner(game.turn().unwrap()) else {
+                continue;
+            };
+
+            let score = solution.value.score(game.turn().unwrap());
+            let result = search(&game, SearchLimits { depth: EMPTY as u8 + 1, nodes: u64::MAX });
+            assert_eq!(score.signum(), result.score.signum());
+            assert_eq!(forced_win(score).map(|(player, _)| player), Some(winner));
+            assert_eq!(forced_win(result.score).map(|(player, _)| player), Some(winner));
+            forced += 1;
+        }
+
+        assert!(forced > 0);
+    }
+
+    /// Las posiciones con más casillas libres que el máximo no se intentan resolver
+    #[test]
+    fn skips_large_positions() {
+        assert_eq!(solver().solve(&Game::new()), None);
+    }
+}
@@ -0,0 +1,214 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Imágenes SVG de una posición y de una partida entera, con el aspecto del
+// tablero de la aplicación web: casillas jugadas con el degradado de cada
+// jugador, la última jugada resaltada y los subtableros ganados con su ganador.
+//
+// La repetición animada tiene un grupo por posición que se muestra durante
+// su parte de la animación, así que no necesita JavaScript.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt::Write;
+use crate::bitboard;
+use crate::board::Player;
+use crate::game::{Game, Move};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+/// Tamaño de una casilla
+const CELL: u32 = 40;
+
+/// Separación entre casillas
+const SPACING: u32 = 2;
+
+/// Margen interior de los subtableros
+const PADDING: u32 = 6;
+
+/// Separación entre subtableros
+const GAP: u32 = 10;
+
+const SUB_BOARD: u32 = 3 * CELL + 2 * SPACING + 2 * PADDING;
+
+/// Lado de la imagen
+pub const SIZE: u32 = 3 * SUB_BOARD + 4 * GAP;
+
+const BACKGROUND: &str = "#1d1c1f";
+const EMPTY_CELL: &str = "#f8f9fa";
+const HIGHLIGHTED: &str = "#feb048";
+const FIRST_PLAYER_COLOR: &str = "hsl(256, 36%, 42%)";
+const SECOND_PLAYER_COLOR: &str = "#c85856";
+
+/// Degradados de las casillas jugadas, como `$first-player-gradient` y `$second-player-gradient`
+const GRADIENTS: &str = r##"<defs>
+<linearGradient id="fp" x1="0" y1="1" x2="1" y2="0"><stop offset="0" stop-color="hsl(257, 38%, 30%)"/><stop offset="0.5" stop-color="hsl(256, 36%, 42%)"/><stop offset="1" stop-color="hsl(304, 40%, 43%)"/></linearGradient>
+<linearGradient id="sp" x1="0" y1="1" x2="1" y2="0"><stop offset="0.15" stop-color="#925078"/><stop offset="0.35" stop-color="#c85856"/><stop offset="0.55" stop-color="#ea7b5b"/><stop offset="1" stop-color="#fcd97a"/></linearGradient>
+</defs>
+"##;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// RENDERING
+
+
+/// Imagen de la posición de la partida
+pub fn position_svg(game: &Game) -> String {
+    let mut svg = header();
+    svg.push_str(&board_group(game, ""));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+
+/// Repetición animada de la partida: cada posición se muestra `frame_seconds` segundos
+/// y la animación vuelve a empezar al terminar. None si alguna jugada es ilegal.
+pub fn replay_svg(moves: &[Move], frame_seconds: f64) -> Option<String> {
+    let frames = moves.len() + 1;
+    let total = frame_seconds * frames as f64;
+
+    let mut svg = header();
+    let mut game = Game::new();
+
+    for frame in 0..frames {
+        if frame > 0 {
+            let mv = moves[frame - 1];
+            if !game.is_legal(mv) {
+                return None;
+            }
+            game.apply(mv);
+        }
+
+        // Visible solo en su parte de la animación
+        let start = frame as f64 / frames as f64;
+        let end = (frame + 1) as f64 / frames as f64;
+        let (values, key_times) = match (frame == 0, frame + 1 == frames) {
+            (true, true) => ("visible".to_string(), "0".to_string()),
+            (true, false) => ("visible;hidden".to_string(), format!("0;{:.6}", end)),
+            (false, true) => ("hidden;visible".to_string(), format!("0;{:.6}", start)),
+            (false, false) => ("hidden;visible;hidden".to_string(), format!("0;{:.6};{:.6}", start, end)),
+        };
+        let animation = format!(
+            r#"<animate attributeName="visibility" values="{}" keyTimes="{}" calcMode="discrete" dur="{}s" repeatCount="indefinite"/>"#,
+            values, key_times, total,
+        );
+
+        svg.push_str(&board_group(&game, &animation));
+    }
+
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+
+fn header() -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}" font-family="sans-serif" font-weight="bold">"#,
+        size = SIZE,
+    );
+    svg.push('\n');
+    svg.push_str(GRADIENTS);
+    let _ = writeln!(svg, r#"<rect width="{size}" height="{size}" rx="12" fill="{}"/>"#, BACKGROUND, size = SIZE);
+    svg
+}
+
+
+fn player_color(player: Player) -> &'static str {
+    match player {
+        Player::First => FIRST_PLAYER_COLOR,
+        Player::Second => SECOND_PLAYER_COLOR,
+    }
+}
+
+
+fn gradient(player: Player) -> &'static str {
+    match player {
+        Player::First => "url(#fp)",
+        Player::Second => "url(#sp)",
+    }
+}
+
+
+/// Grupo con el tablero de la posición. `extra` se añade dentro del grupo, por ejemplo una animación.
+fn board_group(game: &Game, extra: &str) -> String {
+    let board = game.bitboard();
+    let mut svg = String::from("<g>");
+    svg.push_str(extra);
+    svg.push('\n');
+
+    for sub in 0..9 {
+        let position = bitboard::position(sub);
+        let x = GAP + position.y as u32 * (SUB_BOARD + GAP);
+        let y = GAP + position.x as u32 * (SUB_BOARD + GAP);
+
+        if let Some(owner) = board.owner(sub) {
+            // Subtablero ganado: borde y símbolo grande del ganador, como `won-cell`
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="10" fill="none" stroke="{color}" stroke-width="5"/><text x="{}" y="{}" font-size="{}" fill="{color}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x + 3, y + 3, SUB_BOARD - 6, SUB_BOARD - 6,
+                x + SUB_BOARD / 2, y + SUB_BOARD / 2, SUB_BOARD / 2, owner.symbol(),
+                color = player_color(owner),
+            );
+            continue;
+        }
+
+        let active = game.turn().is_some() && board.playable(sub) && game.board_enabled(position);
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="7" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            x, y, SUB_BOARD, SUB_BOARD,
+            if active { HIGHLIGHTED } else { "white" },
+            if active { 2 } else { 1 },
+        );
+
+        for cell in 0..9 {
+            let cell_position = bitboard::position(cell);
+            let cx = x + PADDING + cell_position.y as u32 * (CELL + SPACING);
+            let cy = y + PADDING + cell_position.x as u32 * (CELL + SPACING);
+
+            match board.get(sub, cell) {
+                Some(player) => {
+                    let _ = write!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="4" fill="{}" stroke="{}" stroke-width="3"/><text x="{}" y="{}" font-size="{}" fill="white" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                        cx + 1, cy + 1, CELL - 2, CELL - 2, gradient(player), player_color(player),
+                        cx + CELL / 2, cy + CELL / 2, CELL / 2, player.symbol(),
+                    );
+                },
+                None => {
+                    let _ = write!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" rx="4" fill="{}"/>"#, cx, cy, CELL, CELL, EMPTY_CELL);
+                },
+            }
+
+            // Última jugada resaltada, como `last-move`
+            if game.last_move() == Some(Move::new(position, cell_position)) {
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" rx="5" fill="none" stroke="{}" stroke-width="3"/>"#,
+                    cx - 2, cy - 2, CELL + 4, CELL + 4, HIGHLIGHTED,
+                );
+            }
+            svg.push('\n');
+        }
+    }
+
+    svg.push_str("</g>\n");
+    svg
+}
+
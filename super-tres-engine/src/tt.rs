@@ -0,0 +1,116 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Tabla de transposición de tamaño fijo indexada por la clave de Zobrist.
+// La comparten todos los bots que buscan: alfa-beta y solucionador exacto.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::game::Move;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Tipo de cota de una puntuación guardada
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+
+/// Entrada de la tabla
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Entry {
+
+    /// Clave completa de la posición, para descartar colisiones de índice
+    pub key: u64,
+
+    /// Profundidad con la que se buscó la posición
+    pub depth: u8,
+
+    pub score: i32,
+    pub bound: Bound,
+
+    /// Mejor jugada encontrada, para ordenar primero en la siguiente visita
+    pub best: Option<Move>,
+
+    /// Búsqueda en la que se guardó la entrada
+    generation: u8,
+}
+
+
+/// Tabla de transposición con reemplazo.
+///
+/// Cada clave tiene una única casilla. Una entrada nueva sustituye a la guardada
+/// si es de otra posición de una búsqueda anterior o si se buscó al menos con la misma profundidad.
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    mask: usize,
+    generation: u8,
+}
+
+impl TranspositionTable {
+
+    /// Tabla que ocupa como mucho los megabytes indicados.
+    /// El número de entradas se redondea a una potencia de dos.
+    pub fn new(megabytes: usize) -> Self {
+        let bytes = megabytes.max(1) * 1024 * 1024;
+        let count = (bytes / std::mem::size_of::<Option<Entry>>()).max(1);
+        let count = if count.is_power_of_two() { count } else { count.next_power_of_two() / 2 };
+
+        Self {
+            entries: vec![None; count],
+            mask: count - 1,
+            generation: 0,
+        }
+    }
+
+    /// Marca el comienzo de una búsqueda nueva: las entradas anteriores pasan a ser reemplazables
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<&Entry> {
+        self.entries[key as usize & self.mask].as_ref().filter(|entry| entry.key == key)
+    }
+
+    pub fn store(&mut self, key: u64, depth: u8, score: i32, bound: Bound, best: Option<Move>) {
+        let generation = self.generation;
+        let slot = &mut self.entries[key as usize & self.mask];
+
+        let replace = match slot {
+            None => true,
+            Some(old) => old.key == key || old.generation != generation || depth >= old.depth,
+        };
+
+        if replace {
+            // Si no hay jugada nueva, se conserva la que hubiera de la misma posición
+            let best = best.or_else(|| slot.filter(|old| old.key == key).and_then(|old| old.best));
+            *slot = Some(Entry { key, depth, score, bound, best, generation });
+        }
+    }
+
+    /// Porcentaje de entradas ocupadas, en tantos por mil
+    pub fn hashfull(&self) -> usize {
+        let sample = self.entries.len().min(1000);
+        self.entries[..sample].iter().filter(|e| e.is_some()).count() * 1000 / sample
+    }
+}
@@ -0,0 +1,177 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Bots cargados como módulos WebAssembly que siguen la interfaz de `abi`.
+// Se ejecutan con un intérprete, así que funcionan igual en el navegador
+// que en nativo, y cada jugada tiene un límite de instrucciones para que
+// un bot que no termina no bloquee la partida.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use wasmi::{Config, Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
+use crate::abi::{self, ABI_VERSION, BUFFER_BYTES};
+use crate::bot::Bot;
+use crate::game::{Game, Move};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Instrucciones que puede ejecutar un bot en cada jugada
+pub const FUEL_PER_MOVE: u64 = 500_000_000;
+
+
+/// Error al cargar o ejecutar un bot
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum WasmBotError {
+
+    /// El módulo no es WebAssembly válido o no se pudo instanciar
+    Module(String),
+
+    /// Falta una exportación de la interfaz o no tiene el tipo esperado
+    Export(&'static str),
+
+    /// El módulo declara otra versión de la interfaz
+    Version(i32),
+
+    /// El bot falló o agotó sus instrucciones
+    Trap(String),
+
+    /// El bot devolvió una casilla fuera del tablero o ilegal
+    IllegalMove(i32),
+
+    /// El bot no encontró jugada
+    NoMove,
+}
+
+impl fmt::Display for WasmBotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WasmBotError::Module(err) => write!(f, "invalid module: {}", err),
+            WasmBotError::Export(name) => write!(f, "missing export `{}`", name),
+            WasmBotError::Version(version) => write!(f, "unsupported ABI version {} (expected {})", version, ABI_VERSION),
+            WasmBotError::Trap(err) => write!(f, "bot crashed: {}", err),
+            WasmBotError::IllegalMove(code) => write!(f, "illegal move {}", code),
+            WasmBotError::NoMove => write!(f, "bot found no move"),
+        }
+    }
+}
+
+impl std::error::Error for WasmBotError {}
+
+
+/// Bot de un módulo WebAssembly
+pub struct WasmBot {
+    name: String,
+    store: Store<()>,
+    memory: Memory,
+    buffer: TypedFunc<(), i32>,
+    choose: TypedFunc<(), i32>,
+
+    /// Último error, para explicar por qué el bot no jugó
+    error: Option<WasmBotError>,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// WASM BOTS
+
+
+impl WasmBot {
+
+    /// Carga el módulo y comprueba que exporta la interfaz
+    pub fn new(name: impl Into<String>, wasm: &[u8]) -> Result<Self, WasmBotError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(|err| WasmBotError::Module(err.to_string()))?;
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(FUEL_PER_MOVE).map_err(|err| WasmBotError::Module(err.to_string()))?;
+
+        let instance: Instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|err| WasmBotError::Module(err.to_string()))?;
+
+        let memory = instance.get_memory(&store, "memory").ok_or(WasmBotError::Export("memory"))?;
+        let buffer = instance.get_typed_func::<(), i32>(&store, "st_buffer").map_err(|_| WasmBotError::Export("st_buffer"))?;
+        let choose = instance.get_typed_func::<(), i32>(&store, "st_choose").map_err(|_| WasmBotError::Export("st_choose"))?;
+
+        if let Ok(version) = instance.get_typed_func::<(), i32>(&store, "st_abi_version") {
+            let version = version.call(&mut store, ()).map_err(|err| WasmBotError::Trap(err.to_string()))?;
+            if version != ABI_VERSION {
+                return Err(WasmBotError::Version(version));
+            }
+        }
+
+        Ok(Self { name: name.into(), store, memory, buffer, choose, error: None })
+    }
+
+    /// Pide una jugada al bot
+    pub fn try_choose(&mut self, game: &Game) -> Result<Move, WasmBotError> {
+        let trap = |err: wasmi::Error| WasmBotError::Trap(err.to_string());
+
+        self.store.set_fuel(FUEL_PER_MOVE).map_err(|err| WasmBotError::Trap(err.to_string()))?;
+
+        let address = self.buffer.call(&mut self.store, ()).map_err(trap)?;
+        let position = abi::encode_position(game);
+        debug_assert!(position.len() <= BUFFER_BYTES);
+
+        self.memory
+            .write(&mut self.store, address as u32 as usize, &position)
+            .map_err(|err| WasmBotError::Trap(err.to_string()))?;
+
+        let code = self.choose.call(&mut self.store, ()).map_err(trap)?;
+        if code < 0 {
+            return Err(WasmBotError::NoMove);
+        }
+
+        abi::decode_move(code)
+            .filter(|mv| game.is_legal(*mv))
+            .ok_or(WasmBotError::IllegalMove(code))
+    }
+
+    /// Error de la última jugada que no se pudo elegir
+    pub fn error(&self) -> Option<&WasmBotError> {
+        self.error.as_ref()
+    }
+}
+
+
+impl Bot for WasmBot {
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Si el bot falla, devuelve None y guarda el error
+    fn choose(&mut self, game: &Game) -> Option<Move> {
+        game.turn()?;
+
+        match self.try_choose(game) {
+            Ok(mv) => {
+                self.error = None;
+                Some(mv)
+            },
+            Err(err) => {
+                self.error = Some(err);
+                None
+            },
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Claves de Zobrist para identificar posiciones.
+// La clave de una posición es el XOR de las claves de sus elementos:
+// casillas ocupadas, subtableros decididos, subtablero activo y turno.
+// Las claves se generan en compilación con una semilla fija.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use crate::bitboard::{self, Bitboard};
+use crate::board::{Player, Position, UNREACHABLE};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// KEYS
+
+
+const SEED: u64 = 0x5EED_7E55_E4A7_0001;
+
+/// Número de claves: 81 casillas y 9 subtableros por jugador, 11 estados del
+/// subtablero activo (cualquiera, uno de los nueve o inalcanzable) y el turno de O
+const COUNT: usize = 81 * 2 + 9 * 2 + 11 + 1;
+
+const KEYS: [u64; COUNT] = generate();
+
+const CELLS: usize = 0;
+const DECIDED: usize = CELLS + 81 * 2;
+const ACTIVE: usize = DECIDED + 9 * 2;
+const SECOND_TO_MOVE: usize = ACTIVE + 11;
+
+
+/// Genera las claves con SplitMix64
+const fn generate() -> [u64; COUNT] {
+    let mut keys = [0; COUNT];
+    let mut state = SEED;
+    let mut i = 0;
+
+    while i < COUNT {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    keys
+}
+
+
+/// Clave de una casilla ocupada. La posición es total en el supertablero.
+pub fn cell(total: Position, player: Player) -> u64 {
+    let index = total.x as usize * 9 + total.y as usize;
+    KEYS[CELLS + index * 2 + player.index()]
+}
+
+
+/// Clave de un subtablero ganado
+pub fn decided(board: Position, player: Player) -> u64 {
+    let index = board.x as usize * 3 + board.y as usize;
+    KEYS[DECIDED + index * 2 + player.index()]
+}
+
+
+/// Clave del subtablero activo
+pub fn active(active_table: Option<Position>) -> u64 {
+    let index = match active_table {
+        None => 0,
+        Some(UNREACHABLE) => 10,
+        Some(board) => 1 + board.x as usize * 3 + board.y as usize,
+    };
+    KEYS[ACTIVE + index]
+}
+
+
+/// Clave del turno: solo cuenta cuando le toca a O
+pub fn turn(turn: Option<Player>) -> u64 {
+    match turn {
+        Some(Player::Second) => KEYS[SECOND_TO_MOVE],
+        _ => 0,
+    }
+}
+
+
+/// Clave de las casillas ocupadas de un subtablero
+pub fn sub_board(board: Position, bits: &Bitboard) -> u64 {
+    let index = bitboard::index(board);
+
+    [Player::First, Player::Second]
+        .into_iter()
+        .flat_map(|player| {
+            bitboard::bits(bits.cells(player, index))
+                .map(move |c| cell(Position::total_pos(board, bitboard::position(c)), player))
+        })
+        .fold(0, |key, k| key ^ k)
+}
@@ -0,0 +1,165 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Exporta como SVG una posición o una partida entera de un fichero de partidas
+// (como los que escribe el torneo) o de una lista de jugadas.
+//
+//     cargo run --release -p super-tres-export -- tournament.tres --game 3 --ply 20 -o position.svg
+//     cargo run --release -p super-tres-export -- --moves "e5 d4 c3" --animate -o replay.svg
+//     cargo run --release -p super-tres-export -- tournament.tres --frames frames/
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use super_tres_engine::game::{Game, Move};
+use super_tres_engine::record::GameRecord;
+use super_tres_engine::svg::{position_svg, replay_svg};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ARGUMENTS
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Export positions and games as SVG images")]
+struct Args {
+
+    /// Game record file
+    #[arg(required_unless_present = "moves", conflicts_with = "moves")]
+    record: Option<PathBuf>,
+
+    /// Moves from the start position, separated by spaces, instead of a record file
+    #[arg(long)]
+    moves: Option<String>,
+
+    /// Game of the record file, starting at 1
+    #[arg(short, long, default_value_t = 1)]
+    game: usize,
+
+    /// Export the position after this many moves instead of the final one
+    #[arg(short, long, conflicts_with_all = ["animate", "frames"])]
+    ply: Option<usize>,
+
+    /// Export the whole game as an animated SVG
+    #[arg(short, long)]
+    animate: bool,
+
+    /// Seconds each position is shown in the animation
+    #[arg(long, default_value_t = 1.0)]
+    frame_seconds: f64,
+
+    /// Write one SVG per position into this directory instead
+    #[arg(long, conflicts_with = "animate")]
+    frames: Option<PathBuf>,
+
+    /// Output file
+    #[arg(short, long, default_value = "game.svg")]
+    output: PathBuf,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MAIN
+
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let moves = match read_moves(&args) {
+        Ok(moves) => moves,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let result = match (&args.frames, args.animate) {
+        (Some(dir), _) => write_frames(dir, &moves),
+        (None, true) => match replay_svg(&moves, args.frame_seconds) {
+            Some(svg) => write(&args.output, &svg),
+            None => Err("illegal move sequence".to_string()),
+        },
+        (None, false) => {
+            let ply = args.ply.unwrap_or(moves.len());
+            match moves.get(..ply).and_then(Game::from_moves) {
+                Some(game) => write(&args.output, &position_svg(&game)),
+                None => Err(format!("the game has no position after {} moves", ply)),
+            }
+        },
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+
+/// Jugadas de la partida pedida
+fn read_moves(args: &Args) -> Result<Vec<Move>, String> {
+    if let Some(moves) = &args.moves {
+        return moves
+            .split_whitespace()
+            .map(|mv| mv.parse().map_err(|err| format!("{}", err)))
+            .collect();
+    }
+
+    let path = args.record.as_ref().expect("clap requires a record or moves");
+    let text = fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+    let records = GameRecord::parse_all(&text).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+
+    let record = args.game.checked_sub(1).and_then(|i| records.get(i))
+        .ok_or_else(|| format!("{} has {} games", path.display(), records.len()))?;
+
+    Ok(record.moves.clone())
+}
+
+
+fn write(path: &PathBuf, svg: &str) -> Result<(), String> {
+    fs::write(path, svg).map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+    eprintln!("Written {}", path.display());
+    Ok(())
+}
+
+
+/// Una imagen por posición: `000.svg` es la inicial y `NNN.svg` la posición tras N jugadas
+fn write_frames(dir: &PathBuf, moves: &[Move]) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|err| format!("could not create {}: {}", dir.display(), err))?;
+
+    let mut game = Game::new();
+    for ply in 0..=moves.len() {
+        if ply > 0 {
+            let mv = moves[ply - 1];
+            if !game.is_legal(mv) {
+                return Err(format!("illegal move {} at ply {}", mv, ply));
+            }
+            game.apply(mv);
+        }
+
+        let path = dir.join(format!("{:03}.svg", ply));
+        fs::write(&path, position_svg(&game)).map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+    }
+
+    eprintln!("Written {} frames to {}", moves.len() + 1, dir.display());
+    Ok(())
+}
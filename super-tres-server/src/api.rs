@@ -0,0 +1,214 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// API HTTP de solo lectura del archivo de partidas, en el mismo puerto que
+// los WebSockets. Cada petición se atiende en el hilo de su conexión y la
+// conexión se cierra al responder. Todas las respuestas son JSON:
+//
+//     GET /api/games?<filtros>   {"total": <partidas>, "games": [<partida>, ...]}
+//                                una página de partidas, de la más reciente a la más antigua
+//     GET /api/games/<id>        <partida> con "record", el registro completo
+//
+// Los filtros son los de `super_tres_engine::archive`. Cada partida es
+//
+//     {"id": 7, "x": "alice", "o": null, "ruleset": "standard", "time_control": "5+3",
+//      "result": "1-0", "termination": "resignation", "plies": 31, "opening": "e5",
+//      "rated": false, "ended": 1792398682}
+//
+// con null en el jugador que no entró con una cuenta. Los errores son
+// {"error": <texto>}. Se responde a cualquier origen, para que la aplicación
+// pueda consultar el archivo aunque se sirva desde otra dirección.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super_tres_engine::archive::{ArchiveQuery, ArchivedGame};
+
+use crate::store::Store;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// API
+
+
+/// Comienzo de la ruta de todas las peticiones de la API
+pub const PREFIX: &str = "/api/";
+
+/// Tiempo máximo para recibir la petición
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tamaño máximo de la cabecera de una petición
+const MAX_REQUEST: usize = 8 * 1024;
+
+
+/// Respuesta a una petición: estado HTTP y cuerpo JSON
+type Response = (&'static str, String);
+
+
+/// Atiende una petición y cierra la conexión
+pub fn serve(mut stream: TcpStream, store: &Mutex<Store>, peer: &str) {
+    let request = match read_request(&mut stream) {
+        Ok(request) => request,
+        Err(err) => {
+            eprintln!("{}: bad request: {}", peer, err);
+            return;
+        },
+    };
+
+    let mut words = request.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (words.next().unwrap_or_default(), words.next().unwrap_or_default());
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let (status, body) = match method {
+        // Petición previa del navegador antes de una consulta desde otro origen
+        "OPTIONS" => ("204 No Content", String::new()),
+        "GET" => route(path, query, store),
+        _ => error("405 Method Not Allowed", "only GET requests are supported"),
+    };
+    eprintln!("{}: {} {} {}", peer, method, target, status);
+
+    let _ = respond(&mut stream, status, &body);
+}
+
+
+/// Lee la cabecera de la petición. El cuerpo no se usa.
+fn read_request(stream: &mut TcpStream) -> io::Result<String> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if request.len() > MAX_REQUEST {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "request too large"));
+        }
+    }
+
+    String::from_utf8(request).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, OPTIONS\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        status, body.len(), body,
+    )?;
+    stream.flush()
+}
+
+
+fn route(path: &str, query: &str, store: &Mutex<Store>) -> Response {
+    let store = store.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    match path.strip_prefix(PREFIX) {
+        Some("games") => {
+            let query: ArchiveQuery = match query.parse() {
+                Ok(query) => query,
+                Err(err) => return error("400 Bad Request", &err.to_string()),
+            };
+            match store.search(&query) {
+                Ok((total, games)) => {
+                    let games: Vec<String> = games.iter().map(|game| game_json(game, None)).collect();
+                    ("200 OK", format!("{{\"total\":{},\"games\":[{}]}}", total, games.join(",")))
+                },
+                Err(err) => database_error(err),
+            }
+        },
+        Some(game) if game.starts_with("games/") => {
+            let Ok(id) = game["games/".len()..].parse() else {
+                return error("404 Not Found", "no such game");
+            };
+            match store.game(id) {
+                Ok(Some((game, record))) => ("200 OK", game_json(&game, Some(&record))),
+                Ok(None) => error("404 Not Found", "no such game"),
+                Err(err) => database_error(err),
+            }
+        },
+        _ => error("404 Not Found", "unknown path"),
+    }
+}
+
+
+fn error(status: &'static str, message: &str) -> Response {
+    (status, format!("{{\"error\":{}}}", json_string(message)))
+}
+
+
+fn database_error(err: rusqlite::Error) -> Response {
+    eprintln!("archive query failed: {}", err);
+    error("500 Internal Server Error", "database error")
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// JSON
+
+
+/// Objeto JSON de una partida del archivo, con su registro si se da
+fn game_json(game: &ArchivedGame, record: Option<&str>) -> String {
+    let text = |value: Option<String>| value.map_or_else(|| "null".to_string(), |value| json_string(&value));
+
+    format!(
+        "{{\"id\":{},\"x\":{},\"o\":{},\"ruleset\":{},\"time_control\":{},\"result\":{},\"termination\":{},\
+         \"plies\":{},\"opening\":{},\"rated\":{},\"ended\":{}{}}}",
+        game.id,
+        text(game.players[0].clone()),
+        text(game.players[1].clone()),
+        json_string(&game.ruleset.to_string()),
+        json_string(&game.time_control.to_string()),
+        json_string(&game.result.to_string()),
+        json_string(&game.termination),
+        game.plies,
+        text(game.opening.map(|opening| opening.to_string())),
+        game.rated,
+        game.ended,
+        record.map_or_else(String::new, |record| format!(",\"record\":{}", json_string(record))),
+    )
+}
+
+
+/// Cadena JSON entre comillas
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,139 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v4                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Conexión WebSocket de un cliente, atendida en su propio hilo.
+// Alterna entre leer del socket con un tiempo de espera corto y mandar
+// los mensajes que el vestíbulo dejó en su canal. Las peticiones HTTP a la
+// API del archivo llegan al mismo puerto y se reconocen por su ruta antes
+// de la negociación del WebSocket.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::io;
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tungstenite::error::ProtocolError;
+use tungstenite::{Error, Message};
+use super_tres_engine::net::{ClientMessage, ServerMessage};
+
+use crate::api;
+use crate::lobby::Lobby;
+use crate::store::Store;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONNECTION
+
+
+/// Tiempo máximo de espera de cada lectura antes de mandar los mensajes pendientes
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Tiempo máximo de espera de cada lectura de la petición, hasta completar la negociación
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+/// Atiende al cliente hasta que se desconecta, o responde a su petición a la API del archivo
+pub fn serve(stream: TcpStream, lobby: Arc<Mutex<Lobby>>, archive: Arc<Mutex<Store>>) {
+    let peer = stream.peer_addr().map_or_else(|_| "unknown".to_string(), |addr| addr.to_string());
+
+    // La petición puede llegar por partes; el tiempo de espera corto es solo para después
+    if let Err(err) = stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)) {
+        eprintln!("{}: {}", peer, err);
+        return;
+    }
+    if is_api_request(&stream) {
+        api::serve(stream, &archive, &peer);
+        return;
+    }
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(err) => {
+            eprintln!("{}: handshake failed: {}", peer, err);
+            return;
+        },
+    };
+    if let Err(err) = socket.get_ref().set_read_timeout(Some(POLL_INTERVAL)) {
+        eprintln!("{}: {}", peer, err);
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let client = lock(&lobby).connect(sender.clone());
+    eprintln!("{}: connected as client {}", peer, client);
+
+    'connection: loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                    match line.parse::<ClientMessage>() {
+                        Ok(message) => lock(&lobby).handle(client, message),
+                        Err(err) => {
+                            let _ = sender.send(ServerMessage::Error(err.to_string()));
+                        },
+                    }
+                }
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {},
+            Err(Error::Io(err)) if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {},
+            Err(Error::ConnectionClosed | Error::AlreadyClosed | Error::Protocol(ProtocolError::ResetWithoutClosingHandshake)) => break,
+            Err(err) => {
+                eprintln!("{}: {}", peer, err);
+                break;
+            },
+        }
+
+        while let Ok(message) = receiver.try_recv() {
+            if socket.send(Message::Text(message.to_string())).is_err() {
+                break 'connection;
+            }
+        }
+    }
+
+    lock(&lobby).disconnect(client);
+    eprintln!("{}: client {} disconnected", peer, client);
+}
+
+
+/// Indica si la petición es a la API del archivo. Mira el principio de la
+/// petición sin sacarlo del socket, para que lo lea quien la atienda.
+fn is_api_request(stream: &TcpStream) -> bool {
+    let started = Instant::now();
+    let mut buffer = [0; 16];
+
+    while started.elapsed() < HANDSHAKE_TIMEOUT {
+        match stream.peek(&mut buffer) {
+            // Con el método y el principio de la ruta ya se sabe
+            Ok(read) if read == buffer.len() || buffer[..read].contains(&b'\n') => {
+                let request = &buffer[..read];
+                return request.iter().position(|byte| *byte == b' ')
+                    .is_some_and(|space| request[space + 1..].starts_with(api::PREFIX.as_bytes()));
+            },
+            Ok(0) => return false,
+            Ok(_) => std::thread::sleep(POLL_INTERVAL),
+            Err(err) if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {},
+            Err(_) => return false,
+        }
+    }
+
+    false
+}
+
+
+/// Bloquea el vestíbulo. Si otro hilo falló con él bloqueado, el estado sigue siendo utilizable.
+pub fn lock(lobby: &Mutex<Lobby>) -> std::sync::MutexGuard<'_, Lobby> {
+    lobby.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
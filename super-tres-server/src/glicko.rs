@@ -0,0 +1,190 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Puntuaciones de Glicko-2 (Glickman, 2012). Cada jugador tiene su
+// puntuación, la desviación que mide lo fiable que es y la volatilidad que
+// mide lo que cambia. Las partidas se puntúan según terminan, así que cada
+// partida es un periodo de puntuación.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::f64::consts::PI;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Puntuación de un jugador nuevo
+pub const DEFAULT_RATING: f64 = 1500.0;
+
+/// Desviación de un jugador nuevo
+pub const DEFAULT_DEVIATION: f64 = 350.0;
+
+/// Volatilidad de un jugador nuevo
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+
+/// Cuánto puede cambiar la volatilidad de un periodo a otro
+const TAU: f64 = 0.5;
+
+/// Factor entre la escala de Glicko y la de Glicko-2
+const SCALE: f64 = 173.7178;
+
+/// Precisión con la que se calcula la volatilidad nueva
+const TOLERANCE: f64 = 1e-6;
+
+
+/// Puntuación de Glicko-2 de un jugador, en la escala de Glicko
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self { rating: DEFAULT_RATING, deviation: DEFAULT_DEVIATION, volatility: DEFAULT_VOLATILITY }
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// RATINGS
+
+
+impl Rating {
+
+    /// Puntuación tras un periodo con esas partidas: puntuación del rival y
+    /// puntos del jugador (1, 0.5 o 0). Sin partidas solo crece la desviación.
+    pub fn update(self, games: &[(Rating, f64)]) -> Rating {
+        let mu = (self.rating - DEFAULT_RATING) / SCALE;
+        let phi = self.deviation / SCALE;
+
+        if games.is_empty() {
+            let phi = (phi.powi(2) + self.volatility.powi(2)).sqrt();
+            return Rating { deviation: phi * SCALE, ..self };
+        }
+
+        // Varianza estimada de la puntuación y mejora según los resultados
+        let (mut inverse_v, mut improvement) = (0.0, 0.0);
+        for (opponent, score) in games {
+            let mu_j = (opponent.rating - DEFAULT_RATING) / SCALE;
+            let g = g(opponent.deviation / SCALE);
+            let e = 1.0 / (1.0 + (-g * (mu - mu_j)).exp());
+            inverse_v += g.powi(2) * e * (1.0 - e);
+            improvement += g * (score - e);
+        }
+        let v = 1.0 / inverse_v;
+        let delta = v * improvement;
+
+        let volatility = volatility(phi, self.volatility, v, delta);
+
+        let phi_star = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / v).sqrt();
+        let mu = mu + phi.powi(2) * improvement;
+
+        Rating { rating: mu * SCALE + DEFAULT_RATING, deviation: phi * SCALE, volatility }
+    }
+}
+
+
+/// Peso de una partida según la desviación del rival
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+
+/// Volatilidad nueva, con el método de Illinois del paso 5 del algoritmo
+fn volatility(phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+    let a = sigma.powi(2).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta.powi(2) - phi.powi(2) - v - ex) / (2.0 * (phi.powi(2) + v + ex).powi(2)) - (x - a) / TAU.powi(2)
+    };
+
+    let mut low = a;
+    let mut high = if delta.powi(2) > phi.powi(2) + v {
+        (delta.powi(2) - phi.powi(2) - v).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * TAU) < 0.0 {
+            k += 1.0;
+        }
+        a - k * TAU
+    };
+
+    let (mut f_low, mut f_high) = (f(low), f(high));
+    while (high - low).abs() > TOLERANCE {
+        let c = low + (low - high) * f_low / (f_high - f_low);
+        let f_c = f(c);
+        if f_c * f_high <= 0.0 {
+            low = high;
+            f_low = f_high;
+        } else {
+            f_low /= 2.0;
+        }
+        high = c;
+        f_high = f_c;
+    }
+
+    (low / 2.0).exp()
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn rating(rating: f64, deviation: f64) -> Rating {
+        Rating { rating, deviation, ..Rating::default() }
+    }
+
+    /// Ejemplo del artículo de Glickman: gana a 1400 y pierde con 1550 y 1700
+    #[test]
+    fn glickman_example() {
+        let games = [(rating(1400.0, 30.0), 1.0), (rating(1550.0, 100.0), 0.0), (rating(1700.0, 300.0), 0.0)];
+        let after = rating(1500.0, 200.0).update(&games);
+
+        assert!((after.rating - 1464.06).abs() < 0.01, "{:?}", after);
+        assert!((after.deviation - 151.52).abs() < 0.01, "{:?}", after);
+        assert!((after.volatility - 0.05999).abs() < 0.00001, "{:?}", after);
+    }
+
+    #[test]
+    fn no_games_only_grows_deviation() {
+        let before = rating(1500.0, 200.0);
+        let after = before.update(&[]);
+
+        assert_eq!((after.rating, after.volatility), (before.rating, before.volatility));
+        assert!((after.deviation - 200.271).abs() < 0.001, "{:?}", after);
+    }
+
+    #[test]
+    fn draw_between_equals_keeps_rating() {
+        let after = Rating::default().update(&[(Rating::default(), 0.5)]);
+
+        assert!((after.rating - DEFAULT_RATING).abs() < 1e-9, "{:?}", after);
+        assert!(after.deviation < DEFAULT_DEVIATION);
+    }
+}
@@ -0,0 +1,758 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v13                                                 //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Estado compartido del servidor: clientes conectados y salas.
+// Cada conexión atiende sus mensajes con el vestíbulo bloqueado y recibe
+// las respuestas por su canal, así que los avisos a otros clientes no esperan
+// a que esos clientes lean. Los clientes que no están en ninguna sala reciben
+// la lista de salas abiertas cada vez que cambia. Si un jugador se desconecta
+// a media partida, su asiento se le guarda durante el tiempo de gracia para
+// que vuelva con su sesión, y el reloj se para hasta que vuelve; si no vuelve
+// a tiempo, pierde la partida. El chat de
+// cada sala se reparte a jugadores y espectadores, con un límite de mensajes
+// por cliente. Quien deja una partida empezada la pierde. Los clientes
+// pueden entrar con una cuenta; cada partida que termina se guarda en el
+// archivo y, si la jugaron dos cuentas, actualiza sus puntuaciones.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super_tres_engine::archive::{termination, Date};
+use super_tres_engine::board::Player;
+use super_tres_engine::game::{GameResult, Move};
+use super_tres_engine::net::{ClientMessage, Offer, RoomInfo, RoomSettings, ServerMessage, MAX_CHAT_LENGTH, REACTIONS};
+use super_tres_engine::record::{GameRecord, RecordResult};
+use super_tres_engine::rng::Rng;
+
+use crate::room::{Presence, Room, Seat};
+use crate::store::{new_secret, FinishedGame, Store};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Letras de los códigos de sala, sin las que se confunden entre sí
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Longitud de los códigos de sala
+const CODE_LENGTH: usize = 4;
+
+/// Mensajes de chat y reacciones que un cliente puede mandar en cada ventana de tiempo
+const CHAT_BURST: usize = 5;
+
+/// Ventana de tiempo del límite de mensajes
+const CHAT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Longitud máxima de la clave de una cuenta
+const MAX_KEY_LENGTH: usize = 64;
+
+/// Nombre de cada subtablero en los avisos
+const BOARD_NAMES: [&str; 9] = [
+    "top-left", "top", "top-right",
+    "left", "center", "right",
+    "bottom-left", "bottom", "bottom-right",
+];
+
+
+/// Identificador de una conexión
+pub type ClientId = u64;
+
+
+/// Cliente conectado
+struct Client {
+    sender: Sender<ServerMessage>,
+
+    /// Código de la sala en la que está
+    room: Option<String>,
+
+    /// Cuenta con la que entró
+    account: Option<String>,
+
+    /// Momentos de sus últimos mensajes de chat, para limitarlos
+    said: VecDeque<Instant>,
+}
+
+
+/// Clientes y salas del servidor
+pub struct Lobby {
+    clients: HashMap<ClientId, Client>,
+    rooms: HashMap<String, Room>,
+    next_id: ClientId,
+    rng: Rng,
+
+    /// Tiempo que se guarda el asiento de un jugador desconectado
+    grace: Duration,
+
+    /// Cuentas y archivo de partidas
+    store: Store,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// LOBBY
+
+
+impl Lobby {
+
+    pub fn new(grace: Duration, store: Store) -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        Self { clients: HashMap::new(), rooms: HashMap::new(), next_id: 0, rng: Rng::new(seed), grace, store }
+    }
+
+
+    /// Registra una conexión nueva que recibe sus mensajes por ese canal
+    pub fn connect(&mut self, sender: Sender<ServerMessage>) -> ClientId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.clients.insert(id, Client { sender, room: None, account: None, said: VecDeque::new() });
+        self.send(id, ServerMessage::Rooms(self.open_rooms()));
+        id
+    }
+
+
+    /// Da de baja la conexión. A media partida se le guarda el asiento; si no, sale de su sala.
+    pub fn disconnect(&mut self, client: ClientId) {
+        let room = self.clients.get(&client).and_then(|c| c.room.clone());
+        let held = room.as_deref().and_then(|code| {
+            let room = self.rooms.get_mut(code)?;
+            let seat = room.seat_of(client).filter(|_| !room.is_finished())?;
+            room.seats[seat.index()].as_mut()?.presence = Presence::Away(Instant::now());
+            Some((code.to_string(), room.players().collect::<Vec<ClientId>>()))
+        });
+
+        match held {
+            Some((code, others)) => {
+                for other in others {
+                    self.send(other, ServerMessage::OpponentAway(self.grace.as_secs() as u32));
+                }
+                self.sync_clock(&code);
+            },
+            None => self.leave(client),
+        }
+
+        self.clients.remove(&client);
+        self.broadcast_rooms();
+    }
+
+
+    /// Libera los asientos guardados cuyo tiempo de gracia ya pasó.
+    /// Si la partida ya había empezado, su jugador la pierde.
+    pub fn expire(&mut self, now: Instant) {
+        let expired: Vec<(String, Player)> = self.rooms.iter()
+            .flat_map(|(code, room)| room.expired(now, self.grace).into_iter().map(|seat| (code.clone(), seat)))
+            .collect();
+
+        if expired.is_empty() {
+            return;
+        }
+        for (code, seat) in expired {
+            self.forfeit(&code, seat, "did not come back");
+            self.vacate(&code, seat);
+        }
+        self.broadcast_rooms();
+    }
+
+
+    /// Termina las partidas en las que a algún jugador se le acabó el tiempo
+    pub fn flag(&mut self, now: Instant) {
+        let flagged: Vec<(String, Player)> = self.rooms.iter_mut()
+            .filter_map(|(code, room)| Some((code.clone(), room.flag(now)?)))
+            .collect();
+
+        if flagged.is_empty() {
+            return;
+        }
+        for (code, player) in flagged {
+            self.flagged(&code, player);
+        }
+        self.broadcast_rooms();
+    }
+
+
+    /// Avisa a la sala de que al jugador se le acabó el tiempo
+    fn flagged(&mut self, code: &str, player: Player) {
+        self.broadcast_state(code);
+        self.notice(code, format!("{} ran out of time, {} wins", player.symbol(), player.other().symbol()));
+        self.archive(code);
+    }
+
+
+    /// Pone el reloj de la sala de acuerdo con sus asientos y se lo manda a sus clientes
+    fn sync_clock(&mut self, code: &str) {
+        let now = Instant::now();
+        let Some(room) = self.rooms.get_mut(code) else {
+            return;
+        };
+
+        if let Some(player) = room.flag(now) {
+            self.flagged(code, player);
+            return;
+        }
+        room.sync_clock(now);
+        if let Some(clock) = room.clock {
+            let running = room.running(now);
+            self.broadcast(code, ServerMessage::Clock { remaining: clock.remaining, running });
+        }
+    }
+
+
+    fn send(&self, client: ClientId, message: ServerMessage) {
+        if let Some(c) = self.clients.get(&client) {
+            // Si la conexión ya se cerró, se dará de baja ella misma
+            let _ = c.sender.send(message);
+        }
+    }
+
+
+    /// Mensajes con la partida de la sala: las jugadas, el reloj y, si terminó
+    /// por abandono, por acuerdo o por tiempo, cómo terminó
+    fn state(room: &Room) -> Vec<ServerMessage> {
+        let mut messages = vec![ServerMessage::State(room.moves())];
+        if let Some(clock) = room.clock {
+            messages.push(ServerMessage::Clock { remaining: clock.remaining, running: room.running(Instant::now()) });
+        }
+        if let Some(result) = room.game.result().filter(|result| result.is_early()) {
+            messages.push(ServerMessage::Ended(result));
+        }
+        messages
+    }
+
+
+    /// Manda la partida de la sala a todos sus clientes
+    fn broadcast_state(&self, code: &str) {
+        if let Some(room) = self.rooms.get(code) {
+            for message in Self::state(room) {
+                self.broadcast(code, message);
+            }
+        }
+    }
+
+
+    /// Salas con la partida en juego, en las que se puede entrar o mirar, ordenadas por código
+    fn open_rooms(&self) -> Vec<RoomInfo> {
+        let mut rooms: Vec<RoomInfo> = self.rooms.iter()
+            .filter(|(_, room)| !room.is_finished())
+            .map(|(code, room)| RoomInfo {
+                code: code.clone(),
+                settings: room.settings,
+                seat: room.open_seat(),
+            })
+            .collect();
+        rooms.sort_by(|a, b| a.code.cmp(&b.code));
+        rooms
+    }
+
+
+    /// Manda la lista de salas abiertas a los clientes que no están en ninguna
+    fn broadcast_rooms(&self) {
+        let rooms = self.open_rooms();
+        for (&id, client) in &self.clients {
+            if client.room.is_none() {
+                self.send(id, ServerMessage::Rooms(rooms.clone()));
+            }
+        }
+    }
+
+
+    pub fn handle(&mut self, client: ClientId, message: ClientMessage) {
+        let result = match message {
+            ClientMessage::List => {
+                self.send(client, ServerMessage::Rooms(self.open_rooms()));
+                return;
+            },
+            ClientMessage::Login { name, key } => self.login(client, &name, key),
+            ClientMessage::Ratings => {
+                match self.store.ratings() {
+                    Ok(ratings) => self.send(client, ServerMessage::Ratings(ratings)),
+                    Err(err) => {
+                        eprintln!("could not read the ratings: {}", err);
+                        self.send(client, ServerMessage::Error("ratings are not available".to_string()));
+                    },
+                }
+                return;
+            },
+            ClientMessage::Create { settings, side } => {
+                self.create(client, settings, side);
+                Ok(())
+            },
+            ClientMessage::Join(code) => self.join(client, &code),
+            ClientMessage::Watch(code) => self.watch(client, &code),
+            ClientMessage::Resume(token) => self.resume(client, &token),
+            ClientMessage::Move(mv) => self.play(client, mv),
+            ClientMessage::Say(text) => {
+                self.say(client, text);
+                return;
+            },
+            ClientMessage::React(emoji) => {
+                self.react(client, emoji);
+                return;
+            },
+            ClientMessage::Offer(offer) => self.propose(client, offer),
+            ClientMessage::Accept(offer) => self.answer(client, offer, true),
+            ClientMessage::Decline(offer) => self.answer(client, offer, false),
+            ClientMessage::Resign => self.resign(client),
+            ClientMessage::Leave => {
+                self.leave(client);
+                Ok(())
+            },
+        };
+
+        match result {
+            Ok(()) => self.broadcast_rooms(),
+            Err(err) => self.send(client, ServerMessage::Error(err)),
+        }
+    }
+
+
+    /// Entra con la cuenta, o la crea si no existe. Sin clave se crea una nueva.
+    fn login(&mut self, client: ClientId, name: &str, key: Option<String>) -> Result<(), String> {
+        if key.as_ref().is_some_and(|key| key.len() > MAX_KEY_LENGTH) {
+            return Err("invalid account key".to_string());
+        }
+
+        let key = key.unwrap_or_else(new_secret);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let name = self.store.login(name, &key, now).map_err(|err| err.to_string())?;
+
+        if let Some(c) = self.clients.get_mut(&client) {
+            c.account = Some(name.clone());
+        }
+        self.send(client, ServerMessage::Account { name, key });
+        Ok(())
+    }
+
+
+    /// Sienta al cliente en el asiento de la sala y le manda la sala, su sesión y la partida
+    fn seat(&mut self, client: ClientId, code: &str, seat: Player) {
+        let account = self.clients.get(&client).and_then(|c| c.account.clone());
+        let room = self.rooms.get_mut(code).expect("the room exists");
+        let token = new_secret();
+        room.seats[seat.index()] = Some(Seat::new(client, token.clone(), account));
+        let settings = room.settings;
+
+        if let Some(c) = self.clients.get_mut(&client) {
+            c.room = Some(code.to_string());
+        }
+
+        self.send(client, ServerMessage::Joined { room: code.to_string(), seat, settings });
+        self.send(client, ServerMessage::Session(token));
+    }
+
+
+    fn new_code(&mut self) -> String {
+        loop {
+            let code: String = (0..CODE_LENGTH)
+                .map(|_| CODE_ALPHABET[self.rng.below(CODE_ALPHABET.len())] as char)
+                .collect();
+            if !self.rooms.contains_key(&code) {
+                return code;
+            }
+        }
+    }
+
+
+    /// Crea una sala y sienta al cliente con las fichas que eligió o con unas al azar
+    fn create(&mut self, client: ClientId, settings: RoomSettings, side: Option<Player>) {
+        self.leave(client);
+
+        let seat = side.unwrap_or_else(|| if self.rng.below(2) == 0 { Player::First } else { Player::Second });
+        let code = self.new_code();
+        self.rooms.insert(code.clone(), Room::new(settings));
+
+        self.seat(client, &code, seat);
+        self.broadcast_state(&code);
+    }
+
+
+    /// Sienta al cliente en el asiento libre de la sala
+    fn join(&mut self, client: ClientId, code: &str) -> Result<(), String> {
+        let room = self.rooms.get(code).ok_or_else(|| format!("no room {}", code))?;
+        if room.seat_of(client).is_some() {
+            return Err(format!("already in room {}", code));
+        }
+        if room.is_started() {
+            return Err(format!("the game in room {} has already started", code));
+        }
+        if room.free_seat().is_none() {
+            return Err(format!("room {} is full", code));
+        }
+
+        self.leave(client);
+
+        let seat = self.rooms[code].free_seat().expect("the room has a free seat");
+        self.seat(client, code, seat);
+        self.greet(code, seat);
+        self.rooms.get_mut(code).expect("the room exists").sync_clock(Instant::now());
+        self.broadcast_state(code);
+        self.archive(code);
+        Ok(())
+    }
+
+
+    /// Vuelve a sentar al cliente en el asiento de su sesión y le manda la partida entera
+    fn resume(&mut self, client: ClientId, token: &str) -> Result<(), String> {
+        let (code, seat) = self.rooms.iter()
+            .find_map(|(code, room)| Some((code.clone(), room.seat_with_token(token)?)))
+            .ok_or("session expired")?;
+        if matches!(self.rooms[&code].seats[seat.index()].as_ref().map(|seat| seat.presence), Some(Presence::Connected(_))) {
+            return Err("session already in use".to_string());
+        }
+
+        self.leave(client);
+
+        // El asiento sigue siendo de la cuenta con la que se sentó
+        let room = self.rooms.get_mut(&code).expect("the room exists");
+        let held = room.seats[seat.index()].as_mut().expect("the seat is held");
+        held.presence = Presence::Connected(client);
+        let (token, settings) = (held.token.clone(), room.settings);
+
+        if let Some(c) = self.clients.get_mut(&client) {
+            c.room = Some(code.clone());
+        }
+
+        self.send(client, ServerMessage::Joined { room: code.clone(), seat, settings });
+        self.send(client, ServerMessage::Session(token));
+        self.greet(&code, seat);
+        self.rooms.get_mut(&code).expect("the room exists").sync_clock(Instant::now());
+        self.broadcast_state(&code);
+        self.archive(&code);
+        Ok(())
+    }
+
+
+    /// Presenta al jugador recién sentado y a su rival
+    fn greet(&self, code: &str, seat: Player) {
+        let Some(room) = self.rooms.get(code) else {
+            return;
+        };
+        let (Some(player), Some(opponent)) = (&room.seats[seat.index()], &room.seats[seat.other().index()]) else {
+            return;
+        };
+
+        match (player.client(), opponent.presence) {
+            (Some(client), Presence::Connected(opponent)) => {
+                self.send(client, ServerMessage::Opponent(true));
+                self.send(opponent, ServerMessage::Opponent(true));
+            },
+            (Some(client), Presence::Away(since)) => {
+                let left = self.grace.saturating_sub(since.elapsed());
+                self.send(client, ServerMessage::OpponentAway(left.as_secs() as u32));
+            },
+            (None, _) => {},
+        }
+    }
+
+
+    /// Añade al cliente a los espectadores de la sala y le manda la partida entera
+    fn watch(&mut self, client: ClientId, code: &str) -> Result<(), String> {
+        let room = self.rooms.get(code).ok_or_else(|| format!("no room {}", code))?;
+        if room.clients().any(|other| other == client) {
+            return Err(format!("already in room {}", code));
+        }
+
+        self.leave(client);
+
+        let room = self.rooms.get_mut(code).expect("the room exists");
+        room.spectators.push(client);
+        let (settings, state) = (room.settings, Self::state(room));
+
+        if let Some(c) = self.clients.get_mut(&client) {
+            c.room = Some(code.to_string());
+        }
+
+        self.send(client, ServerMessage::Watching { room: code.to_string(), settings });
+        for message in state {
+            self.send(client, message);
+        }
+        Ok(())
+    }
+
+
+    fn play(&mut self, client: ClientId, mv: Move) -> Result<(), String> {
+        let (code, seat) = self.seated(client)?;
+        let room = self.rooms.get_mut(&code).expect("the room exists");
+        let now = Instant::now();
+
+        // El tiempo se comprueba al jugar, sin esperar a la próxima revisión
+        if let Some(player) = room.flag(now) {
+            self.flagged(&code, player);
+            return Err(if player == seat { "out of time" } else { "the game is over" }.to_string());
+        }
+
+        let won = room.game.bitboard().won(seat);
+        room.play(seat, mv, now)?;
+        let won = room.game.bitboard().won(seat) & !won;
+        let result = room.settings.ruleset.result(&room.game);
+
+        self.broadcast_state(&code);
+
+        self.notice(&code, format!("{} played {}", seat.symbol(), mv));
+        if won != 0 {
+            self.notice(&code, format!("{} won the {} board", seat.symbol(), BOARD_NAMES[won.trailing_zeros() as usize]));
+        }
+        match result.map(GameResult::winner) {
+            Some(Some(winner)) => self.notice(&code, format!("{} wins the game", winner.symbol())),
+            Some(None) => self.notice(&code, "The game is drawn".to_string()),
+            None => {},
+        }
+        self.archive(&code);
+        Ok(())
+    }
+
+
+    /// Sala del cliente y fichas con las que juega en ella
+    fn seated(&self, client: ClientId) -> Result<(String, Player), String> {
+        let code = self.clients.get(&client).and_then(|c| c.room.clone()).ok_or("not in a room")?;
+        let seat = self.rooms.get(&code).and_then(|room| room.seat_of(client)).ok_or("not seated")?;
+        Ok((code, seat))
+    }
+
+
+    fn propose(&mut self, client: ClientId, offer: Offer) -> Result<(), String> {
+        let (code, seat) = self.seated(client)?;
+        self.rooms.get_mut(&code).expect("the room exists").propose(seat, offer)?;
+
+        self.broadcast(&code, ServerMessage::Offered { offer, from: seat });
+        self.notice(&code, match offer {
+            Offer::Draw => format!("{} offers a draw", seat.symbol()),
+            Offer::Takeback => format!("{} asks to take back their last move", seat.symbol()),
+        });
+        Ok(())
+    }
+
+
+    fn answer(&mut self, client: ClientId, offer: Offer, accept: bool) -> Result<(), String> {
+        let (code, seat) = self.seated(client)?;
+        let room = self.rooms.get_mut(&code).expect("the room exists");
+        let now = Instant::now();
+
+        // Una partida que ya se perdió por tiempo no se puede deshacer
+        if let Some(player) = room.flag(now) {
+            self.flagged(&code, player);
+            return Err("the game is over".to_string());
+        }
+        room.answer(seat, offer, accept, now)?;
+
+        if accept {
+            self.broadcast_state(&code);
+        } else {
+            self.broadcast(&code, ServerMessage::Declined { offer, by: seat });
+        }
+        self.notice(&code, match (offer, accept) {
+            (Offer::Draw, true) => "Draw agreed".to_string(),
+            (Offer::Takeback, true) => format!("{} accepts the takeback", seat.symbol()),
+            (_, false) => format!("{} declines the {}", seat.symbol(), offer),
+        });
+        self.archive(&code);
+        Ok(())
+    }
+
+
+    fn resign(&mut self, client: ClientId) -> Result<(), String> {
+        let (code, seat) = self.seated(client)?;
+        self.rooms.get_mut(&code).expect("the room exists").resign(seat, Instant::now())?;
+
+        self.broadcast_state(&code);
+        self.notice(&code, format!("{} resigns, {} wins", seat.symbol(), seat.other().symbol()));
+        self.archive(&code);
+        Ok(())
+    }
+
+
+    /// Da por perdida la partida en juego al jugador que no sigue en ella
+    fn forfeit(&mut self, code: &str, seat: Player, reason: &str) {
+        let Some(room) = self.rooms.get_mut(code) else {
+            return;
+        };
+        if !room.is_started() || room.resign(seat, Instant::now()).is_err() {
+            return;
+        }
+
+        self.broadcast_state(code);
+        self.notice(code, format!("{} {}, {} wins", seat.symbol(), reason, seat.other().symbol()));
+        self.archive(code);
+    }
+
+
+    /// Guarda la partida de la sala en el archivo si terminó y todavía no se guardó.
+    /// Si la jugaron dos cuentas, avisa de cómo cambiaron sus puntuaciones.
+    fn archive(&mut self, code: &str) {
+        let Some(room) = self.rooms.get_mut(code) else {
+            return;
+        };
+        let Some(result) = room.settings.ruleset.result(&room.game).filter(|_| !room.archived) else {
+            return;
+        };
+        room.archived = true;
+
+        let ended = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        // La partida es de quienes la empezaron, aunque luego se vaciara algún asiento
+        let accounts = room.accounts.clone().unwrap_or_default();
+        let players = accounts.each_ref().map(Option::as_deref);
+        let termination = termination(result);
+
+        let mut record = GameRecord::from_game(&room.game);
+        record.result = RecordResult::Finished(result);
+        record.set_tag("Event", "Online game");
+        record.set_tag("Room", code);
+        record.set_tag("Date", date(ended));
+        record.set_tag("X", players[0].unwrap_or("?"));
+        record.set_tag("O", players[1].unwrap_or("?"));
+        record.set_tag("Ruleset", room.settings.ruleset.to_string());
+        record.set_tag("TimeControl", room.settings.time_control.to_string());
+        record.set_tag("Termination", termination);
+
+        let game = FinishedGame { record: &record, players, settings: room.settings, termination, ended };
+        match self.store.archive(&game) {
+            Ok(Some(changes)) => {
+                let changes: Vec<String> = changes.iter()
+                    .map(|change| format!(
+                        "{} {:.0} ({:+.0})",
+                        change.name, change.after.rating, change.after.rating.round() - change.before.rating.round(),
+                    ))
+                    .collect();
+                self.notice(code, format!("Ratings: {}", changes.join(", ")));
+            },
+            Ok(None) => {},
+            Err(err) => eprintln!("could not archive the game of room {}: {}", code, err),
+        }
+    }
+
+
+    /// Manda el mensaje a todos los clientes de la sala
+    fn broadcast(&self, code: &str, message: ServerMessage) {
+        if let Some(room) = self.rooms.get(code) {
+            for client in room.clients() {
+                self.send(client, message.clone());
+            }
+        }
+    }
+
+
+    /// Aviso de la sala en el chat
+    fn notice(&self, code: &str, text: String) {
+        self.broadcast(code, ServerMessage::Notice(text));
+    }
+
+
+    /// Sala del cliente y fichas con las que juega en ella, si puede hablar ahora.
+    /// Si no, le dice por qué.
+    fn speaker(&mut self, client: ClientId) -> Option<(String, Option<Player>)> {
+        let now = Instant::now();
+        let c = self.clients.get_mut(&client)?;
+
+        let Some(code) = c.room.clone() else {
+            self.send(client, ServerMessage::Error("not in a room".to_string()));
+            return None;
+        };
+
+        while c.said.front().is_some_and(|&at| now.duration_since(at) >= CHAT_WINDOW) {
+            c.said.pop_front();
+        }
+        if c.said.len() >= CHAT_BURST {
+            self.send(client, ServerMessage::Error("too many messages, slow down".to_string()));
+            return None;
+        }
+        c.said.push_back(now);
+
+        let seat = self.rooms.get(&code).and_then(|room| room.seat_of(client));
+        Some((code, seat))
+    }
+
+
+    fn say(&mut self, client: ClientId, text: String) {
+        let text = text.trim();
+        if text.chars().count() > MAX_CHAT_LENGTH {
+            self.send(client, ServerMessage::Error(format!("messages are limited to {} characters", MAX_CHAT_LENGTH)));
+            return;
+        }
+
+        if let Some((code, from)) = self.speaker(client) {
+            self.broadcast(&code, ServerMessage::Chat { from, text: text.to_string() });
+        }
+    }
+
+
+    fn react(&mut self, client: ClientId, emoji: String) {
+        if !REACTIONS.contains(&emoji.as_str()) {
+            self.send(client, ServerMessage::Error(format!("unknown reaction {}", emoji)));
+            return;
+        }
+
+        if let Some((code, from)) = self.speaker(client) {
+            self.broadcast(&code, ServerMessage::Reaction { from, emoji });
+        }
+    }
+
+
+    /// Saca al cliente de su sala
+    fn leave(&mut self, client: ClientId) {
+        let Some(code) = self.clients.get_mut(&client).and_then(|c| c.room.take()) else {
+            return;
+        };
+        let Some(room) = self.rooms.get_mut(&code) else {
+            return;
+        };
+
+        match room.seat_of(client) {
+            Some(seat) => {
+                self.forfeit(&code, seat, "left the game");
+                self.vacate(&code, seat);
+            },
+            None => {
+                // Que se vaya un espectador no cambia nada para los demás
+                room.spectators.retain(|&spectator| spectator != client);
+            },
+        }
+    }
+
+
+    /// Libera el asiento y avisa al rival. Las salas sin jugadores se cierran
+    /// y sus espectadores vuelven al vestíbulo.
+    fn vacate(&mut self, code: &str, seat: Player) {
+        let Some(room) = self.rooms.get_mut(code) else {
+            return;
+        };
+        room.seats[seat.index()] = None;
+        room.offer = None;
+
+        if room.is_empty() {
+            let room = self.rooms.remove(code).expect("the room exists");
+            for spectator in room.spectators {
+                if let Some(c) = self.clients.get_mut(&spectator) {
+                    c.room = None;
+                }
+                self.send(spectator, ServerMessage::Closed);
+            }
+        } else {
+            let others: Vec<ClientId> = room.players().collect();
+            for other in others {
+                self.send(other, ServerMessage::Opponent(false));
+            }
+            self.sync_clock(code);
+        }
+    }
+}
+
+
+/// Fecha `AAAA.MM.DD` de un momento en segundos desde la época, en UTC
+fn date(seconds: u64) -> String {
+    let date = Date::from_secs(seconds);
+    format!("{:04}.{:02}.{:02}", date.year, date.month, date.day)
+}
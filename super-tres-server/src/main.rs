@@ -0,0 +1,126 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v5                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Servidor de partidas en red. Aloja salas de dos jugadores por WebSocket
+// y valida todas las jugadas con el motor compartido; el protocolo está en
+// `super_tres_engine::net`. Un hilo aparte termina las partidas en las que
+// se acaba el tiempo de un jugador y libera los asientos de los jugadores
+// desconectados cuando se acaba su tiempo de gracia. Las cuentas, sus
+// puntuaciones y el archivo de partidas se guardan en una base de datos SQLite;
+// el archivo se puede consultar por HTTP en el mismo puerto, bajo `/api/`.
+//
+//     cargo run --release -p super-tres-server -- --addr 0.0.0.0:9000
+
+
+
+mod api;
+mod connection;
+mod glicko;
+mod lobby;
+mod room;
+mod store;
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+
+use lobby::Lobby;
+use store::Store;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ARGUMENTS
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Multiplayer server for networked games over WebSockets")]
+struct Args {
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:9000")]
+    addr: String,
+
+    /// Seconds a disconnected player's seat is held for them to reconnect
+    #[arg(long, default_value_t = 60)]
+    grace: u64,
+
+    /// SQLite database with the accounts, ratings and game archive
+    #[arg(long, default_value = "super-tres.db")]
+    db: String,
+}
+
+
+/// Cada cuánto se buscan relojes y asientos con el tiempo agotado
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MAIN
+
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    // El vestíbulo y la API del archivo usan cada uno su conexión
+    let (store, archive) = match Store::open(&args.db).and_then(|store| Ok((store, Store::open(&args.db)?))) {
+        Ok(stores) => stores,
+        Err(err) => {
+            eprintln!("could not open the database {}: {}", args.db, err);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let listener = match TcpListener::bind(&args.addr) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("could not listen on {}: {}", args.addr, err);
+            return ExitCode::FAILURE;
+        },
+    };
+    eprintln!("Listening on ws://{}", args.addr);
+    eprintln!("Game archive at http://{}{}games", args.addr, api::PREFIX);
+
+    let lobby = Arc::new(Mutex::new(Lobby::new(Duration::from_secs(args.grace), store)));
+    let archive = Arc::new(Mutex::new(archive));
+
+    {
+        let lobby = Arc::clone(&lobby);
+        thread::spawn(move || loop {
+            thread::sleep(TICK_INTERVAL);
+            let mut lobby = connection::lock(&lobby);
+            let now = Instant::now();
+            lobby.flag(now);
+            lobby.expire(now);
+        });
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let (lobby, archive) = (Arc::clone(&lobby), Arc::clone(&archive));
+                thread::spawn(move || connection::serve(stream, lobby, archive));
+            },
+            Err(err) => eprintln!("connection failed: {}", err),
+        }
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,366 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v9                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Sala de juego: la partida, quién ocupa cada asiento y quién la mira.
+// Las jugadas se validan aquí con el motor compartido. Cada asiento tiene
+// la sesión de su jugador, que lo conserva un tiempo si se desconecta.
+// Aquí se resuelven también las propuestas de tablas y de deshacer jugadas
+// y los abandonos. El reloj empieza a correr con la primera jugada y solo
+// corre mientras los dos jugadores están conectados. Al empezar la partida
+// se apuntan las cuentas de los dos jugadores, que son con las que se guarda
+// en el archivo; desde entonces nadie más puede sentarse en la sala.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::time::{Duration, Instant};
+
+use super_tres_engine::board::Player;
+use super_tres_engine::clock::Clock;
+use super_tres_engine::game::{Game, GameResult, Move};
+use super_tres_engine::net::{Offer, RoomSettings};
+
+use crate::lobby::ClientId;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Conexión del jugador de un asiento
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Presence {
+    Connected(ClientId),
+
+    /// Desconectado desde ese momento; el asiento se le guarda hasta que pase el tiempo de gracia
+    Away(Instant),
+}
+
+
+/// Asiento ocupado
+pub struct Seat {
+
+    /// Sesión con la que el jugador vuelve al asiento
+    pub token: String,
+
+    /// Cuenta con la que se sentó. None si no entró con ninguna.
+    pub account: Option<String>,
+    pub presence: Presence,
+}
+
+
+/// Sala con una partida entre dos clientes
+pub struct Room {
+    pub game: Game,
+    pub settings: RoomSettings,
+
+    /// Asientos de X y de O
+    pub seats: [Option<Seat>; 2],
+
+    /// Clientes que miran la partida sin jugar
+    pub spectators: Vec<ClientId>,
+
+    /// Propuesta pendiente y quién la hizo. Se retira con la siguiente jugada.
+    pub offer: Option<(Offer, Player)>,
+
+    /// Reloj de la partida. None si se juega sin tiempo.
+    pub clock: Option<Clock>,
+
+    /// Jugador cuyo reloj corre y desde cuándo
+    running: Option<(Player, Instant)>,
+
+    /// Cuentas de X y de O al empezar la partida. None si todavía no empezó.
+    pub accounts: Option<[Option<String>; 2]>,
+
+    /// Milisegundos de incremento que ganó cada jugada, para quitarlos si se deshace
+    increments: Vec<u64>,
+
+    /// La partida terminada ya se guardó en el archivo
+    pub archived: bool,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ROOMS
+
+
+impl Seat {
+
+    pub fn new(client: ClientId, token: String, account: Option<String>) -> Self {
+        Self { token, account, presence: Presence::Connected(client) }
+    }
+
+
+    /// Cliente sentado, si está conectado
+    pub fn client(&self) -> Option<ClientId> {
+        match self.presence {
+            Presence::Connected(client) => Some(client),
+            Presence::Away(_) => None,
+        }
+    }
+}
+
+
+impl Room {
+
+    pub fn new(settings: RoomSettings) -> Self {
+        Self {
+            game: Game::new(),
+            settings,
+            seats: [None, None],
+            spectators: Vec::new(),
+            offer: None,
+            clock: Clock::new(settings.time_control),
+            running: None,
+            accounts: None,
+            increments: Vec::new(),
+            archived: false,
+        }
+    }
+
+
+    /// Fichas con las que juega el cliente, si está sentado
+    pub fn seat_of(&self, client: ClientId) -> Option<Player> {
+        [Player::First, Player::Second].into_iter()
+            .find(|player| self.seats[player.index()].as_ref().and_then(Seat::client) == Some(client))
+    }
+
+
+    /// Fichas del asiento de esa sesión
+    pub fn seat_with_token(&self, token: &str) -> Option<Player> {
+        [Player::First, Player::Second].into_iter()
+            .find(|player| self.seats[player.index()].as_ref().is_some_and(|seat| seat.token == token))
+    }
+
+
+    /// Primer asiento libre. Los asientos guardados a un jugador desconectado no lo están.
+    pub fn free_seat(&self) -> Option<Player> {
+        [Player::First, Player::Second].into_iter().find(|player| self.seats[player.index()].is_none())
+    }
+
+
+    /// Jugadores conectados de la sala
+    pub fn players(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.seats.iter().flatten().filter_map(Seat::client)
+    }
+
+
+    /// Jugadores y espectadores de la sala
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.players().chain(self.spectators.iter().copied())
+    }
+
+
+    /// La sala no tiene jugadores, ni conectados ni con el asiento guardado.
+    /// Los espectadores solos no la mantienen abierta.
+    pub fn is_empty(&self) -> bool {
+        self.seats.iter().all(Option::is_none)
+    }
+
+
+    /// La partida terminó con las reglas de la sala
+    pub fn is_finished(&self) -> bool {
+        self.settings.ruleset.result(&self.game).is_some()
+    }
+
+
+    /// La partida empezó: ya se jugó, se aceptó una propuesta o se abandonó
+    pub fn is_started(&self) -> bool {
+        self.accounts.is_some()
+    }
+
+
+    /// Asiento que se ofrece en el vestíbulo: la sala tiene sitio y la partida no empezó
+    pub fn open_seat(&self) -> Option<Player> {
+        self.free_seat().filter(|_| !self.is_started())
+    }
+
+
+    /// Empieza la partida con quienes están sentados, si no había empezado
+    fn start(&mut self) {
+        if self.accounts.is_none() {
+            self.accounts = Some(self.seats.each_ref().map(|seat| seat.as_ref().and_then(|seat| seat.account.clone())));
+        }
+    }
+
+
+    /// Asientos de jugadores desconectados cuyo tiempo de gracia ya pasó
+    pub fn expired(&self, now: Instant, grace: Duration) -> Vec<Player> {
+        [Player::First, Player::Second].into_iter()
+            .filter(|player| matches!(
+                self.seats[player.index()].as_ref().map(|seat| seat.presence),
+                Some(Presence::Away(since)) if now.duration_since(since) >= grace
+            ))
+            .collect()
+    }
+
+
+    /// Comprueba que la partida está en juego con los dos asientos ocupados
+    fn in_progress(&self) -> Result<(), String> {
+        if self.seats.iter().any(Option::is_none) {
+            return Err("waiting for an opponent".to_string());
+        }
+        if self.game.turn().is_none() {
+            return Err("the game is over".to_string());
+        }
+        Ok(())
+    }
+
+
+    /// Jugadas que hay que deshacer para que el jugador repita su última jugada.
+    /// None si todavía no jugó.
+    fn takeback_plies(&self, seat: Player) -> Option<usize> {
+        let plies = if self.game.turn() == Some(seat) { 2 } else { 1 };
+        let own = if seat == Player::First { 1 } else { 2 };
+        (self.game.ply() >= plies.max(own)).then_some(plies)
+    }
+
+
+    /// Jugador cuyo reloj corre y milisegundos que lleva su turno
+    pub fn running(&self, now: Instant) -> Option<(Player, u64)> {
+        self.running.map(|(player, since)| (player, now.duration_since(since).as_millis() as u64))
+    }
+
+
+    /// Para el reloj que corre, con el incremento si el turno acabó en una jugada.
+    /// Devuelve false si se le acabó el tiempo a su jugador.
+    fn stop_clock(&mut self, now: Instant, moved: bool) -> bool {
+        let running = self.running(now);
+        self.running = None;
+        match (&mut self.clock, running) {
+            (Some(clock), Some((player, elapsed))) => clock.stop(player, elapsed, moved),
+            _ => true,
+        }
+    }
+
+
+    /// Pone en marcha o para el reloj según cómo esté la sala: corre desde la
+    /// primera jugada mientras la partida sigue y los dos jugadores están conectados
+    pub fn sync_clock(&mut self, now: Instant) {
+        let runs = self.clock.is_some() && self.game.ply() > 0 && self.players().count() == 2;
+        let turn = self.game.turn().filter(|_| runs);
+
+        if self.running.map(|(player, _)| player) == turn {
+            return;
+        }
+
+        if let Some((player, _)) = self.running {
+            if !self.stop_clock(now, false) {
+                self.game.end(GameResult::OutOfTime(player));
+                self.offer = None;
+            }
+        }
+        self.running = self.game.turn().filter(|_| runs).map(|player| (player, now));
+    }
+
+
+    /// Termina la partida si al jugador cuyo reloj corre se le acabó el tiempo.
+    /// Devuelve ese jugador.
+    pub fn flag(&mut self, now: Instant) -> Option<Player> {
+        let (player, elapsed) = self.running(now)?;
+        if self.clock?.left(player, elapsed) > 0 {
+            return None;
+        }
+
+        self.stop_clock(now, false);
+        self.game.end(GameResult::OutOfTime(player));
+        self.offer = None;
+        Some(player)
+    }
+
+
+    /// Juega la jugada del jugador si es su turno y es legal
+    pub fn play(&mut self, seat: Player, mv: Move, now: Instant) -> Result<(), String> {
+        self.in_progress()?;
+        if self.game.turn() != Some(seat) {
+            return Err("not your turn".to_string());
+        }
+        if !self.game.is_legal(mv) {
+            return Err(format!("illegal move {}", mv));
+        }
+
+        // Lo que le queda al acabar el turno, para saber cuánto incremento gana
+        let left = self.running(now).zip(self.clock).map(|((player, elapsed), clock)| clock.left(player, elapsed));
+
+        self.start();
+        self.game.apply(mv);
+        self.offer = None;
+        self.stop_clock(now, true);
+        let increment = left.zip(self.clock).map_or(0, |(left, clock)| clock.remaining[seat.index()] - left);
+        self.increments.push(increment);
+        self.sync_clock(now);
+        Ok(())
+    }
+
+
+    /// Deja pendiente la propuesta del jugador hasta que el rival responda
+    pub fn propose(&mut self, seat: Player, offer: Offer) -> Result<(), String> {
+        self.in_progress()?;
+        if self.offer.is_some() {
+            return Err("there is already a pending offer".to_string());
+        }
+        if offer == Offer::Takeback && self.takeback_plies(seat).is_none() {
+            return Err("no move to take back".to_string());
+        }
+
+        self.offer = Some((offer, seat));
+        Ok(())
+    }
+
+
+    /// Acepta o rechaza la propuesta pendiente del rival
+    pub fn answer(&mut self, seat: Player, offer: Offer, accept: bool, now: Instant) -> Result<(), String> {
+        if self.offer != Some((offer, seat.other())) {
+            return Err(format!("no {} offer to answer", offer));
+        }
+        self.offer = None;
+
+        if accept {
+            self.start();
+            match offer {
+                Offer::Draw => self.game.end(GameResult::AgreedDraw),
+                Offer::Takeback => {
+                    let plies = self.takeback_plies(seat.other()).expect("the offer was checked");
+                    // El turno en curso no cuenta para nadie y cada jugada deshecha
+                    // devuelve el incremento que ganó
+                    self.running = None;
+                    for _ in 0..plies {
+                        self.game.undo();
+                        let increment = self.increments.pop().unwrap_or(0);
+                        if let (Some(clock), Some(player)) = (&mut self.clock, self.game.turn()) {
+                            clock.remaining[player.index()] = clock.remaining[player.index()].saturating_sub(increment);
+                        }
+                    }
+                },
+            }
+            self.sync_clock(now);
+        }
+        Ok(())
+    }
+
+
+    pub fn resign(&mut self, seat: Player, now: Instant) -> Result<(), String> {
+        self.in_progress()?;
+        self.start();
+        self.game.end(GameResult::Resigned(seat));
+        self.offer = None;
+        self.sync_clock(now);
+        Ok(())
+    }
+
+
+    pub fn moves(&self) -> Vec<Move> {
+        self.game.moves().collect()
+    }
+}
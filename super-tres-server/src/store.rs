@@ -0,0 +1,432 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v6                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Base de datos SQLite del servidor: cuentas de los jugadores con su
+// puntuación de Glicko-2 y archivo de las partidas terminadas. Cada partida
+// se guarda entera en el formato de `super_tres_engine::record`, junto con
+// los datos por los que se busca. Una cuenta es un nombre y la clave que el
+// servidor le dio al crearla; quien tiene la clave entra con ese nombre. De
+// la clave solo se guarda su SHA-256 con una sal propia de cada cuenta.
+// El vestíbulo escribe y la API del archivo lee cada uno con su conexión,
+// así que la base de datos va en modo WAL para que las lecturas no esperen.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rusqlite::types::{Type, Value};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use sha2::{Digest, Sha256};
+use super_tres_engine::archive::{ArchiveQuery, ArchivedGame, Date, ARCHIVE_PAGE};
+use super_tres_engine::board::Player;
+use super_tres_engine::net::{RatingInfo, RoomSettings};
+use super_tres_engine::record::GameRecord;
+
+use crate::glicko::Rating;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Tablas de la base de datos. Se crean al abrirla si no existen.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS players (
+        name TEXT PRIMARY KEY COLLATE NOCASE,
+        key TEXT NOT NULL,
+        salt TEXT NOT NULL,
+        rating REAL NOT NULL,
+        deviation REAL NOT NULL,
+        volatility REAL NOT NULL,
+        games INTEGER NOT NULL DEFAULT 0,
+        created INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS games (
+        id INTEGER PRIMARY KEY,
+        x TEXT,
+        o TEXT,
+        ruleset TEXT NOT NULL,
+        time_control TEXT NOT NULL,
+        result TEXT NOT NULL,
+        termination TEXT NOT NULL,
+        plies INTEGER NOT NULL,
+        opening TEXT,
+        rated INTEGER NOT NULL,
+        ended INTEGER NOT NULL,
+        record TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS games_x ON games (x COLLATE NOCASE);
+    CREATE INDEX IF NOT EXISTS games_o ON games (o COLLATE NOCASE);
+    CREATE INDEX IF NOT EXISTS games_ended ON games (ended);
+";
+
+/// Columnas de las partidas del archivo, en el orden de `archived_game`
+const GAME_COLUMNS: &str = "id, x, o, ruleset, time_control, result, termination, plies, opening, rated, ended";
+
+/// Jugadores que se mandan en la clasificación
+const RATINGS_LIMIT: usize = 100;
+
+/// Tiempo que se espera a que otra conexión suelte la base de datos
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+
+
+/// Archivo y cuentas del servidor
+pub struct Store {
+    connection: Connection,
+}
+
+
+/// Partida terminada para guardarla en el archivo
+pub struct FinishedGame<'a> {
+    pub record: &'a GameRecord,
+
+    /// Cuentas de X y de O. None si el jugador no entró con una.
+    pub players: [Option<&'a str>; 2],
+    pub settings: RoomSettings,
+
+    /// Cómo terminó, como en la etiqueta `Termination`
+    pub termination: &'a str,
+
+    /// Segundos desde la época
+    pub ended: u64,
+}
+
+
+/// Puntuación de un jugador antes y después de una partida
+pub struct RatingChange {
+    pub name: String,
+    pub before: Rating,
+    pub after: Rating,
+}
+
+
+/// Error al entrar con una cuenta
+#[derive(Debug)]
+pub enum LoginError {
+
+    /// La cuenta existe y la clave no es la suya
+    Taken(String),
+    Database(rusqlite::Error),
+}
+
+impl fmt::Display for LoginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoginError::Taken(name) => write!(f, "the name {} is taken", name),
+            LoginError::Database(err) => write!(f, "database error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for LoginError {}
+
+impl From<rusqlite::Error> for LoginError {
+    fn from(err: rusqlite::Error) -> Self {
+        LoginError::Database(err)
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// STORE
+
+
+impl Store {
+
+    /// Abre la base de datos del fichero, creándola si no existe
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        connection.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+
+    /// Entra con la cuenta de ese nombre si la clave es la suya, o la crea con
+    /// esa clave si no existe. Devuelve el nombre tal y como se creó la cuenta.
+    pub fn login(&self, name: &str, key: &str, now: u64) -> Result<String, LoginError> {
+        let account: Option<(String, String, String)> = self.connection
+            .query_row("SELECT name, key, salt FROM players WHERE name = ?1", [name], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .optional()?;
+
+        match account {
+            Some((name, stored, salt)) if stored == hash_key(&salt, key) => Ok(name),
+            Some((name, _, _)) => Err(LoginError::Taken(name)),
+            None => {
+                let rating = Rating::default();
+                let salt = new_secret();
+                self.connection.execute(
+                    "INSERT INTO players (name, key, salt, rating, deviation, volatility, created) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![name, hash_key(&salt, key), salt, rating.rating, rating.deviation, rating.volatility, now as i64],
+                )?;
+                Ok(name.to_string())
+            },
+        }
+    }
+
+
+    /// Guarda la partida en el archivo. Si la jugaron dos cuentas distintas,
+    /// actualiza sus puntuaciones y devuelve cómo cambiaron las de X y O.
+    pub fn archive(&mut self, game: &FinishedGame) -> rusqlite::Result<Option<[RatingChange; 2]>> {
+        let transaction = self.connection.transaction()?;
+
+        let rated = match game.players {
+            [Some(x), Some(o)] if !x.eq_ignore_ascii_case(o) => Some([x, o]),
+            _ => None,
+        };
+
+        transaction.execute(
+            "INSERT INTO games (x, o, ruleset, time_control, result, termination, plies, opening, rated, ended, record)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                game.players[0],
+                game.players[1],
+                game.settings.ruleset.to_string(),
+                game.settings.time_control.to_string(),
+                game.record.result.to_string(),
+                game.termination,
+                game.record.moves.len() as i64,
+                game.record.moves.first().map(|mv| mv.to_string()),
+                rated.is_some(),
+                game.ended as i64,
+                game.record.to_string(),
+            ],
+        )?;
+
+        let changes = match rated {
+            Some(names) => {
+                let before = [rating(&transaction, names[0])?, rating(&transaction, names[1])?];
+                let after = [Player::First, Player::Second].map(|player| {
+                    let points = game.record.result.points(player).expect("the game is finished");
+                    before[player.index()].update(&[(before[player.other().index()], points)])
+                });
+
+                for (name, rating) in names.iter().zip(after) {
+                    transaction.execute(
+                        "UPDATE players SET rating = ?2, deviation = ?3, volatility = ?4, games = games + 1 WHERE name = ?1",
+                        params![name, rating.rating, rating.deviation, rating.volatility],
+                    )?;
+                }
+
+                Some([0, 1].map(|i| RatingChange { name: names[i].to_string(), before: before[i], after: after[i] }))
+            },
+            None => None,
+        };
+
+        transaction.commit()?;
+        Ok(changes)
+    }
+
+
+    /// Jugadores con partidas puntuadas, de mayor a menor puntuación
+    pub fn ratings(&self) -> rusqlite::Result<Vec<RatingInfo>> {
+        let mut statement = self.connection.prepare(
+            "SELECT name, rating, deviation, games FROM players WHERE games > 0 ORDER BY rating DESC, name LIMIT ?1"
+        )?;
+        let rows = statement.query_map([RATINGS_LIMIT as i64], |row| {
+            Ok(RatingInfo {
+                name: row.get(0)?,
+                rating: row.get::<_, f64>(1)?.round().max(0.0) as u32,
+                deviation: row.get::<_, f64>(2)?.round() as u32,
+                games: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+
+    /// Partidas del archivo que cumplen los filtros, de la más reciente a la
+    /// más antigua, a partir de la de `offset`. Devuelve también cuántas son en total.
+    pub fn search(&self, query: &ArchiveQuery) -> rusqlite::Result<(u64, Vec<ArchivedGame>)> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(player) = &query.player {
+            conditions.push("(x = ? COLLATE NOCASE OR o = ? COLLATE NOCASE)");
+            values.extend([Value::Text(player.clone()), Value::Text(player.clone())]);
+        }
+        if let Some(ruleset) = query.ruleset {
+            conditions.push("ruleset = ?");
+            values.push(Value::Text(ruleset.to_string()));
+        }
+        if let Some(result) = query.result {
+            conditions.push("result = ?");
+            values.push(Value::Text(result.to_string()));
+        }
+        if let Some(from) = query.from {
+            conditions.push("ended >= ?");
+            values.push(Value::Integer(from.start_secs()));
+        }
+        if let Some(to) = query.to {
+            conditions.push("ended < ?");
+            values.push(Value::Integer(Date::from_days(to.days() + 1).start_secs()));
+        }
+        if let Some(min_plies) = query.min_plies {
+            conditions.push("plies >= ?");
+            values.push(Value::Integer(min_plies.into()));
+        }
+        if let Some(max_plies) = query.max_plies {
+            conditions.push("plies <= ?");
+            values.push(Value::Integer(max_plies.into()));
+        }
+        if let Some(opening) = query.opening {
+            conditions.push("opening = ?");
+            values.push(Value::Text(opening.to_string()));
+        }
+
+        let filter = match conditions.is_empty() {
+            true => String::new(),
+            false => format!(" WHERE {}", conditions.join(" AND ")),
+        };
+
+        let total: i64 = self.connection.query_row(
+            &format!("SELECT COUNT(*) FROM games{}", filter),
+            params_from_iter(&values),
+            |row| row.get(0),
+        )?;
+
+        values.extend([Value::Integer(ARCHIVE_PAGE as i64), Value::Integer(query.offset.into())]);
+        let mut statement = self.connection.prepare(
+            &format!("SELECT {} FROM games{} ORDER BY ended DESC, id DESC LIMIT ? OFFSET ?", GAME_COLUMNS, filter)
+        )?;
+        let games = statement.query_map(params_from_iter(&values), archived_game)?.collect::<rusqlite::Result<_>>()?;
+
+        Ok((total as u64, games))
+    }
+
+
+    /// Partida del archivo con su registro completo
+    pub fn game(&self, id: u64) -> rusqlite::Result<Option<(ArchivedGame, String)>> {
+        self.connection
+            .query_row(
+                &format!("SELECT {}, record FROM games WHERE id = ?1", GAME_COLUMNS),
+                [id as i64],
+                |row| Ok((archived_game(row)?, row.get(11)?)),
+            )
+            .optional()
+    }
+}
+
+
+/// Bytes de las sales, claves y sesiones
+const SECRET_BYTES: usize = 16;
+
+
+/// Secreto nuevo en hexadecimal: sales, claves de cuenta y sesiones. Se saca
+/// del generador del sistema operativo para que no se pueda adivinar.
+pub fn new_secret() -> String {
+    let mut bytes = [0; SECRET_BYTES];
+    getrandom::getrandom(&mut bytes).expect("the system random source is available");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+
+/// SHA-256 en hexadecimal de la clave de una cuenta con su sal
+fn hash_key(salt: &str, key: &str) -> String {
+    Sha256::new()
+        .chain_update(salt)
+        .chain_update(key)
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+
+/// Partida del archivo de una fila con las columnas de `GAME_COLUMNS`
+fn archived_game(row: &Row) -> rusqlite::Result<ArchivedGame> {
+    Ok(ArchivedGame {
+        id: row.get::<_, i64>(0)? as u64,
+        players: [row.get(1)?, row.get(2)?],
+        ruleset: parsed(row, 3)?,
+        time_control: parsed(row, 4)?,
+        result: parsed(row, 5)?,
+        termination: row.get(6)?,
+        plies: row.get(7)?,
+        opening: row.get::<_, Option<String>>(8)?
+            .map(|opening| opening.parse().map_err(|err| rusqlite::Error::FromSqlConversionFailure(8, Type::Text, Box::new(err))))
+            .transpose()?,
+        rated: row.get(9)?,
+        ended: row.get::<_, i64>(10)? as u64,
+    })
+}
+
+
+/// Valor de una columna de texto en el formato del motor
+fn parsed<T>(row: &Row, index: usize) -> rusqlite::Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    row.get::<_, String>(index)?
+        .parse()
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(err)))
+}
+
+
+fn rating(connection: &Connection, name: &str) -> rusqlite::Result<Rating> {
+    connection.query_row(
+        "SELECT rating, deviation, volatility FROM players WHERE name = ?1",
+        [name],
+        |row| Ok(Rating { rating: row.get(0)?, deviation: row.get(1)?, volatility: row.get(2)? }),
+    )
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TESTS
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn login_with_right_and_wrong_key() {
+        let store = Store::open(":memory:").unwrap();
+
+        assert_eq!(store.login("Ana", "secret", 0).unwrap(), "Ana");
+        assert_eq!(store.login("ana", "secret", 1).unwrap(), "Ana");
+        assert!(matches!(store.login("ANA", "guess", 2), Err(LoginError::Taken(name)) if name == "Ana"));
+        assert_eq!(store.login("Bea", "guess", 3).unwrap(), "Bea");
+    }
+
+    /// De la clave solo se guarda su hash, con una sal distinta en cada cuenta
+    #[test]
+    fn keys_are_salted_hashes() {
+        let store = Store::open(":memory:").unwrap();
+        store.login("Ana", "secret", 0).unwrap();
+        store.login("Bea", "secret", 0).unwrap();
+
+        let accounts: Vec<(String, String)> = store.connection
+            .prepare("SELECT key, salt FROM players ORDER BY name").unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+            .collect::<rusqlite::Result<_>>().unwrap();
+
+        assert_eq!(accounts.len(), 2);
+        assert_ne!(accounts[0], accounts[1]);
+        for (key, salt) in &accounts {
+            assert_eq!(*key, hash_key(salt, "secret"));
+            assert_eq!(salt.len(), SECRET_BYTES * 2);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Puntuaciones de los enfrentamientos y estimación de la diferencia de Elo
+// con su intervalo de confianza del 95%.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Cuantil de la normal para el intervalo de confianza del 95%
+const Z_95: f64 = 1.96;
+
+
+/// Victorias, tablas y derrotas de un jugador
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Score {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+
+/// Diferencia de Elo estimada y margen del intervalo de confianza
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Estimate {
+    pub elo: f64,
+    pub margin: f64,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SCORES
+
+
+impl Score {
+
+    /// Suma el resultado de una partida: 1, 0.5 o 0 puntos
+    pub fn add(&mut self, points: f64) {
+        if points > 0.75 {
+            self.wins += 1;
+        } else if points > 0.25 {
+            self.draws += 1;
+        } else {
+            self.losses += 1;
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    /// Diferencia de Elo con los rivales que explica la puntuación.
+    /// None si no hay partidas.
+    pub fn estimate(&self) -> Option<Estimate> {
+        let n = self.games() as f64;
+        if n == 0.0 {
+            return None;
+        }
+
+        let p = self.points() / n;
+
+        // Desviación típica de la puntuación media de una partida
+        let variance = (self.wins as f64 * (1.0 - p).powi(2)
+            + self.draws as f64 * (0.5 - p).powi(2)
+            + self.losses as f64 * p.powi(2)) / n;
+        let error = (variance / n).sqrt();
+
+        let low = elo((p - Z_95 * error).max(0.0));
+        let high = elo((p + Z_95 * error).min(1.0));
+
+        Some(Estimate { elo: elo(p), margin: (high - low) / 2.0 })
+    }
+}
+
+
+/// Diferencia de Elo correspondiente a una puntuación media.
+/// Ganar o perder todo da una diferencia infinita.
+fn elo(p: f64) -> f64 {
+    -400.0 * (1.0 / p - 1.0).log10()
+}
+
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.elo.is_infinite() {
+            write!(f, "{}inf", if self.elo > 0.0 { "+" } else { "-" })
+        } else if self.margin.is_finite() {
+            write!(f, "{:+.0} ± {:.0}", self.elo, self.margin)
+        } else {
+            write!(f, "{:+.0} ± inf", self.elo)
+        }
+    }
+}
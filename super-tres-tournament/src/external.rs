@@ -0,0 +1,178 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v2                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Bot que juega a través de un motor externo que habla el protocolo
+// de `super_tres_engine::protocol`, como `super-tres-uci`. La salida del
+// motor se lee en otro hilo para poder esperarla con un tiempo máximo: un
+// motor que no responde a tiempo se mata y pierde la partida.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super_tres_engine::bot::Bot;
+use super_tres_engine::game::{Game, Move};
+use super_tres_engine::protocol::{format_position, parse_bestmove};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Veces que se comprueba, cada 10 ms, si el motor salió tras `quit`
+const QUIT_POLLS: u32 = 50;
+
+/// Tiempo máximo para que el motor arranque y responda `uciok` y `readyok`
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Margen sobre `movetime` para que el motor responda `bestmove`
+const MOVE_MARGIN: Duration = Duration::from_secs(1);
+
+
+/// Motor externo en un proceso hijo
+pub struct ExternalBot {
+    name: String,
+    movetime: u64,
+    child: Child,
+    stdin: ChildStdin,
+
+    /// Líneas de la salida del motor, leídas en otro hilo
+    lines: Receiver<String>,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// EXTERNAL ENGINE
+
+
+impl ExternalBot {
+
+    /// Arranca el motor y espera a que esté listo.
+    /// La orden se separa por espacios: programa y argumentos.
+    pub fn spawn(command: &str, movetime: u64) -> io::Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty engine command"))?;
+
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+
+        // El hilo termina cuando el motor cierra la salida, también al matarlo
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = Self { name: command.to_string(), movetime, child, stdin, lines };
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+
+        bot.send("uci")?;
+        while let Some(line) = bot.read_line(deadline)? {
+            if let Some(name) = line.strip_prefix("id name ") {
+                bot.name = name.trim().to_string();
+            }
+            if line.trim() == "uciok" {
+                break;
+            }
+        }
+
+        bot.send("ucinewgame")?;
+        bot.send("isready")?;
+        while bot.read_line(deadline)?.is_some_and(|line| line.trim() != "readyok") {}
+
+        Ok(bot)
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()
+    }
+
+    /// Siguiente línea del motor. None si cerró la salida.
+    /// Si no llega antes del límite, mata el motor y devuelve un error.
+    fn read_line(&mut self, deadline: Instant) -> io::Result<Option<String>> {
+        match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(line) => Ok(Some(line)),
+            Err(RecvTimeoutError::Disconnected) => Ok(None),
+            Err(RecvTimeoutError::Timeout) => {
+                let _ = self.child.kill();
+                Err(io::Error::new(io::ErrorKind::TimedOut, format!("{} did not answer in time", self.name)))
+            },
+        }
+    }
+
+    fn best_move(&mut self, game: &Game) -> io::Result<Option<Move>> {
+        let moves: Vec<Move> = game.moves().collect();
+        self.send(&format_position(&moves))?;
+        self.send(&format!("go movetime {}", self.movetime))?;
+
+        let deadline = Instant::now() + Duration::from_millis(self.movetime) + MOVE_MARGIN;
+        while let Some(line) = self.read_line(deadline)? {
+            if let Some(best) = parse_bestmove(&line) {
+                return Ok(best);
+            }
+        }
+        Ok(None)
+    }
+}
+
+
+impl Bot for ExternalBot {
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Si el motor no responde con una jugada a tiempo, devuelve None
+    fn choose(&mut self, game: &Game) -> Option<Move> {
+        self.best_move(game).ok().flatten()
+    }
+}
+
+
+impl Drop for ExternalBot {
+
+    /// Pide al motor que salga y, si no lo hace enseguida, lo mata
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+
+        for _ in 0..QUIT_POLLS {
+            if matches!(self.child.try_wait(), Ok(Some(_))) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,221 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Torneos entre bots: todos contra todos o uno contra los demás,
+// con tabla de resultados, estimación de Elo y las partidas guardadas.
+//
+//     cargo run --release -p super-tres-tournament -- minimax:6 mcts:5000 random --games 20
+
+
+
+mod elo;
+mod external;
+mod spec;
+mod tournament;
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use super_tres_engine::eval::{set_weights, Weights};
+
+use elo::Score;
+use spec::BotSpec;
+use tournament::{Mode, Outcome};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ARGUMENTS
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Bot-vs-bot tournaments with Elo estimates")]
+struct Args {
+
+    /// Bots: random, minimax:<depth>[:<empty>], mcts:<iterations>[:<c>], engine:<ms>:<command> or wasm:<file>
+    #[arg(required = true, num_args = 2..)]
+    bots: Vec<BotSpec>,
+
+    /// Pairing mode. In a gauntlet the first bot plays all the others.
+    #[arg(long, value_enum, default_value_t = ModeArg::RoundRobin)]
+    mode: ModeArg,
+
+    /// Games per pairing, alternating who starts
+    #[arg(short, long, default_value_t = 2)]
+    games: usize,
+
+    /// Games played at the same time
+    #[arg(short = 'j', long, default_value_t = default_threads())]
+    threads: usize,
+
+    /// Random plies at the start of each pair of games
+    #[arg(long, default_value_t = 2)]
+    opening_plies: usize,
+
+    /// Seed for openings and random bots
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// File where all games are written in the game record format
+    #[arg(short, long, default_value = "tournament.tres")]
+    output: PathBuf,
+
+    /// Evaluation weights file for the minimax bots, as written by `tune`
+    #[arg(long)]
+    weights: Option<PathBuf>,
+}
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum ModeArg {
+    RoundRobin,
+    Gauntlet,
+}
+
+impl From<ModeArg> for Mode {
+    fn from(mode: ModeArg) -> Self {
+        match mode {
+            ModeArg::RoundRobin => Mode::RoundRobin,
+            ModeArg::Gauntlet => Mode::Gauntlet,
+        }
+    }
+}
+
+
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MAIN
+
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    if let Some(path) = &args.weights {
+        match Weights::load(path) {
+            Ok(weights) => set_weights(weights).expect("weights are set once at startup"),
+            Err(err) => {
+                eprintln!("could not read {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            },
+        }
+    }
+
+    let names = unique_names(&args.bots);
+    let jobs = tournament::schedule(args.bots.len(), args.mode.into(), args.games, args.seed);
+
+    let total = jobs.len();
+    let mut done = 0;
+    let outcomes = tournament::run(&jobs, &args.bots, &names, args.threads, args.opening_plies, |outcome| {
+        done += 1;
+        eprintln!(
+            "[{}/{}] round {}: {} - {} {}",
+            done, total, outcome.job.round, names[outcome.job.x], names[outcome.job.o], outcome.record.result,
+        );
+    });
+
+    let records: String = outcomes.iter().map(|o| format!("{}\n", o.record)).collect();
+    if let Err(err) = fs::write(&args.output, records) {
+        eprintln!("could not write {}: {}", args.output.display(), err);
+        return ExitCode::FAILURE;
+    }
+
+    print_results(&names, &outcomes);
+    println!("\nGames written to {}", args.output.display());
+
+    ExitCode::SUCCESS
+}
+
+
+/// Nombres de los bots para las tablas; los repetidos se numeran
+fn unique_names(bots: &[BotSpec]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+
+    for bot in bots {
+        let name = bot.to_string();
+        let repeated = names.iter().filter(|n| **n == name || n.starts_with(&format!("{}#", name))).count();
+        names.push(if repeated == 0 { name } else { format!("{}#{}", name, repeated + 1) });
+    }
+
+    names
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// RESULTS
+
+
+/// Clasificación con Elo frente al resto de participantes y tabla cruzada
+fn print_results(names: &[String], outcomes: &[Outcome]) {
+    let n = names.len();
+    let mut totals = vec![Score::default(); n];
+    let mut cross = vec![vec![Score::default(); n]; n];
+
+    for outcome in outcomes {
+        let (x, o) = (outcome.job.x, outcome.job.o);
+        let (x_points, o_points) = outcome.points();
+
+        totals[x].add(x_points);
+        totals[o].add(o_points);
+        cross[x][o].add(x_points);
+        cross[o][x].add(o_points);
+    }
+
+    let mut ranking: Vec<usize> = (0..n).filter(|i| totals[*i].games() > 0).collect();
+    ranking.sort_by(|a, b| {
+        let fraction = |i: usize| totals[i].points() / totals[i].games() as f64;
+        fraction(*b).total_cmp(&fraction(*a))
+    });
+
+    let width = names.iter().map(String::len).max().unwrap_or(0).max(4);
+
+    println!("\n{:>4}  {:<width$}  {:>5}  {:>6}  {:>5}  {:>5}  {:>5}  Elo (95%)", "Rank", "Bot", "Games", "Score", "W", "D", "L");
+    for (rank, i) in ranking.iter().enumerate() {
+        let score = totals[*i];
+        let elo = score.estimate().map(|e| e.to_string()).unwrap_or_default();
+        println!(
+            "{:>4}  {:<width$}  {:>5}  {:>6}  {:>5}  {:>5}  {:>5}  {}",
+            rank + 1, names[*i], score.games(), score.points(), score.wins, score.draws, score.losses, elo,
+        );
+    }
+
+    println!("\n{:>4}  {:<width$}", "", "Crosstable");
+    print!("{:>4}  {:<width$}", "", "");
+    for rank in 0..ranking.len() {
+        print!("  {:>9}", rank + 1);
+    }
+    println!();
+
+    for (rank, i) in ranking.iter().enumerate() {
+        print!("{:>4}  {:<width$}", rank + 1, names[*i]);
+        for j in &ranking {
+            let score = cross[*i][*j];
+            if i == j || score.games() == 0 {
+                print!("  {:>9}", "-");
+            } else {
+                print!("  {:>9}", format!("{}/{}", score.points(), score.games()));
+            }
+        }
+        println!();
+    }
+}
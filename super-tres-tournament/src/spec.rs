@@ -0,0 +1,181 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Descripción de los bots participantes en la línea de órdenes:
+//
+//     random
+//     minimax:<profundidad>[:<casillas del solucionador>]
+//     mcts:<iteraciones>[:<exploración>]
+//     engine:<milisegundos por jugada>:<orden del motor externo>
+//     wasm:<módulo WebAssembly que sigue la interfaz de `abi`>
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use super_tres_engine::bot::{Bot, MinimaxBot, RandomBot};
+use super_tres_engine::mcts::MctsBot;
+use super_tres_engine::search::SearchLimits;
+use super_tres_engine::solver::Solver;
+use super_tres_engine::tt::TranspositionTable;
+use super_tres_engine::wasm_bot::WasmBot;
+
+use crate::external::ExternalBot;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Nodos por jugada de los bots minimax: la profundidad es la que limita
+const MINIMAX_NODES: u64 = 5_000_000;
+
+/// Límite de nodos del solucionador de los bots minimax
+const SOLVER_NODES: u64 = 2_000_000;
+
+/// Tamaño de la tabla del solucionador, en MB
+const SOLVER_MEGABYTES: usize = 16;
+
+
+/// Configuración de un bot del torneo
+#[derive(Clone, PartialEq, Debug)]
+pub enum BotSpec {
+    Random,
+    Minimax { depth: u8, tablebase: Option<usize> },
+    Mcts { iterations: u32, exploration: Option<f64> },
+    External { movetime: u64, command: String },
+    Wasm { path: String },
+}
+
+
+/// Error al leer la configuración de un bot
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseSpecError(pub String);
+
+impl fmt::Display for ParseSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bot `{}`: expected random, minimax:<depth>[:<empty>], mcts:<iterations>[:<c>], engine:<ms>:<command> or wasm:<file>", self.0)
+    }
+}
+
+impl std::error::Error for ParseSpecError {}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SPECS
+
+
+impl BotSpec {
+
+    /// Crea el bot. Cada partida usa una semilla distinta para los bots aleatorios.
+    pub fn build(&self, seed: u64) -> io::Result<Box<dyn Bot>> {
+        Ok(match self {
+            BotSpec::Random => Box::new(RandomBot::new(seed)),
+            BotSpec::Minimax { depth, tablebase } => {
+                let bot = MinimaxBot::new(SearchLimits { depth: *depth, nodes: MINIMAX_NODES });
+                match tablebase {
+                    Some(empty) => Box::new(bot.with_solver(
+                        Solver::new(*empty, SOLVER_NODES, TranspositionTable::new(SOLVER_MEGABYTES))
+                    )),
+                    None => Box::new(bot),
+                }
+            },
+            BotSpec::Mcts { iterations, exploration } => {
+                let bot = MctsBot::new(*iterations, seed);
+                match exploration {
+                    Some(c) => Box::new(bot.with_exploration(*c)),
+                    None => Box::new(bot),
+                }
+            },
+            BotSpec::External { movetime, command } => Box::new(ExternalBot::spawn(command, *movetime)?),
+            BotSpec::Wasm { path } => {
+                let wasm = fs::read(path)?;
+                let bot = WasmBot::new(path.clone(), &wasm)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+                Box::new(bot)
+            },
+        })
+    }
+}
+
+
+impl fmt::Display for BotSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotSpec::Random => write!(f, "random"),
+            BotSpec::Minimax { depth, tablebase: None } => write!(f, "minimax:{}", depth),
+            BotSpec::Minimax { depth, tablebase: Some(empty) } => write!(f, "minimax:{}:{}", depth, empty),
+            BotSpec::Mcts { iterations, exploration: None } => write!(f, "mcts:{}", iterations),
+            BotSpec::Mcts { iterations, exploration: Some(c) } => write!(f, "mcts:{}:{}", iterations, c),
+            BotSpec::External { movetime, command } => write!(f, "engine:{}:{}", movetime, command),
+            BotSpec::Wasm { path } => write!(f, "wasm:{}", path),
+        }
+    }
+}
+
+
+impl FromStr for BotSpec {
+    type Err = ParseSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseSpecError(s.to_string());
+        let (kind, rest) = s.split_once(':').unwrap_or((s, ""));
+
+        // La orden del motor externo y la ruta del módulo pueden contener `:`
+        if kind == "engine" {
+            let (movetime, command) = rest.split_once(':').ok_or_else(err)?;
+            if command.trim().is_empty() {
+                return Err(err());
+            }
+            return Ok(BotSpec::External {
+                movetime: movetime.parse().map_err(|_| err())?,
+                command: command.to_string(),
+            });
+        }
+
+        if kind == "wasm" {
+            if rest.is_empty() {
+                return Err(err());
+            }
+            return Ok(BotSpec::Wasm { path: rest.to_string() });
+        }
+
+        let args: Vec<&str> = if rest.is_empty() { Vec::new() } else { rest.split(':').collect() };
+
+        match (kind, args.as_slice()) {
+            ("random", []) => Ok(BotSpec::Random),
+            ("minimax", [depth]) => Ok(BotSpec::Minimax {
+                depth: depth.parse().map_err(|_| err())?,
+                tablebase: None,
+            }),
+            ("minimax", [depth, empty]) => Ok(BotSpec::Minimax {
+                depth: depth.parse().map_err(|_| err())?,
+                tablebase: Some(empty.parse().map_err(|_| err())?),
+            }),
+            ("mcts", [iterations]) => Ok(BotSpec::Mcts {
+                iterations: iterations.parse().map_err(|_| err())?,
+                exploration: None,
+            }),
+            ("mcts", [iterations, c]) => Ok(BotSpec::Mcts {
+                iterations: iterations.parse().map_err(|_| err())?,
+                exploration: Some(c.parse().map_err(|_| err())?),
+            }),
+            _ => Err(err()),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Calendario del torneo y partidas en paralelo.
+// Cada emparejamiento juega las partidas de dos en dos con la misma apertura
+// aleatoria, alternando quién empieza.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use super_tres_engine::board::Player;
+use super_tres_engine::game::{Game, GameResult};
+use super_tres_engine::record::{GameRecord, RecordResult};
+use super_tres_engine::rng::Rng;
+
+use crate::spec::BotSpec;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Forma de emparejar a los bots
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+
+    /// Todos contra todos
+    RoundRobin,
+
+    /// El primer bot contra cada uno de los demás
+    Gauntlet,
+}
+
+
+/// Partida del calendario
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Job {
+
+    /// Número de partida, desde 1
+    pub round: usize,
+
+    /// Índices de los bots que juegan con X y con O
+    pub x: usize,
+    pub o: usize,
+
+    /// Semilla de la apertura, compartida por las dos partidas de cada par
+    pub opening_seed: u64,
+
+    /// Semilla de los bots aleatorios
+    pub seed: u64,
+}
+
+
+/// Partida jugada
+#[derive(Clone, Debug)]
+pub struct Outcome {
+    pub job: Job,
+    pub record: GameRecord,
+}
+
+impl Outcome {
+
+    /// Puntos de cada bot: (X, O)
+    pub fn points(&self) -> (f64, f64) {
+        let x = self.record.result.points(Player::First).unwrap_or(0.5);
+        (x, 1.0 - x)
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SCHEDULE
+
+
+/// Calendario completo: `games` partidas por emparejamiento
+pub fn schedule(bots: usize, mode: Mode, games: usize, seed: u64) -> Vec<Job> {
+    let pairings: Vec<(usize, usize)> = match mode {
+        Mode::RoundRobin => (0..bots).flat_map(|a| (a + 1..bots).map(move |b| (a, b))).collect(),
+        Mode::Gauntlet => (1..bots).map(|b| (0, b)).collect(),
+    };
+
+    let mut rng = Rng::new(seed);
+    let mut jobs = Vec::new();
+
+    for (a, b) in pairings {
+        let mut opening_seed = 0;
+
+        for game in 0..games {
+            if game % 2 == 0 {
+                opening_seed = rng.next_u64();
+            }
+            let (x, o) = if game % 2 == 0 { (a, b) } else { (b, a) };
+
+            jobs.push(Job { round: jobs.len() + 1, x, o, opening_seed, seed: rng.next_u64() });
+        }
+    }
+
+    jobs
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// GAMES
+
+
+/// Juega una partida. Si un bot no arranca, no responde o hace una jugada ilegal, pierde.
+pub fn play(job: Job, specs: &[BotSpec], names: &[String], opening_plies: usize) -> GameRecord {
+    let mut game = Game::new();
+    let mut rng = Rng::new(job.opening_seed);
+
+    for _ in 0..opening_plies {
+        match rng.choose(&game.legal_moves()) {
+            Some(mv) => game.apply(*mv),
+            None => break,
+        }
+    }
+
+    let mut forfeit = None;
+    let bots = specs[job.x].build(job.seed).map_err(|_| Player::First)
+        .and_then(|x| Ok((x, specs[job.o].build(job.seed ^ 1).map_err(|_| Player::Second)?)));
+
+    match bots {
+        Ok((mut x, mut o)) => {
+            while let Some(turn) = game.turn() {
+                let bot = match turn {
+                    Player::First => &mut x,
+                    Player::Second => &mut o,
+                };
+                match bot.choose(&game) {
+                    Some(mv) if game.is_legal(mv) => game.apply(mv),
+                    Some(_) => {
+                        forfeit = Some((turn, "illegal move"));
+                        break;
+                    },
+                    None => {
+                        forfeit = Some((turn, "no move"));
+                        break;
+                    },
+                }
+            }
+        },
+        Err(loser) => forfeit = Some((loser, "engine failed to start")),
+    }
+
+    let mut record = GameRecord::from_game(&game);
+    record.set_tag("Event", "Tournament");
+    record.set_tag("Round", job.round.to_string());
+    record.set_tag("X", names[job.x].clone());
+    record.set_tag("O", names[job.o].clone());
+    record.set_tag("Opening", opening_plies.to_string());
+
+    if let Some((loser, reason)) = forfeit {
+        record.result = RecordResult::Finished(GameResult::Win(loser.other()));
+        record.set_tag("Termination", reason);
+    }
+
+    record
+}
+
+
+/// Juega el calendario en `threads` hilos.
+/// `on_outcome` se llama en el hilo actual según van terminando las partidas.
+pub fn run(
+    jobs: &[Job],
+    specs: &[BotSpec],
+    names: &[String],
+    threads: usize,
+    opening_plies: usize,
+    mut on_outcome: impl FnMut(&Outcome),
+) -> Vec<Outcome> {
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut outcomes = Vec::with_capacity(jobs.len());
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, jobs.len().max(1)) {
+            let sender = sender.clone();
+            let next = &next;
+
+            scope.spawn(move || {
+                while let Some(job) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let record = play(*job, specs, names, opening_plies);
+                    if sender.send(Outcome { job: *job, record }).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for outcome in receiver {
+            on_outcome(&outcome);
+            outcomes.push(outcome);
+        }
+    });
+
+    outcomes.sort_by_key(|o| o.job.round);
+    outcomes
+}
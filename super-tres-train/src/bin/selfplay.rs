@@ -0,0 +1,223 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Genera posiciones para ajustar la evaluación jugando partidas del motor
+// contra sí mismo desde aperturas aleatorias. Con la misma semilla y los
+// mismos parámetros el resultado es idéntico, juegue en los hilos que juegue.
+//
+//     cargo run --release -p super-tres-train --bin selfplay -- --games 1000 -o dataset.csv
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use clap::{Parser, ValueEnum};
+
+use super_tres_engine::dataset::{self, Sample};
+use super_tres_engine::eval::{set_weights, Weights};
+use super_tres_engine::game::{Game, GameResult};
+use super_tres_engine::rng::Rng;
+use super_tres_engine::search::{search_with, SearchLimits};
+use super_tres_engine::tt::TranspositionTable;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ARGUMENTS
+
+
+/// Tabla de transposición de cada hilo, en MB
+const TABLE_MEGABYTES: usize = 16;
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Self-play dataset generation for evaluation tuning")]
+struct Args {
+
+    /// Number of games
+    #[arg(short, long, default_value_t = 100)]
+    games: usize,
+
+    /// Search depth of both sides
+    #[arg(short, long, default_value_t = 4)]
+    depth: u8,
+
+    /// Node limit per move
+    #[arg(long, default_value_t = 50_000)]
+    nodes: u64,
+
+    /// Minimum number of random opening plies
+    #[arg(long, default_value_t = 2)]
+    opening_min: usize,
+
+    /// Maximum number of random opening plies
+    #[arg(long, default_value_t = 8)]
+    opening_max: usize,
+
+    /// Seed for the openings
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Games played at the same time
+    #[arg(short = 'j', long, default_value_t = default_threads())]
+    threads: usize,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// Output file
+    #[arg(short, long, default_value = "dataset.csv")]
+    output: PathBuf,
+
+    /// Evaluation weights file, as written by `tune`
+    #[arg(long)]
+    weights: Option<PathBuf>,
+}
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum Format {
+    Csv,
+    Binary,
+}
+
+
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// SELF-PLAY
+
+
+/// Juega una partida y devuelve sus posiciones tras la apertura
+fn play(seed: u64, args: &Args, tt: &mut TranspositionTable) -> Vec<Sample> {
+    let mut rng = Rng::new(seed);
+    let mut game = Game::new();
+
+    let spread = args.opening_max.saturating_sub(args.opening_min);
+    let opening = args.opening_min + rng.below(spread + 1);
+    for _ in 0..opening {
+        match rng.choose(&game.legal_moves()) {
+            Some(mv) => game.apply(*mv),
+            None => break,
+        }
+    }
+
+    // El resultado se rellena cuando termina la partida
+    let limits = SearchLimits { depth: args.depth, nodes: args.nodes };
+    let mut samples = Vec::new();
+
+    while game.turn().is_some() {
+        let result = search_with(&game, limits, tt);
+        samples.extend(Sample::new(&game, GameResult::Draw, result.score));
+
+        match result.best_move() {
+            Some(mv) => game.apply(mv),
+            None => break,
+        }
+    }
+
+    let result = game.result().unwrap_or(GameResult::Draw);
+    for sample in &mut samples {
+        sample.result = result;
+    }
+
+    samples
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MAIN
+
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    if let Some(path) = &args.weights {
+        match Weights::load(path) {
+            Ok(weights) => set_weights(weights).expect("weights are set once at startup"),
+            Err(err) => {
+                eprintln!("could not read {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            },
+        }
+    }
+
+    // Las semillas de cada partida no dependen del orden en que se juegan
+    let mut rng = Rng::new(args.seed);
+    let seeds: Vec<u64> = (0..args.games).map(|_| rng.next_u64()).collect();
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut games: Vec<Vec<Sample>> = vec![Vec::new(); args.games];
+
+    thread::scope(|scope| {
+        for _ in 0..args.threads.clamp(1, args.games.max(1)) {
+            let sender = sender.clone();
+            let (next, seeds, args) = (&next, &seeds, &args);
+
+            scope.spawn(move || {
+                let mut tt = TranspositionTable::new(TABLE_MEGABYTES);
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(seed) = seeds.get(index) else {
+                        break;
+                    };
+
+                    // La tabla se vacía para que la partida no dependa de las anteriores del hilo
+                    tt.clear();
+                    if sender.send((index, play(*seed, args, &mut tt))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (done, (index, samples)) in receiver.into_iter().enumerate() {
+            eprintln!("[{}/{}] game {}: {} positions", done + 1, args.games, index + 1, samples.len());
+            games[index] = samples;
+        }
+    });
+
+    let samples: Vec<Sample> = games.into_iter().flatten().collect();
+
+    let written = File::create(&args.output).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        match args.format {
+            Format::Csv => dataset::write_csv(&mut writer, &samples)?,
+            Format::Binary => dataset::write_binary(&mut writer, &samples)?,
+        }
+        writer.flush()
+    });
+
+    if let Err(err) = written {
+        eprintln!("could not write {}: {}", args.output.display(), err);
+        return ExitCode::FAILURE;
+    }
+
+    println!("{} positions from {} games written to {}", samples.len(), args.games, args.output.display());
+    ExitCode::SUCCESS
+}
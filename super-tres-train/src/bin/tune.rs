@@ -0,0 +1,221 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Ajuste de los pesos de la evaluación al estilo Texel: se busca que la
+// evaluación, pasada por una sigmoide, prediga el resultado de las partidas
+// de las que salen las posiciones. Como la evaluación es lineal en los pesos,
+// los términos de cada posición se calculan una sola vez.
+//
+//     cargo run --release -p super-tres-train --bin tune -- dataset.csv -o weights.txt
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use super_tres_engine::dataset;
+use super_tres_engine::eval::{features, Weights, FEATURES};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ARGUMENTS
+
+
+/// Pasos de la búsqueda por coordenadas, de mayor a menor
+const STEPS: [i32; 6] = [32, 16, 8, 4, 2, 1];
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Texel-style tuning of the evaluation weights")]
+struct Args {
+
+    /// Datasets written by `selfplay`, in CSV or binary
+    #[arg(required = true)]
+    datasets: Vec<PathBuf>,
+
+    /// Weights to start from instead of the defaults
+    #[arg(long)]
+    init: Option<PathBuf>,
+
+    /// Share of the target given by the game result; the rest comes from the search value
+    #[arg(long, default_value_t = 1.0)]
+    lambda: f64,
+
+    /// Maximum passes over the weights for each step size
+    #[arg(long, default_value_t = 50)]
+    passes: usize,
+
+    /// Output weights file
+    #[arg(short, long, default_value = "weights.txt")]
+    output: PathBuf,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// TUNING
+
+
+/// Posición preparada para el ajuste
+struct Point {
+    features: [f64; FEATURES],
+    outcome: f64,
+    score: f64,
+}
+
+
+/// Probabilidad de que gane X según una puntuación
+fn sigmoid(score: f64, scale: f64) -> f64 {
+    1.0 / (1.0 + (-score / scale).exp())
+}
+
+
+/// Error cuadrático medio de la predicción con unos pesos
+fn error(points: &[Point], weights: &[i32; FEATURES], scale: f64, lambda: f64) -> f64 {
+    let total: f64 = points.iter().map(|p| {
+        let eval: f64 = p.features.iter().zip(weights).map(|(f, w)| f * *w as f64).sum();
+        let target = lambda * p.outcome + (1.0 - lambda) * sigmoid(p.score, scale);
+        (target - sigmoid(eval, scale)).powi(2)
+    }).sum();
+
+    total / points.len() as f64
+}
+
+
+/// Escala de la sigmoide que mejor ajusta los pesos iniciales, por sección áurea
+fn fit_scale(points: &[Point], weights: &[i32; FEATURES], lambda: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = (10.0, 5_000.0);
+
+    while high - low > 1.0 {
+        let a = high - ratio * (high - low);
+        let b = low + ratio * (high - low);
+        if error(points, weights, a, lambda) < error(points, weights, b, lambda) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+
+/// Búsqueda por coordenadas: se prueba a subir y bajar cada peso mientras mejore el error
+fn tune(points: &[Point], mut weights: [i32; FEATURES], scale: f64, args: &Args) -> [i32; FEATURES] {
+    let mut best = error(points, &weights, scale, args.lambda);
+
+    for step in STEPS {
+        for pass in 0..args.passes {
+            let mut improved = false;
+
+            for i in 0..FEATURES {
+                for delta in [step, -step] {
+                    let mut candidate = weights;
+                    candidate[i] += delta;
+
+                    let e = error(points, &candidate, scale, args.lambda);
+                    if e < best {
+                        best = e;
+                        weights = candidate;
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+
+            eprintln!("step {:>2} pass {:>2}: error {:.6}", step, pass + 1, best);
+            if !improved {
+                break;
+            }
+        }
+    }
+
+    weights
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MAIN
+
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let initial = match &args.init {
+        Some(path) => match Weights::load(path) {
+            Ok(weights) => weights,
+            Err(err) => {
+                eprintln!("could not read {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            },
+        },
+        None => Weights::default(),
+    };
+
+    let mut points = Vec::new();
+    for path in &args.datasets {
+        let samples = match dataset::read_file(path) {
+            Ok(samples) => samples,
+            Err(err) => {
+                eprintln!("could not read {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            },
+        };
+
+        points.extend(samples.iter().filter_map(|sample| {
+            let game = sample.game()?;
+            Some(Point {
+                features: features(&game).map(f64::from),
+                outcome: sample.outcome(),
+                score: sample.score as f64,
+            })
+        }));
+    }
+
+    if points.is_empty() {
+        eprintln!("no positions to tune on");
+        return ExitCode::FAILURE;
+    }
+
+    let start = initial.to_array();
+    let scale = fit_scale(&points, &start, args.lambda);
+    let before = error(&points, &start, scale, args.lambda);
+    eprintln!("{} positions, sigmoid scale {:.1}, error {:.6}", points.len(), scale, before);
+
+    let tuned = tune(&points, start, scale, &args);
+    let after = error(&points, &tuned, scale, args.lambda);
+    let weights = Weights::from_array(tuned);
+
+    let contents = format!(
+        "# Tuned on {} positions with lambda {}: error {:.6} -> {:.6}\n{}",
+        points.len(), args.lambda, before, after, weights,
+    );
+    if let Err(err) = fs::write(&args.output, contents) {
+        eprintln!("could not write {}: {}", args.output.display(), err);
+        return ExitCode::FAILURE;
+    }
+
+    for (name, (old, new)) in Weights::NAMES.iter().zip(start.iter().zip(tuned)) {
+        println!("{:<14} {:>5} -> {:>5}", name, old, new);
+    }
+    println!("error {:.6} -> {:.6}, written to {}", before, after, args.output.display());
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,237 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Estado de la partida en la terminal: jugadores, cursor y jugadas de los bots.
+// Los bots piensan en otro hilo para que la interfaz siga respondiendo.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::mem;
+use std::thread::{self, JoinHandle};
+
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind};
+use ratatui::layout::Rect;
+use super_tres_engine::board::{Player, Position};
+use super_tres_engine::bot::Bot;
+use super_tres_engine::game::{Game, Move};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Bot de un lado y la jugada que eligió
+type Thought = (Box<dyn Bot + Send>, Option<Move>);
+
+
+/// Quién controla un lado
+pub enum Controller {
+    Human,
+    Bot(Box<dyn Bot + Send>),
+
+    /// Bot pensando en otro hilo sobre la partida de ese número
+    Thinking(JoinHandle<Thought>, u64),
+}
+
+
+/// Jugador de un lado
+pub struct Side {
+    pub name: String,
+    pub controller: Controller,
+}
+
+
+/// Estado de la aplicación
+pub struct App {
+
+    /// Partida en curso
+    pub game: Game,
+
+    /// Casilla seleccionada, en coordenadas del tablero de 9x9
+    pub cursor: Position,
+
+    /// Jugadores de X y de O
+    pub sides: [Side; 2],
+
+    /// Número de la partida, para descartar las jugadas de bots de partidas anteriores
+    pub number: u64,
+
+    /// Aviso que se muestra junto al tablero
+    pub message: Option<String>,
+
+    /// Zona de la pantalla donde se dibujó el tablero, para los clics
+    pub board_area: Rect,
+
+    pub quit: bool,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// APP
+
+
+impl App {
+
+    pub fn new(sides: [Side; 2]) -> Self {
+        Self {
+            game: Game::new(),
+            cursor: Position { x: 4, y: 4 },
+            sides,
+            number: 0,
+            message: None,
+            board_area: Rect::default(),
+            quit: false,
+        }
+    }
+
+
+    /// Indica si le toca a una persona
+    pub fn human_to_move(&self) -> bool {
+        self.game.turn().is_some_and(|turn| matches!(self.sides[turn.index()].controller, Controller::Human))
+    }
+
+
+    /// Pone a pensar al bot al que le toca y recoge las jugadas de los que terminaron
+    pub fn tick(&mut self) {
+        for player in [Player::First, Player::Second] {
+            self.collect(player);
+        }
+
+        let Some(turn) = self.game.turn() else {
+            return;
+        };
+
+        let side = &mut self.sides[turn.index()];
+        if let Controller::Bot(_) = side.controller {
+            let Controller::Bot(mut bot) = mem::replace(&mut side.controller, Controller::Human) else {
+                unreachable!("the side is a bot");
+            };
+            let game = self.game.clone();
+            side.controller = Controller::Thinking(thread::spawn(move || {
+                let mv = bot.choose(&game);
+                (bot, mv)
+            }), self.number);
+        }
+    }
+
+
+    /// Recoge la jugada del bot de ese lado si ya terminó de pensar
+    fn collect(&mut self, player: Player) {
+        let side = &mut self.sides[player.index()];
+
+        let finished = matches!(&side.controller, Controller::Thinking(handle, _) if handle.is_finished());
+        if !finished {
+            return;
+        }
+
+        let Controller::Thinking(handle, number) = mem::replace(&mut side.controller, Controller::Human) else {
+            unreachable!("the side is thinking");
+        };
+
+        let Ok((bot, mv)) = handle.join() else {
+            self.message = Some(format!("{} crashed; {} is now played by a human", side.name, player.symbol()));
+            side.name = String::from("human");
+            return;
+        };
+        side.controller = Controller::Bot(bot);
+
+        // La partida cambió mientras pensaba
+        if number != self.number || self.game.turn() != Some(player) {
+            return;
+        }
+
+        match mv.filter(|mv| self.game.is_legal(*mv)) {
+            Some(mv) => self.play(mv),
+            None => {
+                // El lado del bot que falla pasa a jugarlo una persona
+                self.message = Some(format!("{} could not move; {} is now played by a human", side.name, player.symbol()));
+                side.name = String::from("human");
+                side.controller = Controller::Human;
+            },
+        }
+    }
+
+
+    fn play(&mut self, mv: Move) {
+        self.game.apply(mv);
+
+        // El cursor pasa a la misma casilla del subtablero en el que hay que jugar
+        if let Some(active) = self.game.active_table() {
+            if !self.game.board_enabled(self.board_under_cursor()) {
+                self.cursor = Position { x: active.x * 3 + mv.cell.x, y: active.y * 3 + mv.cell.y };
+            }
+        }
+    }
+
+
+    fn board_under_cursor(&self) -> Position {
+        Position { x: self.cursor.x / 3, y: self.cursor.y / 3 }
+    }
+
+
+    /// Juega en la casilla del cursor si le toca a una persona y es legal
+    fn play_cursor(&mut self) {
+        let mv = Move::from_total(self.cursor);
+
+        if self.human_to_move() && self.game.is_legal(mv) {
+            self.message = None;
+            self.play(mv);
+        }
+    }
+
+
+    fn new_game(&mut self) {
+        self.game = Game::new();
+        self.number += 1;
+        self.message = None;
+    }
+
+
+    pub fn handle(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.cursor.x = self.cursor.x.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => self.cursor.x = (self.cursor.x + 1).min(8),
+                KeyCode::Left | KeyCode::Char('h') => self.cursor.y = self.cursor.y.saturating_sub(1),
+                KeyCode::Right | KeyCode::Char('l') => self.cursor.y = (self.cursor.y + 1).min(8),
+                KeyCode::Enter | KeyCode::Char(' ') => self.play_cursor(),
+                KeyCode::Char('n') => self.new_game(),
+                KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+                _ => {},
+            },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(cell) = cell_at(self.board_area, mouse.column, mouse.row) {
+                    self.cursor = cell;
+                    self.play_cursor();
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+
+/// Casilla del tablero dibujado en esa zona que está en esas coordenadas de la pantalla.
+/// Cada casilla ocupa 3 columnas y 1 fila, separadas por los bordes.
+fn cell_at(area: Rect, column: u16, row: u16) -> Option<Position> {
+    let x = column.checked_sub(area.x)?;
+    let y = row.checked_sub(area.y)?;
+
+    if x.is_multiple_of(4) || y.is_multiple_of(2) || x / 4 >= 9 || y / 2 >= 9 {
+        return None;
+    }
+
+    Some(Position { x: (y / 2) as u8, y: (x / 4) as u8 })
+}
@@ -0,0 +1,116 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Versión de terminal del juego sobre el motor compartido.
+// Se juega con las flechas o con el ratón, contra otra persona o contra un bot.
+//
+//     cargo run --release -p super-tres-tui -- --o minimax:6
+
+
+
+mod app;
+mod player;
+mod ui;
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::io::{self, stdout};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::Parser;
+use ratatui::crossterm::event::{self, DisableMouseCapture, EnableMouseCapture};
+use ratatui::crossterm::execute;
+use ratatui::DefaultTerminal;
+
+use app::{App, Controller, Side};
+use player::PlayerSpec;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ARGUMENTS
+
+
+/// Tiempo máximo de espera de eventos, para ir recogiendo las jugadas de los bots
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Play super tic-tac-toe in the terminal")]
+struct Args {
+
+    /// Player X: human, random, minimax:<depth>, mcts:<iterations> or wasm:<file>
+    #[arg(short, long, default_value = "human")]
+    x: PlayerSpec,
+
+    /// Player O: human, random, minimax:<depth>, mcts:<iterations> or wasm:<file>
+    #[arg(short, long, default_value = "minimax:6")]
+    o: PlayerSpec,
+
+    /// Seed for random and MCTS bots
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MAIN
+
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let mut sides = Vec::with_capacity(2);
+    for (i, spec) in [&args.x, &args.o].into_iter().enumerate() {
+        match spec.build(args.seed.wrapping_add(i as u64)) {
+            Ok(bot) => sides.push(Side {
+                name: spec.to_string(),
+                controller: bot.map_or(Controller::Human, Controller::Bot),
+            }),
+            Err(err) => {
+                eprintln!("could not create {}: {}", spec, err);
+                return ExitCode::FAILURE;
+            },
+        }
+    }
+    let sides: [Side; 2] = sides.try_into().unwrap_or_else(|_| unreachable!("two sides"));
+
+    let mut terminal = ratatui::init();
+    let result = execute!(stdout(), EnableMouseCapture).and_then(|_| run(&mut terminal, App::new(sides)));
+    let _ = execute!(stdout(), DisableMouseCapture);
+    ratatui::restore();
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("terminal error: {}", err);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+
+fn run(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
+    while !app.quit {
+        app.tick();
+        terminal.draw(|frame| ui::draw(frame, &mut app))?;
+
+        if event::poll(POLL_INTERVAL)? {
+            app.handle(event::read()?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v1                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Quién juega cada lado en la línea de órdenes:
+//
+//     human
+//     random
+//     minimax:<profundidad>
+//     mcts:<iteraciones>
+//     wasm:<módulo WebAssembly que sigue la interfaz de `abi`>
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use super_tres_engine::bot::{Bot, MinimaxBot, RandomBot};
+use super_tres_engine::mcts::MctsBot;
+use super_tres_engine::search::SearchLimits;
+use super_tres_engine::wasm_bot::WasmBot;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Nodos por jugada de los bots minimax: la profundidad es la que limita
+const MINIMAX_NODES: u64 = 5_000_000;
+
+
+/// Jugador de uno de los lados
+#[derive(Clone, PartialEq, Debug)]
+pub enum PlayerSpec {
+    Human,
+    Random,
+    Minimax { depth: u8 },
+    Mcts { iterations: u32 },
+    Wasm { path: String },
+}
+
+
+/// Error al leer un jugador
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParsePlayerError(pub String);
+
+impl fmt::Display for ParsePlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid player `{}`: expected human, random, minimax:<depth>, mcts:<iterations> or wasm:<file>", self.0)
+    }
+}
+
+impl std::error::Error for ParsePlayerError {}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// PLAYERS
+
+
+impl PlayerSpec {
+
+    /// Crea el bot del jugador. None si juega una persona.
+    pub fn build(&self, seed: u64) -> io::Result<Option<Box<dyn Bot + Send>>> {
+        Ok(Some(match self {
+            PlayerSpec::Human => return Ok(None),
+            PlayerSpec::Random => Box::new(RandomBot::new(seed)),
+            PlayerSpec::Minimax { depth } => Box::new(MinimaxBot::new(SearchLimits { depth: *depth, nodes: MINIMAX_NODES })),
+            PlayerSpec::Mcts { iterations } => Box::new(MctsBot::new(*iterations, seed)),
+            PlayerSpec::Wasm { path } => {
+                let wasm = fs::read(path)?;
+                let bot = WasmBot::new(path.clone(), &wasm)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+                Box::new(bot)
+            },
+        }))
+    }
+}
+
+
+impl fmt::Display for PlayerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerSpec::Human => write!(f, "human"),
+            PlayerSpec::Random => write!(f, "random"),
+            PlayerSpec::Minimax { depth } => write!(f, "minimax:{}", depth),
+            PlayerSpec::Mcts { iterations } => write!(f, "mcts:{}", iterations),
+            PlayerSpec::Wasm { path } => write!(f, "wasm:{}", path),
+        }
+    }
+}
+
+
+impl FromStr for PlayerSpec {
+    type Err = ParsePlayerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParsePlayerError(s.to_string());
+
+        match s.split_once(':').unwrap_or((s, "")) {
+            ("human", "") => Ok(PlayerSpec::Human),
+            ("random", "") => Ok(PlayerSpec::Random),
+            ("minimax", depth) => Ok(PlayerSpec::Minimax { depth: depth.parse().map_err(|_| err())? }),
+            ("mcts", iterations) => Ok(PlayerSpec::Mcts { iterations: iterations.parse().map_err(|_| err())? }),
+            // La ruta del módulo puede contener `:`
+            ("wasm", path) if !path.is_empty() => Ok(PlayerSpec::Wasm { path: path.to_string() }),
+            _ => Err(err()),
+        }
+    }
+}
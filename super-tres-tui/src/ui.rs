@@ -0,0 +1,201 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v3                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Dibujo del tablero de 9x9 con caracteres de caja y del panel de la partida.
+// Los bordes gruesos separan los subtableros y los subtableros en los que se
+// puede jugar se resaltan como con la clase `active-board` de la aplicación web.
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use ratatui::layout::{Constraint, Flex, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use super_tres_engine::bitboard;
+use super_tres_engine::board::{Player, Position};
+use super_tres_engine::game::{GameResult, Move};
+
+use crate::app::{App, Controller};
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+/// Tamaño del tablero dibujado: 9 casillas de 3 columnas y 1 fila más los bordes
+const BOARD_WIDTH: u16 = 9 * 4 + 1;
+const BOARD_HEIGHT: u16 = 9 * 2 + 1;
+
+/// Ancho del panel de la partida
+const PANEL_WIDTH: u16 = 40;
+
+const FIRST_PLAYER_COLOR: Color = Color::Rgb(124, 92, 184);
+const SECOND_PLAYER_COLOR: Color = Color::Rgb(200, 88, 86);
+const ACTIVE_BOARD_COLOR: Color = Color::Rgb(60, 60, 60);
+const BORDER_COLOR: Color = Color::Gray;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DRAWING
+
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [area] = Layout::vertical([Constraint::Length(BOARD_HEIGHT)]).flex(Flex::Center).areas(frame.area());
+    let [board, panel] = Layout::horizontal([Constraint::Length(BOARD_WIDTH), Constraint::Length(PANEL_WIDTH)])
+        .flex(Flex::Center)
+        .spacing(3)
+        .areas(area);
+
+    app.board_area = board;
+    frame.render_widget(Paragraph::new(board_lines(app)), board);
+    frame.render_widget(Paragraph::new(panel_lines(app)), panel);
+}
+
+
+fn player_color(player: Player) -> Color {
+    match player {
+        Player::First => FIRST_PLAYER_COLOR,
+        Player::Second => SECOND_PLAYER_COLOR,
+    }
+}
+
+
+/// Borde horizontal encima de la fila `row` (9 es el borde inferior)
+fn border_line(row: usize) -> Line<'static> {
+    let (left, right, heavy, light) = match row {
+        0 => ('┏', '┓', '┳', '┯'),
+        9 => ('┗', '┛', '┻', '┷'),
+        r if r.is_multiple_of(3) => ('┣', '┫', '╋', '┿'),
+        _ => ('┠', '┨', '╂', '┼'),
+    };
+    let fill = if row.is_multiple_of(3) { "━━━" } else { "───" };
+
+    let mut line = String::from(left);
+    for col in 0..9 {
+        line.push_str(fill);
+        line.push(match col {
+            8 => right,
+            c if c % 3 == 2 => heavy,
+            _ => light,
+        });
+    }
+
+    Line::from(Span::styled(line, Style::new().fg(BORDER_COLOR)))
+}
+
+
+/// Fila de casillas del tablero de 9x9
+fn cell_line(app: &App, row: u8) -> Line<'static> {
+    let game = &app.game;
+    let board = game.bitboard();
+    let border = Style::new().fg(BORDER_COLOR);
+
+    let mut spans = vec![Span::styled("┃", border)];
+
+    for col in 0..9 {
+        let mv = Move::from_total(Position { x: row, y: col });
+        let sub = bitboard::index(mv.board);
+
+        let mut style = Style::new();
+        if let Some(owner) = board.owner(sub) {
+            // Los subtableros ganados se tiñen del color del ganador
+            style = style.bg(player_color(owner)).fg(Color::White);
+        } else if game.turn().is_some() && game.board_enabled(mv.board) {
+            style = style.bg(ACTIVE_BOARD_COLOR);
+        }
+
+        let value = board.get(sub, bitboard::index(mv.cell));
+        if let Some(player) = value {
+            style = style.add_modifier(Modifier::BOLD);
+            if board.owner(sub).is_none() {
+                style = style.fg(player_color(player));
+            }
+        }
+        if game.last_move() == Some(mv) {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        if app.cursor == mv.total() && app.human_to_move() {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+
+        let symbol = value.map_or(" ", Player::symbol);
+        spans.push(Span::styled(format!(" {} ", symbol), style));
+        spans.push(Span::styled(if col % 3 == 2 { "┃" } else { "│" }, border));
+    }
+
+    Line::from(spans)
+}
+
+
+fn board_lines(app: &App) -> Vec<Line<'static>> {
+    let mut lines = Vec::with_capacity(BOARD_HEIGHT as usize);
+
+    for row in 0..9 {
+        lines.push(border_line(row as usize));
+        lines.push(cell_line(app, row));
+    }
+    lines.push(border_line(9));
+
+    lines
+}
+
+
+fn panel_lines(app: &App) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    for player in [Player::First, Player::Second] {
+        let side = &app.sides[player.index()];
+        let marker = if app.game.turn() == Some(player) { "▶ " } else { "  " };
+        let status = match side.controller {
+            Controller::Thinking(..) if app.game.turn() == Some(player) => " (thinking…)",
+            _ => "",
+        };
+
+        lines.push(Line::from(vec![
+            Span::raw(marker),
+            Span::styled(player.symbol(), Style::new().fg(player_color(player)).bold()),
+            Span::raw(format!("  {}{}", side.name, status)),
+        ]));
+    }
+    lines.push(Line::default());
+
+    match app.game.result() {
+        Some(GameResult::Win(winner)) => lines.push(Line::from(format!("Player {} wins!", winner.symbol())).bold()),
+        Some(GameResult::Draw) => lines.push(Line::from("Draw!").bold()),
+        Some(GameResult::Resigned(player)) => {
+            lines.push(Line::from(format!("Player {} resigned, {} wins!", player.symbol(), player.other().symbol())).bold())
+        },
+        Some(GameResult::AgreedDraw) => lines.push(Line::from("Draw agreed!").bold()),
+        Some(GameResult::OutOfTime(player)) => {
+            lines.push(Line::from(format!("Player {} ran out of time, {} wins!", player.symbol(), player.other().symbol())).bold())
+        },
+        None => lines.push(Line::from(format!("Move {}", app.game.ply() + 1))),
+    }
+    if let Some(mv) = app.game.last_move() {
+        lines.push(Line::from(format!("Last move: {}", mv)));
+    }
+    if let Some(message) = &app.message {
+        lines.push(Line::default());
+        lines.push(Line::from(message.clone()).fg(SECOND_PLAYER_COLOR));
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::from("←↑↓→ / hjkl  move cursor").dim());
+    lines.push(Line::from("Enter / click  play").dim());
+    lines.push(Line::from("n  new game    q  quit").dim());
+
+    lines
+}
@@ -0,0 +1,312 @@
+//-------------------------------------------------------------------//
+//  AUTHOR:    @sfmolina                                            //
+//  Version:   v3                                                  //
+//  Modified:  19oc26                                             //
+//---------------------------------------------------------------//
+// DESCRIPCIÓN:
+// Motor sin interfaz que habla el protocolo de `super_tres_engine::protocol`
+// por la entrada y la salida estándar, para usarlo desde interfaces externas
+// y gestores de torneos.
+//
+//     cargo run --release -p super-tres-uci [-- <fichero de pesos>]
+
+
+
+//-------------------------------------------------------------------
+// IMPORTS
+
+
+
+use std::io::{self, BufRead};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super_tres_engine::board::Player;
+use super_tres_engine::eval::{set_weights, Weights};
+use super_tres_engine::game::Game;
+use super_tres_engine::protocol::{format_bestmove, format_info, Command, GoParams};
+use super_tres_engine::search::{search_observed, SearchLimits, SearchObserver, SearchResult};
+use super_tres_engine::solver::Solver;
+use super_tres_engine::tt::TranspositionTable;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// CONSTANTS
+
+
+const NAME: &str = concat!("SuperTres ", env!("CARGO_PKG_VERSION"));
+const AUTHOR: &str = "@sfmolina";
+
+/// Tamaño por defecto de la tabla de transposición de la búsqueda, en MB
+const DEFAULT_HASH: usize = 16;
+const MAX_HASH: usize = 1024;
+
+/// Casillas libres por defecto a partir de las que se usa el solucionador exacto.
+/// Con 0 no se usa.
+const DEFAULT_TABLEBASE: usize = 18;
+
+/// Límite de nodos del solucionador; si lo supera se busca con normalidad
+const TABLEBASE_NODES: u64 = 2_000_000;
+
+/// Tamaño de la tabla del solucionador, en MB
+const TABLEBASE_MEGABYTES: usize = 16;
+
+/// Ninguna partida dura más jugadas que casillas tiene el tablero
+const MAX_DEPTH: u8 = 81;
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// DATA STRUCTURES
+
+
+/// Tablas que conserva el motor entre búsquedas
+struct Tables {
+    tt: TranspositionTable,
+    solver: Solver,
+}
+
+
+/// Búsqueda en curso en otro hilo. Al terminar devuelve las tablas.
+struct Running {
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+    handle: JoinHandle<Tables>,
+}
+
+
+struct Engine {
+    game: Game,
+    tables: Option<Tables>,
+    running: Option<Running>,
+}
+
+
+/// Informa de cada iteración y corta la búsqueda con `stop` o al acabarse el tiempo
+struct Reporter {
+    stop: Arc<AtomicBool>,
+    start: Instant,
+    movetime: Option<Duration>,
+    turn: Player,
+}
+
+impl SearchObserver for Reporter {
+
+    fn should_stop(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+            || self.movetime.is_some_and(|movetime| self.start.elapsed() >= movetime)
+    }
+
+    fn on_iteration(&mut self, result: &SearchResult) {
+        println!("{}", format_info(result, self.turn, self.start.elapsed().as_millis()));
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// ENGINE
+
+
+impl Engine {
+
+    fn new() -> Self {
+        Self {
+            game: Game::new(),
+            tables: Some(Tables {
+                tt: TranspositionTable::new(DEFAULT_HASH),
+                solver: Solver::new(DEFAULT_TABLEBASE, TABLEBASE_NODES, TranspositionTable::new(TABLEBASE_MEGABYTES)),
+            }),
+            running: None,
+        }
+    }
+
+    /// Atiende una orden. Devuelve false si hay que salir.
+    fn handle(&mut self, command: Command) -> bool {
+        match command {
+            Command::Uci => {
+                println!("id name {}", NAME);
+                println!("id author {}", AUTHOR);
+                println!("option name Hash type spin default {} min 1 max {}", DEFAULT_HASH, MAX_HASH);
+                println!("option name Tablebase type spin default {} min 0 max 81", DEFAULT_TABLEBASE);
+                println!("uciok");
+            },
+            Command::IsReady => println!("readyok"),
+            Command::NewGame => {
+                let tables = self.wait();
+                tables.tt.clear();
+                tables.solver.clear();
+                self.game = Game::new();
+            },
+            Command::SetOption { name, value } => self.set_option(&name, &value),
+            Command::Position(moves) => {
+                self.wait();
+                match Game::from_moves(&moves) {
+                    Some(game) => self.game = game,
+                    None => println!("info string illegal move sequence"),
+                }
+            },
+            Command::Go(params) => self.go(params),
+            Command::Stop => {
+                self.stop();
+            },
+            Command::Perft(depth) => {
+                self.wait();
+                println!("perft {} {}", depth, self.game.clone().perft(depth));
+            },
+            Command::Display => println!("{}", self.game),
+            Command::Quit => {
+                self.stop();
+                return false;
+            },
+        }
+        true
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) {
+        let tables = self.wait();
+
+        match (name.to_ascii_lowercase().as_str(), value.parse::<usize>()) {
+            ("hash", Ok(megabytes)) => {
+                tables.tt = TranspositionTable::new(megabytes.clamp(1, MAX_HASH));
+            },
+            ("tablebase", Ok(empty)) => tables.solver.max_empty = empty.min(81),
+            _ => println!("info string unknown option or value `{}` `{}`", name, value),
+        }
+    }
+
+    /// Empieza a buscar en otro hilo; `bestmove` se escribe al terminar
+    fn go(&mut self, params: GoParams) {
+        self.wait();
+        let mut tables = self.tables.take().expect("tables are back after waiting");
+
+        let game = self.game.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut reporter = Reporter {
+            stop: stop.clone(),
+            start: Instant::now(),
+            movetime: params.movetime.map(Duration::from_millis),
+            turn: game.turn().unwrap_or(Player::First),
+        };
+
+        let handle = thread::spawn(move || {
+            // El solucionador también se corta con `stop` o al acabarse el tiempo,
+            // y entonces se busca con normalidad en lo que queda. Con una profundidad
+            // o unos nodos fijos no se usa: no los respetaría.
+            let fixed = params.depth.is_some() || params.nodes.is_some();
+            let solution = if fixed { None } else { tables.solver.solve_observed(&game, &reporter) };
+
+            let best = match solution {
+                Some(solution) => {
+                    let result = SearchResult {
+                        score: solution.value.score(reporter.turn),
+                        depth: game.empty_cells() as u8,
+                        pv: solution.best.into_iter().collect(),
+                        nodes: solution.nodes,
+                    };
+                    reporter.on_iteration(&result);
+                    println!("info string tablebase {}", solution.value);
+                    solution.best
+                },
+                None => search_observed(&game, limits(&params), &mut tables.tt, &mut reporter).best_move(),
+            };
+
+            // Con `infinite` no se responde hasta recibir `stop`
+            while params.infinite && !reporter.stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            println!("{}", format_bestmove(best));
+            tables
+        });
+
+        self.running = Some(Running { stop, infinite: params.infinite, handle });
+    }
+
+    /// Detiene la búsqueda en curso, si la hay, y recupera las tablas
+    fn stop(&mut self) -> &mut Tables {
+        self.join(true)
+    }
+
+    /// Espera a que termine la búsqueda en curso, si la hay, y recupera las tablas.
+    /// Las búsquedas infinitas se detienen.
+    fn wait(&mut self) -> &mut Tables {
+        self.join(false)
+    }
+
+    fn join(&mut self, stop: bool) -> &mut Tables {
+        if let Some(running) = self.running.take() {
+            if stop || running.infinite {
+                running.stop.store(true, Ordering::Relaxed);
+            }
+            self.tables = Some(running.handle.join().expect("search thread panicked"));
+        }
+        self.tables.as_mut().expect("tables are only missing while searching")
+    }
+}
+
+
+/// Límites de la búsqueda. Sin parámetros se usan los de siempre;
+/// con alguno, lo que no se indica no tiene límite.
+fn limits(params: &GoParams) -> SearchLimits {
+    if *params == GoParams::default() {
+        return SearchLimits::default();
+    }
+
+    SearchLimits {
+        depth: params.depth.unwrap_or(MAX_DEPTH).min(MAX_DEPTH),
+        nodes: params.nodes.unwrap_or(u64::MAX),
+    }
+}
+
+
+
+//-------------------------------------------------------------------
+//-------------------------------------------------------------------
+// MAIN
+
+
+fn main() -> ExitCode {
+
+    // Los pesos de la evaluación se pueden dar como argumento
+    if let Some(path) = std::env::args().nth(1) {
+        match Weights::load(&path) {
+            Ok(weights) => set_weights(weights).expect("weights are set once at startup"),
+            Err(err) => {
+                eprintln!("could not read {}: {}", path, err);
+                return ExitCode::FAILURE;
+            },
+        }
+    }
+
+    let mut engine = Engine::new();
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match Command::parse(&line) {
+            Ok(command) => {
+                if !engine.handle(command) {
+                    return ExitCode::SUCCESS;
+                }
+            },
+            Err(err) => println!("info string {}", err),
+        }
+    }
+
+    // Sin más órdenes se deja terminar la búsqueda pendiente
+    engine.wait();
+    ExitCode::SUCCESS
+}